@@ -20,12 +20,21 @@ trash-folder-icon = Trash folder icon
 icon-size-and-spacing = Icon size and spacing
 icon-size = Icon size
 grid-spacing = Grid spacing
+stacks = Stacks
+stacks-off = Off
+stacks-by-kind = By kind
+stacks-by-date = By date
 
 # List view
 name = Name
 modified = Modified
 trashed-on = Trashed
 size = Size
+title = Title
+artist = Artist
+album = Album
+duration = Duration
+date-taken = Date taken
 
 # Progress footer
 details = Details
@@ -51,6 +60,10 @@ extract-password-required = Password required
 extract-to = Extract To...
 extract-to-title = Extract to folder
 
+## Restore To Dialog
+restore-to-title = Restore to folder
+restore-here = Restore
+
 ## Empty Trash Dialog
 empty-trash = Empty trash
 empty-trash-title = Empty trash?
@@ -95,6 +108,22 @@ selected-items = the {$items} selected items
 permanently-delete-question = Permanently delete?
 delete = Delete
 permanently-delete-warning = {$target} will be permanently deleted. This action can't be undone.
+permanently-delete-no-trash-warning = The drive containing {$target} has no trash, so {$items ->
+        [one] it
+        *[other] they
+    } can't be moved to the trash. Deleting will remove {$items ->
+        [one] it
+        *[other] them
+    } permanently. This action can't be undone.
+
+## Secure delete Dialog
+secure-delete-question = Securely delete?
+secure-delete = Securely delete
+secure-delete-warning = The contents of {$target} will be overwritten {$passes ->
+        [one] once
+        *[other] {$passes} times
+    } and then deleted. This action can't be undone.
+secure-delete-storage-warning = Overwriting is less reliable on SSDs, flash drives and copy-on-write filesystems like Btrfs, which may keep old copies of the data elsewhere on the drive.
 
 ## Rename Dialog
 rename-file = Rename file
@@ -138,6 +167,31 @@ read-write = Read and write
 ### Mode 7
 read-write-execute = Read, write, and execute
 
+## Properties Dialog
+apply = Apply
+properties-title = Properties of "{$name}"
+properties-title-items = Properties of {$items} items
+properties-details = Details
+properties-inode = Inode
+properties-device = Device
+properties-links = Links
+properties-ownership = Ownership
+properties-permissions = Permissions
+properties-setuid = Set user ID
+properties-setgid = Set group ID
+properties-sticky = Sticky
+properties-acl = Access control list
+properties-acl-user = User "{$name}"
+properties-acl-group = Group "{$name}"
+properties-acl-placeholder = user:name or group:name
+properties-xattrs = Extended attributes
+properties-xattr-name = Name
+properties-xattr-value = Value
+properties-xattr-binary = Not text, shown as hexadecimal and cannot be edited
+properties-recursive = Apply to enclosed items
+properties-unknown-user = Unknown user "{$name}"
+properties-unknown-group = Unknown group "{$name}"
+
 ## Favorite Path Error Dialog
 favorite-path-error = Error opening directory
 favorite-path-error-description =
@@ -148,6 +202,14 @@ favorite-path-error-description =
 remove = Remove
 keep = Keep
 
+## Verify Failed Dialog
+verify-failed = Copies did not match
+verify-failed-description = {$items} {$items ->
+        [one] file was
+        *[other] files were
+    } not copied correctly and left in place. The incorrect copies were removed:
+verify-failed-cli = verification failed, not copied correctly: {$path}
+
 # Context Pages
 
 ## About
@@ -199,6 +261,14 @@ compressed = Compressed {$items} {$items ->
         [one] item
         *[other] items
     } from "{$from}" to "{$to}"
+computing-checksums = Computing checksums of {$items} {$items ->
+        [one] item
+        *[other] items
+    } ({$progress})...
+computed-checksums = Computed checksums of {$items} {$items ->
+        [one] item
+        *[other] items
+    }
 copy_noun = Copy
 creating = Creating "{$name}" in "{$parent}"
 created = Created "{$name}" in "{$parent}"
@@ -232,6 +302,14 @@ setting-executable-and-launching = Setting "{$name}" as executable and launching
 set-executable-and-launched = Set "{$name}" as executable and launched
 setting-permissions = Setting permissions for "{$name}" to {$mode}
 set-permissions = Set permissions for "{$name}" to {$mode}
+setting-properties = Setting properties of {$items} {$items ->
+        [one] item
+        *[other] items
+    } in "{$from}" ({$progress})...
+set-properties = Set properties of {$items} {$items ->
+        [one] item
+        *[other] items
+    } in "{$from}"
 moving = Moving {$items} {$items ->
         [one] item
         *[other] items
@@ -266,7 +344,35 @@ restored = Restored {$items} {$items ->
         [one] item
         *[other] items
     } from {trash}
+restoring-to = Restoring {$items} {$items ->
+        [one] item
+        *[other] items
+    } from {trash} to "{$to}" ({$progress})...
+restored-to = Restored {$items} {$items ->
+        [one] item
+        *[other] items
+    } from {trash} to "{$to}"
+rotating = Rotating {$items} {$items ->
+        [one] image
+        *[other] images
+    }
+rotated = Rotated {$items} {$items ->
+        [one] image
+        *[other] images
+    }
+securely-deleting = Securely deleting {$items} {$items ->
+        [one] item
+        *[other] items
+    } ({$progress})...
+securely-deleted = Securely deleted {$items} {$items ->
+        [one] item
+        *[other] items
+    }
 unknown-folder = unknown folder
+operation-warnings = Completed with {$items} {$items ->
+        [one] warning
+        *[other] warnings
+    }: {$warning}
 
 ## Open with
 menu-open-with = Open with...
@@ -281,10 +387,117 @@ item-created = Created: {$created}
 item-modified = Modified: {$modified}
 item-accessed = Accessed: {$accessed}
 calculating = Calculating...
+pdf-page = Page {$page} of {$pages}
+pdf-page-unknown = Page {$page}
+text-encoding = Encoding: {$encoding}
+text-lines = Lines {$first}–{$last}
+media-title = Title: {$title}
+media-artist = Artist: {$artist}
+media-album = Album: {$album}
+media-duration = Duration: {$duration}
+media-codec = Codec: {$codec}
+media-bitrate = Bitrate: {$bitrate} kbps
+photo-camera = Camera: {$camera}
+photo-lens = Lens: {$lens}
+photo-exposure = Exposure: {$exposure}
+photo-date-taken = Taken: {$date}
+photo-location = Location: {$latitude}, {$longitude}
+
+## Checksums
+checksums = Checksums
+checksum-expected = Paste an expected checksum
+checksum-matches = Matches {$kind} checksum
+checksum-mismatch = Does not match the expected checksum
+checksum-file-matches = Matches {$kind} checksum from checksum file
+checksum-file-mismatch = Does not match {$kind} checksum from checksum file
+checksum-write = Write checksum files
+checksum-written = Wrote {$items} checksum {$items ->
+        [one] file
+        *[other] files
+    }
+checksum-write-failed = Failed to write checksum file: {$error}
+checksum-replace-title = Replace checksum files?
+checksum-replace-warning = {$items ->
+        [one] A checksum file already exists and will be overwritten.
+        *[other] {$items} checksum files already exist and will be overwritten.
+    }
+
+## Compare
+compare = Compare
+comparing = Comparing...
+compare-failed = Failed to compare: {$error}
+compare-mismatch = Files can only be compared with files, and folders with folders
+files-identical = The files are identical
+files-differ = The files are different
+folders-identical = The folders have the same contents
+compare-only-left = Only on the left
+compare-only-right = Only on the right
+compare-kind = File on one side, folder on the other
+compare-size = Different size
+compare-content = Different contents
+compare-modified = Same contents, modified at different times
+copy-to-left = Copy to the left
+copy-to-right = Copy to the right
+compare-copy-failed = Failed to create the folder {$folder}: {$error}
 
 ## Settings
 settings = Settings
 single-click = Single click to open
+hover-playback = Play videos on hover
+hover-playback-description = Play videos without sound while the pointer is over them in the grid view
+recents-limit = Recent files shown
+restore-session = Restore windows on launch
+restore-session-description = Reopen closed windows with their tabs when started without any folders to open
+folder-previews = Folder previews
+folder-previews-description = Show images from inside folders, or their custom folder picture, in the grid view
+preserve-metadata = Preserve file attributes
+preserve-metadata-description = Keep timestamps, permissions, extended attributes and, with administrator rights, ownership of copied files
+verify-copies = Verify copied files
+verify-copies-description = Read copied files back and compare checksums before moved files are removed
+secure-delete-passes = Secure delete passes
+secure-delete-passes-description = Times the contents of files are overwritten before they are securely deleted
+secure-delete-passes-count = {$passes ->
+        [one] 1 pass
+        *[other] {$passes} passes
+    }
+
+### Trash
+trash-by-origin = Group by original location
+trash-by-origin-description = List trashed items under the folder they were deleted from
+trash-purge = Empty automatically
+trash-purge-description = Permanently delete items that have been in the trash for longer than this
+trash-purge-never = Never
+trash-purge-days = After {$days} days
+
+### Thumbnail cache
+thumbnail-cache = Thumbnail cache
+thumbnail-cache-max-size = Maximum cache size
+thumbnail-cache-usage = {$thumbnails} {$thumbnails ->
+        [one] thumbnail
+        *[other] thumbnails
+    } using {$size}
+thumbnail-cache-clean = Remove unused thumbnails
+thumbnail-cache-clean-description = Remove thumbnails of deleted files and trim the cache to its maximum size
+thumbnail-cache-failed = Failed thumbnails
+thumbnail-cache-failed-description = {$count} {$count ->
+        [one] file
+        *[other] files
+    } will not be thumbnailed again until retried
+thumbnail-cache-cleaned = Removed {$count} {$count ->
+        [one] file
+        *[other] files
+    }, freeing {$size}
+clean-up = Clean up
+retry = Retry
+
+### Remote thumbnails
+remote-thumbnails = Thumbnails on network locations
+remote-thumbnails-none = No network locations are mounted
+remote-thumbnails-host = Server {$host}
+remote-thumbnails-host-local = Server {$host} on the local network
+remote-thumbnails-never = Never
+remote-thumbnails-local-network = Local network only
+remote-thumbnails-always = Always
 
 ### Appearance
 appearance = Appearance
@@ -302,24 +515,51 @@ type-to-search-enter-path = Enters the path to the directory or file
 add-to-sidebar = Add to sidebar
 compress = Compress
 delete-permanently = Delete permanently
+secure-delete-menu = Securely delete...
 eject = Eject
 extract-here = Extract
+properties = Properties
 new-file = New file...
 new-folder = New folder...
 open-in-terminal = Open in terminal
 move-to-trash = Move to trash
 restore-from-trash = Restore from trash
+restore-to = Restore to...
 remove-from-sidebar = Remove from sidebar
 sort-by-name = Sort by name
 sort-by-modified = Sort by modified
 sort-by-size = Sort by size
 sort-by-trashed = Sort by delete time
+sort-by-date-taken = Sort by date taken
 remove-from-recents = Remove from recents
 
 ## Desktop
 change-wallpaper = Change wallpaper...
 desktop-appearance = Desktop appearance...
 display-settings = Display settings...
+auto-arrange = Auto arrange
+align-to-grid = Align to grid
+stack-size = {$name} ({$size})
+stack-images = Images
+stack-documents = Documents
+stack-archives = Archives
+stack-installers = Installers
+stack-yesterday = Yesterday
+stack-this-week = This week
+stack-this-month = This month
+stack-older = Older
+
+# Recents
+all-applications = All applications
+all-types = All types
+recents-audio = Audio
+recents-video = Videos
+clear-older-than = Clear older than
+clear-older-than-day = A day
+clear-older-than-week = A week
+clear-older-than-month = A month
+clear-older-than-year = A year
+clear = Clear
 
 # Menu
 
@@ -329,7 +569,19 @@ new-tab = New tab
 new-window = New window
 reload-folder = Reload folder
 rename = Rename...
+menu-properties = Properties...
 close-tab = Close tab
+duplicate-tab = Duplicate tab
+pin-tab = Pin tab
+unpin-tab = Unpin tab
+move-tab-left = Move tab left
+move-tab-right = Move tab right
+move-tab-to-new-window = Move tab to new window
+new-tab-group = New group...
+create-tab-group = Create tab group
+tab-group-name = Group name
+remove-from-tab-group = Remove from group
+close-tab-group = Close group
 quit = Quit
 
 ## Edit
@@ -349,6 +601,12 @@ list-view = List view
 show-hidden-files = Show hidden files
 list-directories-first = List directories first
 gallery-preview = Gallery preview
+zoom-to-fit = Zoom to fit
+original-size = Original size
+rotate-left = Rotate left
+rotate-right = Rotate right
+start-slideshow = Start slideshow
+stop-slideshow = Stop slideshow
 menu-settings = Settings...
 menu-about = About COSMIC Files...
 
@@ -17,7 +17,7 @@ use cosmic::iced_winit::commands::overlap_notify::overlap_notify;
 use cosmic::{
     Application, ApplicationExt, Element,
     app::{self, Core, Task, context_drawer},
-    cosmic_config::{self, ConfigSet},
+    cosmic_config::{self, ConfigGet, ConfigSet},
     cosmic_theme, executor,
     iced::{
         self, Alignment, Event, Length, Rectangle, Size, Subscription,
@@ -68,11 +68,17 @@ use trash::TrashItem;
 #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
 use wayland_client::{Proxy, protocol::wl_output::WlOutput};
 
+#[cfg(unix)]
+use crate::properties::{PermissionsChange, Properties};
 use crate::{
     FxOrderMap,
-    clipboard::{ClipboardCopy, ClipboardKind, ClipboardPaste},
+    checksum::{self, ChecksumEntry, ChecksumKind, Digests},
+    cli::{Args, CliOperation, CliOperationKind},
+    clipboard::{ClipboardCopy, ClipboardKind, ClipboardPaste, TabDrag, TabDrop, wait_tab_drop},
+    compare::{self, Compare, Comparison, Difference, Side},
     config::{
-        AppTheme, Config, DesktopConfig, Favorite, IconSizes, TIME_CONFIG_ID, TabConfig,
+        AppTheme, Config, DesktopConfig, DesktopLayout, Favorite, IconSizes, RemoteThumbnails,
+        SessionLocation, SessionTab, SessionWindow, StackBy, TIME_CONFIG_ID, TabConfig, ThumbCfg,
         TimeConfig, TypeToSearch,
     },
     dialog::{Dialog, DialogKind, DialogMessage, DialogResult},
@@ -87,10 +93,16 @@ use crate::{
         Controller, Operation, OperationError, OperationErrorType, OperationSelection,
         ReplaceResult,
     },
+    photo::Rotation,
+    recents::{self, RECENTS_LIMITS, RecentUse},
+    remote::{self, RemoteMount, RemoteThumbnailPolicy},
+    shred,
     spawn_detached::spawn_detached,
     tab::{
         self, HOVER_DURATION, HeadingOptions, ItemMetadata, Location, SORT_OPTION_FALLBACK, Tab,
     },
+    thumbnail_cacher::{self, CacheStats, CleanupStats},
+    trash_folder,
 };
 use crate::{
     config::State,
@@ -98,6 +110,9 @@ use crate::{
     zoom::{zoom_in_view, zoom_out_view, zoom_to_default},
 };
 
+/// Choices for the maximum thumbnail cache size
+const THUMBNAIL_CACHE_SIZES_MB: [u16; 7] = [128, 256, 512, 1024, 2048, 4096, 8192];
+
 static PERMANENT_DELETE_BUTTON_ID: LazyLock<widget::Id> =
     LazyLock::new(|| widget::Id::new("permanent-delete-button"));
 
@@ -132,20 +147,23 @@ pub struct Flags {
     pub state_handler: Option<cosmic_config::Config>,
     pub state: State,
     pub mode: Mode,
-    pub locations: Vec<Location>,
-    pub uris: Vec<url::Url>,
+    pub args: Args,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Action {
     About,
     AddToSidebar,
+    Checksums,
+    Compare,
     Compress,
     Copy,
     Cut,
     CosmicSettingsDesktop,
     CosmicSettingsDisplays,
     CosmicSettingsWallpaper,
+    DesktopAlignToGrid,
+    DesktopAutoArrange,
     DesktopViewOptions,
     Delete,
     EditHistory,
@@ -175,19 +193,30 @@ pub enum Action {
     Paste,
     PermanentlyDelete,
     Preview,
+    Properties,
     Reload,
     RemoveFromRecents,
     Rename,
     RestoreFromTrash,
+    RestoreTo,
     SearchActivate,
+    SecureDelete,
     SelectFirst,
     SelectLast,
     SelectAll,
     SetSort(HeadingOptions, bool),
     Settings,
     TabClose,
+    TabDuplicate,
+    TabGroupClose,
+    TabGroupNew,
+    TabGroupRemove,
+    TabMoveLeft,
+    TabMoveRight,
+    TabMoveToNewWindow,
     TabNew,
     TabNext,
+    TabPin,
     TabPrev,
     TabViewGrid,
     TabViewList,
@@ -207,6 +236,8 @@ impl Action {
         match self {
             Self::About => Message::ToggleContextPage(ContextPage::About),
             Self::AddToSidebar => Message::AddToSidebar(entity_opt),
+            Self::Checksums => Message::Checksums(entity_opt),
+            Self::Compare => Message::Compare(entity_opt),
             Self::Compress => Message::Compress(entity_opt),
             Self::Copy => Message::Copy(entity_opt),
             Self::Cut => Message::Cut(entity_opt),
@@ -214,6 +245,12 @@ impl Action {
             Self::CosmicSettingsDisplays => Message::CosmicSettings("displays"),
             Self::CosmicSettingsWallpaper => Message::CosmicSettings("wallpaper"),
             Self::Delete => Message::Delete(entity_opt),
+            Self::DesktopAlignToGrid => {
+                Message::TabMessage(entity_opt, tab::Message::DesktopAlignToGrid)
+            }
+            Self::DesktopAutoArrange => {
+                Message::TabMessage(entity_opt, tab::Message::DesktopAutoArrange)
+            }
             Self::DesktopViewOptions => Message::DesktopViewOptions,
             Self::EditHistory => Message::ToggleContextPage(ContextPage::EditHistory),
             Self::EditLocation => Message::TabMessage(entity_opt, tab::Message::EditLocationEnable),
@@ -244,11 +281,14 @@ impl Action {
             Self::Paste => Message::Paste(entity_opt),
             Self::PermanentlyDelete => Message::PermanentlyDelete(entity_opt),
             Self::Preview => Message::Preview(entity_opt),
+            Self::Properties => Message::Properties(entity_opt),
             Self::Reload => Message::TabMessage(entity_opt, tab::Message::Reload),
             Self::RemoveFromRecents => Message::RemoveFromRecents(entity_opt),
             Self::Rename => Message::Rename(entity_opt),
             Self::RestoreFromTrash => Message::RestoreFromTrash(entity_opt),
+            Self::RestoreTo => Message::RestoreTo(entity_opt),
             Self::SearchActivate => Message::SearchActivate,
+            Self::SecureDelete => Message::SecureDelete(entity_opt),
             Self::SelectAll => Message::TabMessage(entity_opt, tab::Message::SelectAll),
             Self::SelectFirst => Message::TabMessage(entity_opt, tab::Message::SelectFirst),
             Self::SelectLast => Message::TabMessage(entity_opt, tab::Message::SelectLast),
@@ -257,8 +297,16 @@ impl Action {
             }
             Self::Settings => Message::ToggleContextPage(ContextPage::Settings),
             Self::TabClose => Message::TabClose(entity_opt),
+            Self::TabDuplicate => Message::TabDuplicate(entity_opt),
+            Self::TabGroupClose => Message::TabGroupClose(entity_opt),
+            Self::TabGroupNew => Message::TabGroupNew(entity_opt),
+            Self::TabGroupRemove => Message::TabGroupRemove(entity_opt),
+            Self::TabMoveLeft => Message::TabMoveLeft(entity_opt),
+            Self::TabMoveRight => Message::TabMoveRight(entity_opt),
+            Self::TabMoveToNewWindow => Message::TabMoveToNewWindow(entity_opt),
             Self::TabNew => Message::TabNew,
             Self::TabNext => Message::TabNext,
+            Self::TabPin => Message::TabPin(entity_opt),
             Self::TabPrev => Message::TabPrev,
             Self::TabViewGrid => Message::TabView(entity_opt, tab::View::Grid),
             Self::TabViewList => Message::TabView(entity_opt, tab::View::List),
@@ -322,11 +370,36 @@ impl MenuAction for NavMenuAction {
     }
 }
 
+/// Action from the context menu of a tab in the tab bar
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TabMenuAction(Action, Entity);
+
+impl MenuAction for TabMenuAction {
+    type Message = Message;
+
+    fn message(&self) -> Message {
+        self.0.message(Some(self.1))
+    }
+}
+
 /// Messages that are used specifically by our [`App`].
 #[derive(Clone, Debug)]
 pub enum Message {
     AddToSidebar(Option<Entity>),
     AppTheme(AppTheme),
+    ChecksumCancel,
+    ChecksumCopy(String),
+    ChecksumExpected(String),
+    ChecksumResult(PathBuf, Result<Digests, String>),
+    Checksums(Option<Entity>),
+    ChecksumSiblings(Vec<(PathBuf, ChecksumKind, String)>),
+    ChecksumWrite,
+    ChecksumWriteKind(ChecksumKind),
+    ChecksumWritten(usize, Option<String>),
+    Compare(Option<Entity>),
+    CompareCopy(window::Id, PathBuf, Side),
+    CompareCopyFolder(window::Id, PathBuf, Side, Result<(), String>),
+    CompareResult(window::Id, Result<Comparison, String>),
     CloseToast(widget::ToastId),
     Compress(Option<Entity>),
     Config(Config),
@@ -393,25 +466,57 @@ pub enum Message {
     PendingPauseAll(bool),
     PermanentlyDelete(Option<Entity>),
     Preview(Option<Entity>),
+    Properties(Option<Entity>),
+    #[cfg(unix)]
+    PropertiesLoaded(Box<Properties>),
     RescanRecents,
     RescanTrash,
     RemoveFromRecents(Option<Entity>),
+    RemoteMounts(Vec<RemoteMount>),
+    RemoteThumbnails(String, RemoteThumbnails),
     Rename(Option<Entity>),
     ReplaceResult(ReplaceResult),
     RestoreFromTrash(Option<Entity>),
+    RestoreTo(Option<Entity>),
+    RestoreToResult(DialogResult),
+    SaveDesktopLayouts,
     SaveSortNames,
     ScrollTab(i16),
     SearchActivate,
     SearchClear,
     SearchInput(String),
+    SecureDelete(Option<Entity>),
+    SetFolderPreviews(bool),
+    SetPreserveMetadata(bool),
+    SetRestoreSession(bool),
+    SetSecureDeletePasses(usize),
     SetShowDetails(bool),
     SetTypeToSearch(TypeToSearch),
+    SetVerifyCopies(bool),
     SystemThemeModeChange,
     Size(window::Id, Size),
+    ThumbnailCacheClean,
+    ThumbnailCacheCleaned(Result<CleanupStats, String>),
+    ThumbnailCacheClearFailed,
+    ThumbnailCacheMaxSize(usize),
+    ThumbnailCacheStats(Option<CacheStats>),
     TabActivate(Entity),
     TabNext,
     TabPrev,
     TabClose(Option<Entity>),
+    TabContext(Entity),
+    TabDragCancel,
+    TabDragFinish,
+    TabDragStart(Entity, u64),
+    TabDropConfirmed(Entity),
+    TabDuplicate(Option<Entity>),
+    TabGroupClose(Option<Entity>),
+    TabGroupNew(Option<Entity>),
+    TabGroupRemove(Option<Entity>),
+    TabMoveLeft(Option<Entity>),
+    TabMoveRight(Option<Entity>),
+    TabMoveToNewWindow(Option<Entity>),
+    TabPin(Option<Entity>),
     TabConfig(TabConfig),
     TabMessage(Option<Entity>, tab::Message),
     TabNew,
@@ -427,7 +532,10 @@ pub enum Message {
     ToggleContextPage(ContextPage),
     ToggleFoldersFirst,
     ToggleShowHidden,
+    TrashPurge,
+    TrashPurgeDays(usize),
     Undo(usize),
+    UndoRotate(widget::ToastId, Vec<PathBuf>, Rotation),
     UndoTrash(widget::ToastId, Arc<[PathBuf]>),
     UndoTrashStart(Vec<TrashItem>),
     WindowClose,
@@ -443,7 +551,7 @@ pub enum Message {
     DndExitNav,
     DndEnterTab(Entity),
     DndExitTab,
-    DndDropTab(Entity, Option<ClipboardPaste>, DndAction),
+    DndDropTab(Entity, Option<TabDrop>, DndAction),
     DndDropNav(Entity, Option<ClipboardPaste>, DndAction),
     Recents,
     #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
@@ -457,6 +565,7 @@ pub enum Message {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ContextPage {
     About,
+    Checksums,
     EditHistory,
     NetworkDrive,
     Preview(Option<Entity>, PreviewKind),
@@ -491,6 +600,13 @@ impl AsRef<str> for ArchiveType {
 
 #[derive(Clone, Debug)]
 pub enum DialogPage {
+    ChecksumReplace {
+        kind: ChecksumKind,
+        /// Files with the checksum to write for them
+        files: Box<[(PathBuf, String)]>,
+        /// Number of checksum files that already exist
+        existing: usize,
+    },
     Compress {
         paths: Box<[PathBuf]>,
         to: PathBuf,
@@ -500,6 +616,8 @@ pub enum DialogPage {
     },
     EmptyTrash,
     FailedOperation(u64),
+    /// Files whose copy did not match when read back
+    VerifyFailed(Box<[PathBuf]>),
     ExtractPassword {
         id: u64,
         password: String,
@@ -533,7 +651,11 @@ pub enum DialogPage {
     },
     PermanentlyDelete {
         paths: Box<[PathBuf]>,
+        /// The files are on a volume without a trash folder
+        no_trash: bool,
     },
+    #[cfg(unix)]
+    Properties(Box<Properties>),
     RenameItem {
         from: PathBuf,
         parent: PathBuf,
@@ -547,6 +669,9 @@ pub enum DialogPage {
         apply_to_all: bool,
         tx: mpsc::Sender<ReplaceResult>,
     },
+    SecureDelete {
+        paths: Box<[PathBuf]>,
+    },
     SetExecutableAndLaunch {
         path: PathBuf,
     },
@@ -554,6 +679,10 @@ pub enum DialogPage {
         path: PathBuf,
         entity: Entity,
     },
+    TabGroup {
+        entity: Entity,
+        name: String,
+    },
 }
 
 pub struct DialogPages {
@@ -635,8 +764,11 @@ pub enum WindowKind {
     ContextMenu(Entity, widget::Id),
     Desktop(Entity),
     DesktopViewOptions,
+    Compare(Box<Compare>),
     Dialogs(widget::Id),
     FileDialog(Option<Box<[PathBuf]>>),
+    /// File dialog choosing where trashed items are restored to
+    RestoreToDialog(Vec<TrashItem>),
     Preview(Option<Entity>, PreviewKind),
 }
 
@@ -689,6 +821,9 @@ pub struct App {
     state: State,
     mode: Mode,
     app_themes: Vec<String>,
+    checksum_entries: Vec<ChecksumEntry>,
+    checksum_expected: String,
+    checksum_write_kind: ChecksumKind,
     compio_tx: mpsc::Sender<Pin<Box<dyn Future<Output = ()> + Send>>>,
     context_page: ContextPage,
     dialog_pages: DialogPages,
@@ -698,6 +833,7 @@ pub struct App {
     mime_app_cache: MimeAppCache,
     modifiers: Modifiers,
     mounter_items: FxHashMap<MounterKey, MounterItems>,
+    must_save_desktop_layouts: bool,
     must_save_sort_names: bool,
     network_drive_connecting: Option<(MounterKey, String)>,
     network_drive_input: String,
@@ -706,10 +842,18 @@ pub struct App {
     #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
     overlap: FxHashMap<String, (window::Id, Rectangle)>,
     pending_operation_id: u64,
+    /// Operation given on the command line, the application exits when it is done
+    cli_operation_opt: Option<u64>,
     pending_operations: BTreeMap<u64, (Operation, Controller)>,
     progress_operations: BTreeSet<u64>,
     complete_operations: BTreeMap<u64, Operation>,
     failed_operations: BTreeMap<u64, (Operation, Controller, String)>,
+    /// Compare windows that copy operations were started from
+    compare_operations: BTreeMap<u64, window::Id>,
+    remote_mounts: Vec<RemoteMount>,
+    remote_thumbnail_names: Vec<String>,
+    /// Scroll positions of restored and duplicated tabs, applied once their items are loaded
+    restore_scroll: FxHashMap<Entity, scrollable::AbsoluteOffset>,
     scrollable_id: widget::Id,
     search_id: widget::Id,
     size: Option<Size>,
@@ -719,6 +863,9 @@ pub struct App {
     surface_ids: FxHashMap<WlOutput, WindowId>,
     #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
     surface_names: FxHashMap<WindowId, String>,
+    thumbnail_cache_busy: bool,
+    thumbnail_cache_sizes: Vec<String>,
+    thumbnail_cache_stats: Option<CacheStats>,
     toasts: widget::toaster::Toasts<Message>,
     watcher_opt: Option<(
         Debouncer<RecommendedWatcher, RecommendedCache>,
@@ -729,6 +876,11 @@ pub struct App {
     tab_dnd_hover: Option<(Entity, Instant)>,
     nav_drag_id: DragId,
     tab_drag_id: DragId,
+    /// Tab dragged from the tab bar of this window, with the ID of the drag
+    tab_drag_opt: Option<(Entity, u64)>,
+    /// Number of tabs dragged from this window, used as the ID of the next drag
+    tab_drags: u64,
+    tab_context_id: Entity,
     auto_scroll_speed: Option<i16>,
     file_dialog_opt: Option<Dialog<Message>>,
 }
@@ -745,6 +897,7 @@ impl App {
 
     fn open_file(&mut self, paths: &[impl AsRef<Path>]) -> Task<Message> {
         let mut tasks = Vec::new();
+        let mut recent_uses = Vec::new();
 
         // Associate all paths to its MIME type
         // This allows handling paths as groups if possible, such as launching a single video
@@ -801,14 +954,14 @@ impl App {
             }
 
             // Try mime apps, which should be faster than xdg-open
-            if self.launch_from_mime_cache(&mime, &paths) {
+            if self.launch_from_mime_cache(&mime, &mime, &paths, &mut recent_uses) {
                 continue;
             }
 
             // loop through subclasses if available
             if let Some(mime_sub_classes) = mime_icon::parent_mime_types(&mime) {
                 for sub_class in mime_sub_classes {
-                    if self.launch_from_mime_cache(&sub_class, &paths) {
+                    if self.launch_from_mime_cache(&sub_class, &mime, &paths, &mut recent_uses) {
                         continue 'outer;
                     }
                 }
@@ -818,12 +971,12 @@ impl App {
             for path in paths {
                 match open::that_detached(&path) {
                     Ok(()) => {
-                        let _ = recently_used_xbel::update_recently_used(
+                        recent_uses.push(RecentUse::new(
                             &path,
-                            Self::APP_ID.to_string(),
-                            "cosmic-files".to_string(),
-                            None,
-                        );
+                            &mime,
+                            Self::APP_ID,
+                            "cosmic-files",
+                        ));
                     }
                     Err(err) => {
                         log::warn!("failed to open {}: {}", path.display(), err);
@@ -832,9 +985,44 @@ impl App {
             }
         }
 
+        tasks.push(Self::add_recents(recent_uses));
         Task::batch(tasks)
     }
 
+    /// Add files to the recent files in the background, as this writes `recently-used.xbel`
+    fn add_recents(recent_uses: Vec<RecentUse>) -> Task<Message> {
+        if recent_uses.is_empty() {
+            return Task::none();
+        }
+        cosmic::task::future(async move {
+            if let Err(err) =
+                tokio::task::spawn_blocking(move || recents::add_recents(&recent_uses)).await
+            {
+                log::warn!("failed to add recent files: {err}");
+            }
+            Message::None
+        })
+    }
+
+    /// Load the properties dialog in the background, as reading ownership, ACLs and extended
+    /// attributes can be slow on network folders, and so can looking up user and group names
+    #[cfg(unix)]
+    fn load_properties(paths: Box<[PathBuf]>) -> Task<Message> {
+        cosmic::task::future(async move {
+            match tokio::task::spawn_blocking(move || Properties::new(paths)).await {
+                Ok(Ok(properties)) => Message::PropertiesLoaded(Box::new(properties)),
+                Ok(Err(err)) => {
+                    log::warn!("failed to load properties: {err}");
+                    Message::None
+                }
+                Err(err) => {
+                    log::warn!("failed to load properties: {err}");
+                    Message::None
+                }
+            }
+        })
+    }
+
     fn launch_desktop_entries(paths: &[impl AsRef<Path>]) {
         for path in paths.iter().map(AsRef::as_ref) {
             match freedesktop_entry_parser::parse_entry(path) {
@@ -868,7 +1056,15 @@ impl App {
         }
     }
 
-    fn launch_from_mime_cache<P>(&self, mime: &Mime, paths: &[P]) -> bool
+    /// Open `paths` of type `file_mime` with an app for `mime`, which is `file_mime` or one of
+    /// its parent types. Opened paths are added to `recent_uses`.
+    fn launch_from_mime_cache<P>(
+        &self,
+        mime: &Mime,
+        file_mime: &Mime,
+        paths: &[P],
+        recent_uses: &mut Vec<RecentUse>,
+    ) -> bool
     where
         P: std::fmt::Debug + AsRef<Path> + AsRef<std::ffi::OsStr>,
     {
@@ -878,19 +1074,25 @@ impl App {
             };
             let len = commands.len();
 
+            let mut launched = false;
             for (i, mut command) in commands.into_iter().enumerate() {
                 match spawn_detached(&mut command) {
                     Ok(()) => {
-                        for path in paths {
-                            let _ = recently_used_xbel::update_recently_used(
-                                &path.into(),
-                                Self::APP_ID.to_string(),
-                                "cosmic-files".to_string(),
-                                None,
-                            );
+                        // More than one command: each command opens one of the paths
+                        let opened = if len > 1 {
+                            paths.get(i..=i).unwrap_or_default()
+                        } else {
+                            paths
+                        };
+                        for path in opened {
+                            recent_uses.push(RecentUse::new(
+                                path.as_ref(),
+                                file_mime,
+                                &app.name,
+                                app.exec.as_deref().unwrap_or_default(),
+                            ));
                         }
-
-                        return true;
+                        launched = true;
                     }
                     Err(err) => {
                         // More than one command: The app doesn't support lists of paths so each command
@@ -906,6 +1108,9 @@ impl App {
                     }
                 }
             }
+            if launched {
+                return true;
+            }
         }
 
         // No app matched for mimes and paths
@@ -1068,6 +1273,17 @@ impl App {
             scrollable_id,
             window_id,
         );
+        tab.remote_thumbnails =
+            RemoteThumbnailPolicy::new(&self.remote_mounts, &self.config.remote_thumbnails);
+        if let Location::Desktop(_, display, _) = &location {
+            if let Some(layout) = self.state.desktop_layouts.get(display) {
+                if let Some((sort_name, sort_direction)) = layout.sort {
+                    tab.sort_name = sort_name;
+                    tab.sort_direction = sort_direction;
+                }
+                tab.desktop_layout = layout.clone();
+            }
+        }
         tab.mode = match self.mode {
             Mode::App => tab::Mode::App,
             Mode::Desktop => {
@@ -1115,15 +1331,291 @@ impl App {
         .1
     }
 
-    // This wrapper ensures that local folders use trash and remote folders permanently delete with a dialog
+    /// Open `location` in a new cosmic-files window, returning true if it was started
+    fn open_in_new_window(location: &Location) -> bool {
+        let exe = match env::current_exe() {
+            Ok(exe) => exe,
+            Err(err) => {
+                log::error!("failed to get current executable path: {err}");
+                return false;
+            }
+        };
+        let mut command = process::Command::new(&exe);
+        match location {
+            Location::Path(path) | Location::Search(path, ..) => {
+                command.arg(path);
+            }
+            Location::Trash => {
+                command.arg("--trash");
+            }
+            Location::Network(uri, _, Some(_)) => {
+                command.arg(uri);
+            }
+            Location::Network(..) => {
+                command.arg("--network");
+            }
+            Location::Recents => {
+                command.arg("--recents");
+            }
+            _ => {
+                log::error!("unsupported location for open in new window: {location:?}");
+                return false;
+            }
+        }
+        match command.spawn() {
+            Ok(_child) => true,
+            Err(err) => {
+                log::error!("failed to execute {}: {}", exe.display(), err);
+                false
+            }
+        }
+    }
+
+    fn close_tab(&mut self, entity: Entity) -> Task<Message> {
+        let mut tasks = Vec::with_capacity(2);
+
+        // If the last tab is closed, close the window
+        // Otherwise, activate closest item
+        if self.tab_model.len() == 1 {
+            tasks.push(Task::future(async move {
+                cosmic::action::app(Message::WindowClose)
+            }));
+        } else if let Some(position) = self.tab_model.position(entity) {
+            let new_position = if position > 0 {
+                position - 1
+            } else {
+                position + 1
+            };
+
+            if let Some(new_entity) = self.tab_model.entity_at(new_position) {
+                tasks.push(self.update(Message::TabActivate(new_entity)));
+            }
+        }
+
+        // Remove item
+        self.tab_model.remove(entity);
+
+        tasks.push(self.update_watcher());
+
+        Task::batch(tasks)
+    }
+
+    fn tab_pinned(&self, entity: Entity) -> bool {
+        self.tab_model
+            .data::<Tab>(entity)
+            .is_some_and(|tab| tab.pinned)
+    }
+
+    // Pinned tabs are kept in front of the others, in the order they were pinned, and have no
+    // close button
+    fn set_tab_pinned(&mut self, entity: Entity, pinned: bool) {
+        let Some(tab) = self.tab_model.data_mut::<Tab>(entity) else {
+            return;
+        };
+        tab.pinned = pinned;
+        // Groups are only made of tabs that are not pinned
+        if pinned && tab.group_opt.take().is_some() {
+            self.update_tab_text(entity);
+        }
+        let pinned_count = self
+            .tab_model
+            .iter()
+            .filter(|&entity| self.tab_pinned(entity))
+            .count() as u16;
+        if pinned {
+            self.tab_model.position_set(entity, pinned_count - 1);
+            self.tab_model
+                .icon_set(entity, icon::from_name("view-pin-symbolic").size(16).icon());
+        } else {
+            self.tab_model.position_set(entity, pinned_count);
+            self.tab_model.icon_remove(entity);
+        }
+        self.tab_model.closable_set(entity, !pinned);
+    }
+
+    fn tab_group(&self, entity: Entity) -> Option<&str> {
+        self.tab_model.data::<Tab>(entity)?.group_opt.as_deref()
+    }
+
+    fn set_tab_group(&mut self, entity: Entity, group_opt: Option<String>) {
+        let Some(tab) = self.tab_model.data_mut::<Tab>(entity) else {
+            return;
+        };
+        if tab.pinned || tab.group_opt == group_opt {
+            return;
+        }
+        tab.group_opt = group_opt;
+        self.update_tab_text(entity);
+        self.sort_tab_groups();
+    }
+
+    // The tabs of a group are kept together, at the position of the first tab of the group
+    fn sort_tab_groups(&mut self) {
+        let entities: Vec<_> = self
+            .tab_model
+            .iter()
+            .filter(|&entity| !self.tab_pinned(entity))
+            .collect();
+        let Some(first) = entities
+            .first()
+            .and_then(|&entity| self.tab_model.position(entity))
+        else {
+            return;
+        };
+        let mut sorted = Vec::with_capacity(entities.len());
+        for &entity in &entities {
+            if sorted.contains(&entity) {
+                continue;
+            }
+            match self.tab_group(entity) {
+                Some(group) => sorted.extend(
+                    entities
+                        .iter()
+                        .copied()
+                        .filter(|&other| self.tab_group(other) == Some(group)),
+                ),
+                None => sorted.push(entity),
+            }
+        }
+        for (position, entity) in (first..).zip(sorted) {
+            self.tab_model.position_set(entity, position);
+        }
+    }
+
+    /// Set the text of the tab in the tab bar to its title, after the name of its group
+    fn update_tab_text(&mut self, entity: Entity) {
+        let Some(tab) = self.tab_model.data::<Tab>(entity) else {
+            return;
+        };
+        let text = match &tab.group_opt {
+            Some(group) => format!("{group} · {}", tab.title()),
+            None => tab.title(),
+        };
+        self.tab_model.text_set(entity, text);
+    }
+
+    /// Move a tab dropped on `target`, either from this window or from another one
+    fn drop_tab(&mut self, target: Entity, drag: TabDrag) -> Task<Message> {
+        let Some(position) = self.tab_model.position(target) else {
+            return Task::none();
+        };
+        let pinned = self.tab_pinned(target);
+        let group_opt = self.tab_group(target).map(str::to_string);
+        if drag.pid == process::id() {
+            let Some((entity, _)) = self.tab_drag_opt.take().filter(|&(_, id)| id == drag.id)
+            else {
+                return Task::none();
+            };
+            // Tabs cannot be moved past the pinned tabs
+            if entity != target && self.tab_pinned(entity) == pinned {
+                self.tab_model.position_set(entity, position);
+                self.set_tab_group(entity, group_opt);
+                self.sort_tab_groups();
+            }
+            return Task::none();
+        }
+
+        // The other window only closes its tab once this window confirms that it opened it
+        let mut session_tab = drag.tab.clone();
+        session_tab.active = true;
+        session_tab.pinned = pinned;
+        session_tab.group = group_opt;
+        let Some((entity, task)) = self.open_session_tab(session_tab) else {
+            return Task::none();
+        };
+        drag.confirm_drop();
+        self.tab_model.position_set(entity, position + 1);
+        self.sort_tab_groups();
+        task
+    }
+
+    fn tab_context_menu(&self, entity: Entity) -> Option<Vec<widget::menu::Tree<Message>>> {
+        let position = self.tab_model.position(entity)?;
+        let pinned = self.tab_pinned(entity);
+        let grouped = self.tab_group(entity).is_some();
+        // Tabs can only be moved next to tabs that are also pinned or also not pinned
+        let can_move = |position_opt: Option<u16>| {
+            position_opt
+                .and_then(|position| self.tab_model.entity_at(position))
+                .is_some_and(|other| self.tab_pinned(other) == pinned)
+        };
+
+        let menu_item = |label, action, enabled| {
+            let action = TabMenuAction(action, entity);
+            if enabled {
+                widget::menu::Item::Button(label, None, action)
+            } else {
+                widget::menu::Item::ButtonDisabled(label, None, action)
+            }
+        };
+
+        let items = vec![
+            menu_item(
+                if pinned {
+                    fl!("unpin-tab")
+                } else {
+                    fl!("pin-tab")
+                },
+                Action::TabPin,
+                true,
+            ),
+            menu_item(fl!("duplicate-tab"), Action::TabDuplicate, true),
+            widget::menu::Item::Divider,
+            menu_item(
+                fl!("move-tab-left"),
+                Action::TabMoveLeft,
+                can_move(position.checked_sub(1)),
+            ),
+            menu_item(
+                fl!("move-tab-right"),
+                Action::TabMoveRight,
+                can_move(position.checked_add(1)),
+            ),
+            menu_item(
+                fl!("move-tab-to-new-window"),
+                Action::TabMoveToNewWindow,
+                true,
+            ),
+            widget::menu::Item::Divider,
+            menu_item(fl!("new-tab-group"), Action::TabGroupNew, !pinned),
+            menu_item(
+                fl!("remove-from-tab-group"),
+                Action::TabGroupRemove,
+                grouped,
+            ),
+            widget::menu::Item::Divider,
+            menu_item(fl!("close-tab"), Action::TabClose, !pinned),
+            menu_item(fl!("close-tab-group"), Action::TabGroupClose, grouped),
+        ];
+
+        Some(widget::menu::items(&HashMap::new(), items))
+    }
+
+    fn swap_tabs(&mut self, entity: Entity, position_opt: Option<u16>) {
+        if let Some(other) = position_opt.and_then(|position| self.tab_model.entity_at(position)) {
+            // Tabs cannot be moved past the pinned tabs
+            if self.tab_pinned(other) == self.tab_pinned(entity) {
+                self.tab_model.position_swap(entity, other);
+                // The moved tab joins the group it is moved into, or leaves its group
+                let group_opt = self.tab_group(other).map(str::to_string);
+                self.set_tab_group(entity, group_opt);
+            }
+        }
+    }
+
+    // This wrapper ensures that files on volumes with a trash folder use it, and other files
+    // permanently delete with a dialog
     fn delete(&mut self, paths: impl IntoIterator<Item = PathBuf>) -> Task<Message> {
         let mut dialog_paths = Vec::new();
         let mut trash_paths = Vec::new();
 
         for path in paths {
-            //TODO: is there a smarter way to check this? (like checking for trash folders)
-            let can_trash = match path.metadata() {
-                Ok(metadata) => matches!(tab::fs_kind(&metadata), tab::FsKind::Local),
+            let can_trash = match path.symlink_metadata() {
+                // GVFS mounts do not support trash folders
+                Ok(metadata) => {
+                    tab::fs_kind(&metadata) != tab::FsKind::Gvfs
+                        && trash_folder::target(&path).is_some()
+                }
                 Err(err) => {
                     log::warn!("failed to get metadata for {}: {}", path.display(), err);
                     false
@@ -1141,6 +1633,7 @@ impl App {
             tasks.push(self.update(Message::DialogPush(
                 DialogPage::PermanentlyDelete {
                     paths: dialog_paths.into_boxed_slice(),
+                    no_trash: true,
                 },
                 Some(PERMANENT_DELETE_BUTTON_ID.clone()),
             )));
@@ -1293,6 +1786,209 @@ impl App {
         })
     }
 
+    /// Reopen the tabs of the first window saved in the session. The next one is reopened by
+    /// starting another process, as each window is its own process. That process only restores
+    /// a window if restoring the session is still enabled, so disabling it stops the chain.
+    fn restore_session(&mut self) -> Task<Message> {
+        let _lock_opt = Self::lock_sessions();
+        let mut sessions = self.load_sessions();
+        if sessions.is_empty() {
+            return Task::none();
+        }
+        let session = sessions.remove(0);
+        // Restored windows are saved again when they close
+        self.store_sessions(sessions);
+        if !self.state.sessions.is_empty() {
+            // Started without arguments, the process restores the next window
+            match env::current_exe() {
+                Ok(exe) => {
+                    if let Err(err) = process::Command::new(&exe).spawn() {
+                        log::error!("failed to execute {}: {}", exe.display(), err);
+                    }
+                }
+                Err(err) => {
+                    log::error!("failed to get current executable path: {err}");
+                }
+            }
+        }
+
+        let tasks: Vec<_> = session
+            .tabs
+            .into_iter()
+            .filter_map(|session_tab| self.open_session_tab(session_tab))
+            .map(|(_, task)| task)
+            .collect();
+        // The active tab may have been skipped
+        if self.tab_model.active_data::<Tab>().is_none() {
+            if let Some(entity) = self.tab_model.entity_at(0) {
+                self.tab_model.activate(entity);
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    /// Open a tab saved in the session, or dragged from another window
+    fn open_session_tab(&mut self, session_tab: SessionTab) -> Option<(Entity, Task<Message>)> {
+        let location = session_tab.location()?;
+        // Skip folders that were removed or unmounted since
+        if location.path_opt().is_some_and(|path| !path.is_dir()) {
+            return None;
+        }
+        let selection_paths = (!session_tab.selected.is_empty()).then_some(session_tab.selected);
+        let (entity, task) = self.open_tab_entity(
+            location,
+            session_tab.active,
+            selection_paths,
+            self.scrollable_id.clone(),
+            None,
+        );
+        if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
+            tab.history = session_tab
+                .history
+                .iter()
+                .map(SessionLocation::location)
+                .collect();
+            tab.history_i = session_tab.history_i;
+            tab.config = TabConfig {
+                military_time: self.config.tab.military_time,
+                ..session_tab.config
+            };
+        }
+        if session_tab.pinned {
+            self.set_tab_pinned(entity, true);
+        } else if session_tab.group.is_some() {
+            self.set_tab_group(entity, session_tab.group);
+        }
+        if session_tab.scroll_y > 0 {
+            self.restore_scroll.insert(
+                entity,
+                scrollable::AbsoluteOffset {
+                    x: 0.0,
+                    y: session_tab.scroll_y as f32,
+                },
+            );
+        }
+        Some((entity, task))
+    }
+
+    /// Returns `None` if the tab only visited locations that are not saved in the session
+    fn session_tab(&self, entity: Entity) -> Option<SessionTab> {
+        let tab = self.tab_model.data::<Tab>(entity)?;
+        let mut history = Vec::with_capacity(tab.history.len());
+        let mut history_i = 0;
+        for (i, location) in tab.history.iter().enumerate() {
+            if let Some(session_location) = SessionLocation::new(location) {
+                if i <= tab.history_i {
+                    history_i = history.len();
+                }
+                history.push(session_location);
+            }
+        }
+        if history.is_empty() {
+            return None;
+        }
+        let selected = tab
+            .items_opt()
+            .into_iter()
+            .flatten()
+            .filter(|item| item.selected)
+            .filter_map(|item| item.path_opt().cloned())
+            .collect();
+        Some(SessionTab {
+            history,
+            history_i,
+            config: tab.config,
+            scroll_y: tab.scroll_opt.map_or(0, |offset| offset.y.max(0.0) as u32),
+            selected,
+            active: entity == self.tab_model.active(),
+            pinned: tab.pinned,
+            group: tab.group_opt.clone(),
+        })
+    }
+
+    /// Add the open tabs of this window to the session to reopen them on the next launch, or
+    /// forget all windows if restoring the session is disabled
+    fn save_session(&mut self) {
+        if !self.config.restore_session {
+            self.clear_sessions();
+            return;
+        }
+        let tabs: Vec<_> = self
+            .tab_model
+            .iter()
+            .filter_map(|entity| self.session_tab(entity))
+            .collect();
+        if tabs.is_empty() {
+            return;
+        }
+        // Other windows may have been saved since this one started
+        let _lock_opt = Self::lock_sessions();
+        let mut sessions = self.load_sessions();
+        sessions.push(SessionWindow { tabs });
+        self.store_sessions(sessions);
+    }
+
+    /// Forget all saved windows, so windows still waiting to be restored are not reopened
+    fn clear_sessions(&mut self) {
+        let _lock_opt = Self::lock_sessions();
+        self.store_sessions(Vec::new());
+    }
+
+    /// Lock the saved sessions until the returned file is dropped, as other windows read and
+    /// change them from their own processes
+    fn lock_sessions() -> Option<fs::File> {
+        let path = dirs::runtime_dir()
+            .or_else(dirs::state_dir)?
+            .join("cosmic-files-sessions.lock");
+        let file = match fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+        {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::warn!("failed to open {}: {}", path.display(), err);
+                return None;
+            }
+        };
+        #[cfg(unix)]
+        {
+            use std::os::fd::AsRawFd;
+
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                log::warn!(
+                    "failed to lock {}: {}",
+                    path.display(),
+                    io::Error::last_os_error()
+                );
+                return None;
+            }
+        }
+        Some(file)
+    }
+
+    /// Saved windows as currently stored, which other windows may have changed
+    fn load_sessions(&self) -> Vec<SessionWindow> {
+        match self.state_handler.as_ref() {
+            Some(state_handler) => state_handler
+                .get::<Vec<SessionWindow>>("sessions")
+                .unwrap_or_default(),
+            None => self.state.sessions.clone(),
+        }
+    }
+
+    fn store_sessions(&mut self, sessions: Vec<SessionWindow>) {
+        self.state.sessions = sessions;
+        if let Some(state_handler) = self.state_handler.as_ref() {
+            if let Err(err) =
+                state_handler.set::<&Vec<SessionWindow>>("sessions", &self.state.sessions)
+            {
+                log::warn!("Failed to save session: {err:?}");
+            }
+        }
+    }
+
     fn rescan_trash(&mut self) -> Task<Message> {
         let needs_reload: Box<[_]> = self
             .tab_model
@@ -1365,7 +2061,7 @@ impl App {
         term_opt: Option<String>,
         selection_paths: Option<Vec<PathBuf>>,
     ) -> Task<Message> {
-        let mut title_location_opt = None;
+        let mut changed_opt = None;
         if let Some(tab) = self.tab_model.data_mut::<Tab>(tab) {
             let location_opt = match term_opt {
                 Some(term) => tab.location.path_opt().map(|path| {
@@ -1386,11 +2082,11 @@ impl App {
             };
             if let Some((location, focus_search)) = location_opt {
                 tab.change_location(&location, None);
-                title_location_opt = Some((tab.title(), tab.location.clone(), focus_search));
+                changed_opt = Some((tab.location.clone(), focus_search));
             }
         }
-        if let Some((title, location, focus_search)) = title_location_opt {
-            self.tab_model.text_set(tab, title);
+        if let Some((location, focus_search)) = changed_opt {
+            self.update_tab_text(tab);
             return Task::batch([
                 self.update_title(),
                 self.update_watcher(),
@@ -1420,6 +2116,37 @@ impl App {
             })
     }
 
+    /// Name of a single file to delete, or the number of files, shown in delete dialogs
+    fn delete_target(paths: &[PathBuf]) -> String {
+        if paths.len() == 1 {
+            format!(
+                "\"{}\"",
+                paths[0].file_name().map_or_else(
+                    || paths[0].to_string_lossy(),
+                    std::ffi::OsStr::to_string_lossy
+                )
+            )
+        } else {
+            fl!("selected-items", items = paths.len())
+        }
+    }
+
+    fn selected_trash_items(&self, entity_opt: Option<Entity>) -> Vec<TrashItem> {
+        let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+        self.tab_model
+            .data::<Tab>(entity)
+            .and_then(Tab::items_opt)
+            .into_iter()
+            .flatten()
+            .filter(|item| item.selected)
+            .filter_map(|item| match &item.metadata {
+                ItemMetadata::Trash { entry, .. } => Some(entry.clone()),
+                //TODO: error on trying to restore non-trash file?
+                _ => None,
+            })
+            .collect()
+    }
+
     fn set_cut(&mut self, entity_opt: Option<Entity>) {
         let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
         if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
@@ -1429,6 +2156,7 @@ impl App {
 
     fn update_config(&mut self) -> Task<Message> {
         self.update_nav_model();
+        self.update_remote_thumbnails();
         // Tabs are collected first to placate the borrowck
         let tabs: Box<[_]> = self.tab_model.iter().collect();
         // Update main conf and each tab with the new config
@@ -1609,6 +2337,274 @@ impl App {
         Task::none()
     }
 
+    fn thumbnail_cache_max_size(&self) -> u64 {
+        u64::from(self.config.thumb_cfg.max_cache_mb.get()) * 1000 * 1000
+    }
+
+    /// Write a checksum file next to each file in the background
+    /// Look for expected checksums in the checksum files next to `paths` in the background, as
+    /// this reads several files for each path
+    fn find_sibling_checksums(paths: Vec<PathBuf>) -> Task<Message> {
+        cosmic::task::future(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                paths
+                    .into_iter()
+                    .filter_map(|path| {
+                        let (kind, checksum) = checksum::find_sibling_checksum(&path)?;
+                        Some((path, kind, checksum))
+                    })
+                    .collect()
+            })
+            .await;
+            match result {
+                Ok(siblings) => Message::ChecksumSiblings(siblings),
+                Err(err) => {
+                    log::warn!("failed to find checksum files: {err}");
+                    Message::None
+                }
+            }
+        })
+    }
+
+    fn write_checksum_files(kind: ChecksumKind, files: Box<[(PathBuf, String)]>) -> Task<Message> {
+        cosmic::task::future(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                let mut written = 0;
+                let mut error_opt = None;
+                for (path, checksum) in &files {
+                    match checksum::write_checksum_file(path, kind, checksum) {
+                        Ok(_) => written += 1,
+                        Err(err) => {
+                            log::warn!(
+                                "failed to write checksum file for {}: {}",
+                                path.display(),
+                                err
+                            );
+                            error_opt.get_or_insert(err.to_string());
+                        }
+                    }
+                }
+                (written, error_opt)
+            })
+            .await;
+            match result {
+                Ok((written, error_opt)) => Message::ChecksumWritten(written, error_opt),
+                Err(err) => Message::ChecksumWritten(0, Some(err.to_string())),
+            }
+        })
+    }
+
+    /// Trim the thumbnail cache to its maximum size in the background
+    fn thumbnail_cache_enforce(&self) -> Task<Message> {
+        let max_size = self.thumbnail_cache_max_size();
+        cosmic::task::future(async move {
+            match tokio::task::spawn_blocking(move || thumbnail_cacher::enforce_max_size(max_size))
+                .await
+            {
+                Ok(Ok(stats)) => {
+                    if stats.removed > 0 {
+                        log::info!(
+                            "removed {} thumbnails to free {} bytes",
+                            stats.removed,
+                            stats.freed
+                        );
+                    }
+                }
+                Ok(Err(err)) => {
+                    log::warn!("failed to trim thumbnail cache: {err}");
+                }
+                Err(err) => {
+                    log::warn!("failed to trim thumbnail cache: {err}");
+                }
+            }
+            Message::ThumbnailCacheStats(None)
+        })
+    }
+
+    /// Permanently delete items that were in the trash for longer than the configured days
+    fn trash_purge(&self) -> Task<Message> {
+        let Some(days) = self.config.trash_purge_days else {
+            return Task::none();
+        };
+        cosmic::task::future(async move {
+            match tokio::task::spawn_blocking(move || trash_folder::purge(days)).await {
+                Ok(Ok(count)) => {
+                    if count > 0 {
+                        log::info!("deleted {count} items trashed more than {days} days ago");
+                        return Message::RescanTrash;
+                    }
+                }
+                Ok(Err(err)) => {
+                    log::warn!("failed to delete old trash items: {err}");
+                }
+                Err(err) => {
+                    log::warn!("failed to delete old trash items: {err}");
+                }
+            }
+            Message::None
+        })
+    }
+
+    fn thumbnail_cache_stats(&self) -> Task<Message> {
+        cosmic::task::future(async move {
+            let stats = match tokio::task::spawn_blocking(thumbnail_cacher::cache_stats).await {
+                Ok(Ok(stats)) => Some(stats),
+                Ok(Err(err)) => {
+                    log::warn!("failed to read thumbnail cache: {err}");
+                    None
+                }
+                Err(err) => {
+                    log::warn!("failed to read thumbnail cache: {err}");
+                    None
+                }
+            };
+            Message::ThumbnailCacheStats(stats)
+        })
+    }
+
+    fn thumbnail_cache_settings(&self) -> widget::settings::Section<'_, Message> {
+        let usage = match &self.thumbnail_cache_stats {
+            Some(stats) => fl!(
+                "thumbnail-cache-usage",
+                thumbnails = stats.thumbnails,
+                size = tab::format_size(stats.size)
+            ),
+            None => fl!("calculating"),
+        };
+        let selected_size = THUMBNAIL_CACHE_SIZES_MB
+            .iter()
+            .position(|&mb| mb == self.config.thumb_cfg.max_cache_mb.get());
+        let fail_markers = self
+            .thumbnail_cache_stats
+            .map_or(0, |stats| stats.fail_markers);
+
+        let mut clean_button = widget::button::standard(fl!("clean-up"));
+        let mut retry_button = widget::button::standard(fl!("retry"));
+        if !self.thumbnail_cache_busy {
+            clean_button = clean_button.on_press(Message::ThumbnailCacheClean);
+            if fail_markers > 0 {
+                retry_button = retry_button.on_press(Message::ThumbnailCacheClearFailed);
+            }
+        }
+
+        widget::settings::section()
+            .title(fl!("thumbnail-cache"))
+            .add(
+                widget::settings::item::builder(fl!("thumbnail-cache-max-size"))
+                    .description(usage)
+                    .control(widget::dropdown(
+                        &self.thumbnail_cache_sizes,
+                        selected_size,
+                        Message::ThumbnailCacheMaxSize,
+                    )),
+            )
+            .add(
+                widget::settings::item::builder(fl!("thumbnail-cache-clean"))
+                    .description(fl!("thumbnail-cache-clean-description"))
+                    .control(clean_button),
+            )
+            .add(
+                widget::settings::item::builder(fl!("thumbnail-cache-failed"))
+                    .description(fl!(
+                        "thumbnail-cache-failed-description",
+                        count = fail_markers
+                    ))
+                    .control(retry_button),
+            )
+    }
+
+    /// List remote mounts in the background, as resolving their hosts may block
+    fn reload_remote_mounts(&self) -> Task<Message> {
+        cosmic::task::future(async move {
+            match tokio::task::spawn_blocking(remote::remote_mounts).await {
+                Ok(mounts) => Message::RemoteMounts(mounts),
+                Err(err) => {
+                    log::warn!("failed to list remote mounts: {err}");
+                    Message::RemoteMounts(Vec::new())
+                }
+            }
+        })
+    }
+
+    /// Apply the remote thumbnail policy and thumbnail limits to all tabs
+    fn update_remote_thumbnails(&mut self) {
+        let policy =
+            RemoteThumbnailPolicy::new(&self.remote_mounts, &self.config.remote_thumbnails);
+        let entities: Box<[_]> = self.tab_model.iter().collect();
+        for entity in entities {
+            if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
+                tab.thumb_config = self.config.thumb_cfg;
+                tab.remote_thumbnails = policy.clone();
+            }
+        }
+    }
+
+    fn trash_settings(&self) -> widget::settings::Section<'_, Message> {
+        let tab_config = self.config.tab;
+        let purge_selected = trash_folder::PURGE_DAYS
+            .iter()
+            .position(|&days| days == self.config.trash_purge_days);
+        widget::settings::section()
+            .title(fl!("trash"))
+            .add(
+                widget::settings::item::builder(fl!("trash-by-origin"))
+                    .description(fl!("trash-by-origin-description"))
+                    .toggler(tab_config.trash_by_origin, move |trash_by_origin| {
+                        Message::TabConfig(TabConfig {
+                            trash_by_origin,
+                            ..tab_config
+                        })
+                    }),
+            )
+            .add(
+                widget::settings::item::builder(fl!("trash-purge"))
+                    .description(fl!("trash-purge-description"))
+                    .control(widget::dropdown(
+                        trash_folder::PURGE_NAMES.as_slice(),
+                        purge_selected,
+                        Message::TrashPurgeDays,
+                    )),
+            )
+    }
+
+    fn remote_thumbnail_settings(&self) -> widget::settings::Section<'_, Message> {
+        let mut section = widget::settings::section().title(fl!("remote-thumbnails"));
+        if self.remote_mounts.is_empty() {
+            return section.add(widget::settings::item_row(vec![
+                widget::text::body(fl!("remote-thumbnails-none")).into(),
+            ]));
+        }
+        for mount in &self.remote_mounts {
+            let policy = self
+                .config
+                .remote_thumbnails
+                .get(&mount.source)
+                .copied()
+                .unwrap_or_default();
+            let selected = RemoteThumbnails::ALL.iter().position(|&x| x == policy);
+            let description = match &mount.host {
+                Some(host) if mount.local_network => {
+                    fl!("remote-thumbnails-host-local", host = host.as_str())
+                }
+                Some(host) => fl!("remote-thumbnails-host", host = host.as_str()),
+                None => mount.source.clone(),
+            };
+            let source = mount.source.clone();
+            section = section.add(
+                widget::settings::item::builder(mount.mount_point.display().to_string())
+                    .description(description)
+                    .control(widget::dropdown(
+                        &self.remote_thumbnail_names,
+                        selected,
+                        move |index| {
+                            Message::RemoteThumbnails(source.clone(), RemoteThumbnails::ALL[index])
+                        },
+                    )),
+            );
+        }
+        section
+    }
+
     fn update_title(&mut self) -> Task<Message> {
         let window_title = match self.tab_model.text(self.tab_model.active()) {
             Some(tab_title) => format!("{tab_title} — {}", fl!("cosmic-files")),
@@ -1626,10 +2622,14 @@ impl App {
             let new_paths: FxHashSet<_> = self
                 .tab_model
                 .iter()
-                .filter_map(|entity| {
-                    let tab = self.tab_model.data::<Tab>(entity)?;
-                    tab.location.path_opt().cloned()
+                .filter_map(|entity| self.tab_model.data::<Tab>(entity))
+                .flat_map(|tab| {
+                    tab.location
+                        .path_opt()
+                        .into_iter()
+                        .chain(tab.folder_preview_paths())
                 })
+                .cloned()
                 .collect();
 
             // Unwatch paths no longer used
@@ -1645,26 +2645,294 @@ impl App {
                     }
                 }
             }
-
-            // Watch new paths
-            for path in &new_paths {
-                if !old_paths.contains(path) {
-                    match watcher.watch(path, notify::RecursiveMode::NonRecursive) {
-                        Ok(()) => {
-                            log::debug!("watching {}", path.display());
-                        }
-                        Err(err) => {
-                            log::debug!("failed to watch {}: {}", path.display(), err);
-                        }
+
+            // Watch new paths
+            for path in &new_paths {
+                if !old_paths.contains(path) {
+                    match watcher.watch(path, notify::RecursiveMode::NonRecursive) {
+                        Ok(()) => {
+                            log::debug!("watching {}", path.display());
+                        }
+                        Err(err) => {
+                            log::debug!("failed to watch {}: {}", path.display(), err);
+                        }
+                    }
+                }
+            }
+
+            self.watcher_opt = Some((watcher, new_paths));
+        }
+
+        //TODO: should any of this run in a command?
+        Task::none()
+    }
+
+    fn checksums_running(&self) -> bool {
+        self.pending_operations
+            .values()
+            .any(|(op, _)| matches!(op, Operation::Checksum { .. }))
+    }
+
+    fn checksums(&self) -> Element<'_, Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_m, ..
+        } = theme::active().cosmic().spacing;
+
+        let mut children = Vec::with_capacity(self.checksum_entries.len() + 1);
+        for entry in &self.checksum_entries {
+            let name = entry.path.file_name().map_or_else(
+                || entry.path.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+            let mut section = widget::settings::section().title(name);
+            match &entry.result_opt {
+                None => {
+                    section = section.add(widget::text::body(fl!("calculating")));
+                }
+                Some(Err(err)) => {
+                    section = section.add(widget::text::body(err.clone()));
+                }
+                Some(Ok(digests)) => {
+                    let expected = self.checksum_expected.trim();
+                    if !expected.is_empty() {
+                        section =
+                            section.add(widget::text::body(match digests.matches(expected) {
+                                Some(kind) => fl!("checksum-matches", kind = kind.as_ref()),
+                                None => fl!("checksum-mismatch"),
+                            }));
+                    }
+                    if let Some((kind, checksum)) = &entry.expected_opt {
+                        let kind_name = kind.as_ref();
+                        section = section.add(widget::text::body(
+                            if digests.get(*kind).eq_ignore_ascii_case(checksum) {
+                                fl!("checksum-file-matches", kind = kind_name)
+                            } else {
+                                fl!("checksum-file-mismatch", kind = kind_name)
+                            },
+                        ));
+                    }
+                    for kind in ChecksumKind::ALL {
+                        let checksum = digests.get(kind);
+                        section = section.add(
+                            widget::row::with_children([
+                                widget::column::with_children([
+                                    widget::text::heading(kind.as_ref()).into(),
+                                    widget::text::caption(checksum).into(),
+                                ])
+                                .width(Length::Fill)
+                                .into(),
+                                widget::tooltip(
+                                    widget::button::icon(icon::from_name("edit-copy-symbolic"))
+                                        .on_press(Message::ChecksumCopy(checksum.to_string()))
+                                        .padding(8),
+                                    widget::text::body(fl!("copy")),
+                                    widget::tooltip::Position::Top,
+                                )
+                                .into(),
+                            ])
+                            .align_y(Alignment::Center)
+                            .spacing(space_xxs),
+                        );
+                    }
+                }
+            }
+            children.push(section.into());
+        }
+
+        if self.checksums_running() {
+            children.push(
+                widget::row::with_children([
+                    widget::horizontal_space().into(),
+                    widget::button::standard(fl!("cancel"))
+                        .on_press(Message::ChecksumCancel)
+                        .into(),
+                ])
+                .into(),
+            );
+        }
+
+        widget::column::with_children(children)
+            .spacing(space_m)
+            .into()
+    }
+
+    /// Compare two files or folders in the background
+    fn compare_task(id: WindowId, left: PathBuf, right: PathBuf) -> Task<Message> {
+        cosmic::task::future(async move {
+            let result = tokio::task::spawn_blocking(move || compare::compare(&left, &right))
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|result| result);
+            Message::CompareResult(id, result)
+        })
+    }
+
+    /// Compare the items of a compare window again after they were changed
+    fn refresh_compare(&mut self, id: WindowId) -> Task<Message> {
+        if let Some(window) = self.windows.get_mut(&id) {
+            if let WindowKind::Compare(compare) = &mut window.kind {
+                compare.result_opt = None;
+                return Self::compare_task(id, compare.left.clone(), compare.right.clone());
+            }
+        }
+        Task::none()
+    }
+
+    fn compare_view(&self, id: WindowId, compare: &Compare) -> Element<'_, Message> {
+        let cosmic_theme::Spacing {
+            space_xxs,
+            space_s,
+            space_m,
+            ..
+        } = theme::active().cosmic().spacing;
+
+        let mut column = widget::column::with_capacity(2)
+            .spacing(space_s)
+            .padding(space_m);
+        column = column.push(
+            widget::row::with_children([
+                widget::text::heading(compare.left.display().to_string())
+                    .width(Length::Fill)
+                    .into(),
+                widget::text::heading(compare.right.display().to_string())
+                    .width(Length::Fill)
+                    .into(),
+            ])
+            .spacing(space_s),
+        );
+
+        let comparison = match &compare.result_opt {
+            Some(Ok(comparison)) => comparison,
+            Some(Err(err)) => {
+                return column
+                    .push(widget::text::body(fl!(
+                        "compare-failed",
+                        error = err.as_str()
+                    )))
+                    .into();
+            }
+            None => {
+                return column.push(widget::text::body(fl!("comparing"))).into();
+            }
+        };
+
+        match comparison {
+            Comparison::Binary { identical } => {
+                column = column.push(widget::text::body(if *identical {
+                    fl!("files-identical")
+                } else {
+                    fl!("files-differ")
+                }));
+            }
+            Comparison::Text(lines) => {
+                if lines.iter().all(|line| !line.changed) {
+                    column = column.push(widget::text::body(fl!("files-identical")));
+                }
+                let cell = |side: &Option<(usize, String)>, changed: bool| {
+                    let content: Element<'_, Message> = match side {
+                        Some((number, text)) => widget::row::with_children([
+                            widget::text::caption(number.to_string())
+                                .font(cosmic::font::mono())
+                                .width(Length::Fixed(48.0))
+                                .into(),
+                            widget::text::body(text.as_str())
+                                .font(cosmic::font::mono())
+                                .into(),
+                        ])
+                        .spacing(space_xxs)
+                        .into(),
+                        None => widget::horizontal_space().into(),
+                    };
+                    widget::container(content)
+                        .width(Length::Fill)
+                        .padding([0, space_xxs])
+                        .style(move |theme| {
+                            if !changed {
+                                return widget::container::Style::default();
+                            }
+                            let mut bg = theme.cosmic().accent_color();
+                            bg.alpha = 0.2;
+                            widget::container::Style {
+                                background: Some(iced::Color::from(bg).into()),
+                                ..Default::default()
+                            }
+                        })
+                };
+                let mut rows = widget::column::with_capacity(lines.len());
+                for line in lines {
+                    rows = rows.push(
+                        widget::row::with_children([
+                            cell(&line.left, line.changed && line.left.is_some()).into(),
+                            cell(&line.right, line.changed && line.right.is_some()).into(),
+                        ])
+                        .spacing(space_s),
+                    );
+                }
+                column = column.push(rows);
+            }
+            Comparison::Folder(entries) => {
+                if entries.is_empty() {
+                    return column
+                        .push(widget::text::body(fl!("folders-identical")))
+                        .into();
+                }
+                let copy_button = |icon_name: &'static str,
+                                   tooltip: String,
+                                   entry: &compare::FolderEntry,
+                                   from: Side| {
+                    let mut button = widget::button::icon(widget::icon::from_name(icon_name));
+                    if entry.difference.can_copy_from(from) {
+                        button =
+                            button.on_press(Message::CompareCopy(id, entry.path.clone(), from));
+                    }
+                    widget::tooltip(
+                        button,
+                        widget::text::body(tooltip),
+                        widget::tooltip::Position::Bottom,
+                    )
+                };
+                let mut section = widget::settings::section();
+                for entry in entries {
+                    let description = match entry.difference {
+                        Difference::OnlyLeft => fl!("compare-only-left"),
+                        Difference::OnlyRight => fl!("compare-only-right"),
+                        Difference::Kind => fl!("compare-kind"),
+                        Difference::Size => fl!("compare-size"),
+                        Difference::Content => fl!("compare-content"),
+                        Difference::Modified => fl!("compare-modified"),
+                    };
+                    let mut name = entry.path.display().to_string();
+                    if entry.is_dir {
+                        name.push(std::path::MAIN_SEPARATOR);
                     }
+                    section = section.add(
+                        widget::settings::item::builder(name)
+                            .description(description)
+                            .control(
+                                widget::row::with_children([
+                                    copy_button(
+                                        "go-previous-symbolic",
+                                        fl!("copy-to-left"),
+                                        entry,
+                                        Side::Right,
+                                    )
+                                    .into(),
+                                    copy_button(
+                                        "go-next-symbolic",
+                                        fl!("copy-to-right"),
+                                        entry,
+                                        Side::Left,
+                                    )
+                                    .into(),
+                                ])
+                                .spacing(space_xxs),
+                            ),
+                    );
                 }
+                column = column.push(section);
             }
-
-            self.watcher_opt = Some((watcher, new_paths));
         }
-
-        //TODO: should any of this run in a command?
-        Task::none()
+        column.into()
     }
 
     fn network_drive(&self) -> Element<'_, Message> {
@@ -1704,7 +2972,7 @@ impl App {
         } = theme::active().cosmic().spacing;
         let config = self.config.desktop;
 
-        let mut column = widget::column::with_capacity(2);
+        let mut column = widget::column::with_capacity(3);
 
         let mut section = widget::settings::section().title(fl!("show-on-desktop"));
         section = section.add(
@@ -1742,6 +3010,21 @@ impl App {
         );
         column = column.push(section);
 
+        let mut section = widget::settings::section().title(fl!("stacks"));
+        for (stack_by, label) in [
+            (StackBy::Off, fl!("stacks-off")),
+            (StackBy::Kind, fl!("stacks-by-kind")),
+            (StackBy::Date, fl!("stacks-by-date")),
+        ] {
+            section = section.add(widget::radio(
+                widget::text::body(label),
+                stack_by,
+                Some(config.stack_by),
+                move |stack_by| Message::DesktopConfig(DesktopConfig { stack_by, ..config }),
+            ));
+        }
+        column = column.push(section);
+
         let mut section = widget::settings::section().title(fl!("icon-size-and-spacing"));
         let icon_size = config.icon_size;
         section = section.add(
@@ -1971,9 +3254,8 @@ impl App {
                     Message::SetTypeToSearch,
                 ))
                 .into(),
-            widget::settings::section()
-                .title(fl!("other"))
-                .add({
+            {
+                let mut section = widget::settings::section().title(fl!("other")).add({
                     widget::settings::item::builder(fl!("single-click")).toggler(
                         tab_config.single_click,
                         move |single_click| {
@@ -1983,8 +3265,80 @@ impl App {
                             })
                         },
                     )
-                })
-                .into(),
+                });
+                #[cfg(feature = "playback")]
+                {
+                    section = section.add(
+                        widget::settings::item::builder(fl!("hover-playback"))
+                            .description(fl!("hover-playback-description"))
+                            .toggler(tab_config.hover_playback, move |hover_playback| {
+                                Message::TabConfig(TabConfig {
+                                    hover_playback,
+                                    ..tab_config
+                                })
+                            }),
+                    );
+                }
+                let recents_limit_selected = RECENTS_LIMITS
+                    .iter()
+                    .position(|&limit| limit == tab_config.recents_limit);
+                section
+                    .add(
+                        widget::settings::item::builder(fl!("recents-limit")).control(
+                            widget::dropdown(
+                                recents::LIMIT_NAMES.as_slice(),
+                                recents_limit_selected,
+                                move |index| {
+                                    Message::TabConfig(TabConfig {
+                                        recents_limit: RECENTS_LIMITS
+                                            .get(index)
+                                            .copied()
+                                            .unwrap_or(tab_config.recents_limit),
+                                        ..tab_config
+                                    })
+                                },
+                            ),
+                        ),
+                    )
+                    .add(
+                        widget::settings::item::builder(fl!("restore-session"))
+                            .description(fl!("restore-session-description"))
+                            .toggler(self.config.restore_session, Message::SetRestoreSession),
+                    )
+                    .add(
+                        widget::settings::item::builder(fl!("folder-previews"))
+                            .description(fl!("folder-previews-description"))
+                            .toggler(
+                                self.config.thumb_cfg.folder_previews,
+                                Message::SetFolderPreviews,
+                            ),
+                    )
+                    .add(
+                        widget::settings::item::builder(fl!("preserve-metadata"))
+                            .description(fl!("preserve-metadata-description"))
+                            .toggler(self.config.preserve_metadata, Message::SetPreserveMetadata),
+                    )
+                    .add(
+                        widget::settings::item::builder(fl!("verify-copies"))
+                            .description(fl!("verify-copies-description"))
+                            .toggler(self.config.verify_copies, Message::SetVerifyCopies),
+                    )
+                    .add(
+                        widget::settings::item::builder(fl!("secure-delete-passes"))
+                            .description(fl!("secure-delete-passes-description"))
+                            .control(widget::dropdown(
+                                shred::PASSES_NAMES.as_slice(),
+                                shred::SHRED_PASSES
+                                    .iter()
+                                    .position(|&passes| passes == self.config.secure_delete_passes),
+                                Message::SetSecureDeletePasses,
+                            )),
+                    )
+                    .into()
+            },
+            self.trash_settings().into(),
+            self.thumbnail_cache_settings().into(),
+            self.remote_thumbnail_settings().into(),
         ])
         .into()
     }
@@ -2165,6 +3519,9 @@ impl Application for App {
             state: flags.state,
             mode: flags.mode,
             app_themes,
+            checksum_entries: Vec::new(),
+            checksum_expected: String::new(),
+            checksum_write_kind: ChecksumKind::Sha256,
             compio_tx,
             context_page: ContextPage::Preview(None, PreviewKind::Selected),
             dialog_pages: DialogPages::new(),
@@ -2174,6 +3531,7 @@ impl Application for App {
             mime_app_cache: MimeAppCache::new(),
             modifiers: Modifiers::empty(),
             mounter_items: FxHashMap::default(),
+            must_save_desktop_layouts: false,
             must_save_sort_names: false,
             network_drive_connecting: None,
             network_drive_input: String::new(),
@@ -2182,10 +3540,19 @@ impl Application for App {
             #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
             overlap: FxHashMap::default(),
             pending_operation_id: 0,
+            cli_operation_opt: None,
             pending_operations: BTreeMap::new(),
             progress_operations: BTreeSet::new(),
             complete_operations: BTreeMap::new(),
             failed_operations: BTreeMap::new(),
+            compare_operations: BTreeMap::new(),
+            remote_mounts: Vec::new(),
+            remote_thumbnail_names: vec![
+                fl!("remote-thumbnails-never"),
+                fl!("remote-thumbnails-local-network"),
+                fl!("remote-thumbnails-always"),
+            ],
+            restore_scroll: FxHashMap::default(),
             scrollable_id: widget::Id::new("File Scrollable"),
             search_id: widget::Id::new("File Search"),
             size: None,
@@ -2193,6 +3560,12 @@ impl Application for App {
             surface_ids: FxHashMap::default(),
             #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
             surface_names: FxHashMap::default(),
+            thumbnail_cache_busy: false,
+            thumbnail_cache_sizes: THUMBNAIL_CACHE_SIZES_MB
+                .iter()
+                .map(|&mb| tab::format_size(u64::from(mb) * 1000 * 1000))
+                .collect(),
+            thumbnail_cache_stats: None,
             toasts: widget::toaster::Toasts::new(Message::CloseToast),
             watcher_opt: None,
             windows: FxHashMap::default(),
@@ -2200,15 +3573,28 @@ impl Application for App {
             tab_dnd_hover: None,
             nav_drag_id: DragId::new(),
             tab_drag_id: DragId::new(),
+            tab_drag_opt: None,
+            tab_drags: 0,
+            tab_context_id: Entity::null(),
             auto_scroll_speed: None,
             file_dialog_opt: None,
             #[cfg(all(feature = "wayland", feature = "desktop-applet"))]
             layer_sizes: FxHashMap::default(),
         };
 
-        let mut commands = vec![app.update_config()];
+        let mut commands = vec![
+            app.update_config(),
+            app.thumbnail_cache_enforce(),
+            app.trash_purge(),
+            app.reload_remote_mounts(),
+        ];
+
+        let args = flags.args;
+        if args.is_empty() && matches!(app.mode, Mode::App) && app.config.restore_session {
+            commands.push(app.restore_session());
+        }
 
-        for location in flags.locations {
+        for location in args.locations {
             if let Some(path) = location.path_opt() {
                 if path.is_file() {
                     if let Some(parent) = path.parent() {
@@ -2223,7 +3609,25 @@ impl Application for App {
             }
             commands.push(app.open_tab(location, true, None));
         }
-        for location in flags.uris {
+        // Files to select or show the properties of are selected in a tab for each folder
+        let mut selections: Vec<(PathBuf, Vec<PathBuf>)> = Vec::new();
+        for path in args.selections.iter().chain(&args.properties) {
+            let Some(parent) = path.parent() else {
+                continue;
+            };
+            match selections.iter_mut().find(|(folder, _)| folder == parent) {
+                Some((_, paths)) => {
+                    if !paths.contains(path) {
+                        paths.push(path.clone());
+                    }
+                }
+                None => selections.push((parent.to_path_buf(), vec![path.clone()])),
+            }
+        }
+        for (folder, paths) in selections {
+            commands.push(app.open_tab(Location::Path(folder), true, Some(paths)));
+        }
+        for location in args.uris {
             if let Some(e) = app.nav_model.iter().find(|e| {
                 app.nav_model.data::<Location>(*e).is_some_and(
                     |l| matches!(l, Location::Network(uri, ..) if *uri == *location.as_str()),
@@ -2235,6 +3639,34 @@ impl Application for App {
             }
         }
 
+        if let Some(CliOperation { kind, paths, to }) = args.operation_opt {
+            if app.tab_model.entity_at(0).is_none() {
+                commands.push(app.open_tab(Location::Path(to.clone()), true, None));
+            }
+            app.cli_operation_opt = Some(app.pending_operation_id);
+            let operation = match kind {
+                CliOperationKind::Copy => Operation::Copy {
+                    paths,
+                    to,
+                    preserve: app.config.preserve_metadata,
+                    verify: app.config.verify_copies,
+                },
+                CliOperationKind::Move => Operation::Move {
+                    paths,
+                    to,
+                    cross_device_copy: false,
+                    preserve: app.config.preserve_metadata,
+                    verify: app.config.verify_copies,
+                },
+                CliOperationKind::Extract => Operation::Extract {
+                    paths: paths.into_boxed_slice(),
+                    to,
+                    password: None,
+                },
+            };
+            commands.push(app.operation(operation));
+        }
+
         if app.tab_model.entity_at(0).is_none() {
             if let Ok(current_dir) = env::current_dir() {
                 commands.push(app.open_tab(Location::Path(current_dir), true, None));
@@ -2243,6 +3675,34 @@ impl Application for App {
             }
         }
 
+        // View options from the command line only apply to this window
+        if args.view_opt.is_some() || args.show_hidden {
+            let entities: Vec<_> = app.tab_model.iter().collect();
+            for entity in entities {
+                let Some(tab) = app.tab_model.data_mut::<Tab>(entity) else {
+                    continue;
+                };
+                if let Some(view) = args.view_opt {
+                    tab.config.view = view;
+                }
+                if args.show_hidden && !tab.config.show_hidden {
+                    let config = TabConfig {
+                        show_hidden: true,
+                        ..tab.config
+                    };
+                    commands.push(app.update(Message::TabMessage(
+                        Some(entity),
+                        tab::Message::Config(config),
+                    )));
+                }
+            }
+        }
+
+        #[cfg(unix)]
+        if !args.properties.is_empty() {
+            commands.push(Self::load_properties(args.properties.into_boxed_slice()));
+        }
+
         (app, Task::batch(commands))
     }
 
@@ -2653,6 +4113,198 @@ impl Application for App {
                 let contents = ClipboardCopy::new(ClipboardKind::Cut { is_dnd: false }, paths);
                 return clipboard::write_data(contents);
             }
+            Message::ChecksumCancel => {
+                for (op, controller) in self.pending_operations.values() {
+                    if matches!(op, Operation::Checksum { .. }) {
+                        controller.cancel();
+                    }
+                }
+            }
+            Message::ChecksumCopy(checksum) => {
+                return clipboard::write(checksum);
+            }
+            Message::ChecksumExpected(expected) => {
+                self.checksum_expected = expected;
+            }
+            Message::ChecksumResult(path, result) => {
+                if let Some(entry) = self
+                    .checksum_entries
+                    .iter_mut()
+                    .find(|entry| entry.path == path)
+                {
+                    entry.result_opt = Some(result);
+                }
+            }
+            Message::Checksums(entity_opt) => {
+                let paths: Vec<_> = self
+                    .selected_paths(entity_opt)
+                    .filter(|path| !path.is_dir())
+                    .collect();
+                if !paths.is_empty() {
+                    // Cancel any previous calculation, its results would be discarded
+                    for (op, controller) in self.pending_operations.values() {
+                        if matches!(op, Operation::Checksum { .. }) {
+                            controller.cancel();
+                        }
+                    }
+                    self.checksum_entries = paths.iter().cloned().map(ChecksumEntry::new).collect();
+                    self.context_page = ContextPage::Checksums;
+                    self.set_show_context(true);
+                    return Task::batch([
+                        Self::find_sibling_checksums(paths.clone()),
+                        self.operation(Operation::Checksum { paths }),
+                    ]);
+                }
+            }
+            Message::ChecksumSiblings(siblings) => {
+                for (path, kind, checksum) in siblings {
+                    if let Some(entry) = self
+                        .checksum_entries
+                        .iter_mut()
+                        .find(|entry| entry.path == path)
+                    {
+                        entry.expected_opt = Some((kind, checksum));
+                    }
+                }
+            }
+            Message::ChecksumWrite => {
+                let kind = self.checksum_write_kind;
+                let files: Box<[_]> = self
+                    .checksum_entries
+                    .iter()
+                    .filter_map(|entry| match &entry.result_opt {
+                        Some(Ok(digests)) => {
+                            Some((entry.path.clone(), digests.get(kind).to_string()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let existing = files
+                    .iter()
+                    .filter(|(path, _)| {
+                        checksum::checksum_file_path(path, kind)
+                            .is_ok_and(|sum_path| sum_path.exists())
+                    })
+                    .count();
+                if existing > 0 {
+                    return self.push_dialog(
+                        DialogPage::ChecksumReplace {
+                            kind,
+                            files,
+                            existing,
+                        },
+                        None,
+                    );
+                }
+                return Self::write_checksum_files(kind, files);
+            }
+            Message::ChecksumWriteKind(kind) => {
+                self.checksum_write_kind = kind;
+            }
+            Message::ChecksumWritten(written, error_opt) => {
+                let text = match error_opt {
+                    Some(error) => fl!("checksum-write-failed", error = error.as_str()),
+                    None => fl!("checksum-written", items = written),
+                };
+                return self
+                    .toasts
+                    .push(widget::toaster::Toast::new(text))
+                    .map(cosmic::Action::App);
+            }
+            Message::Compare(entity_opt) => {
+                let paths: Vec<_> = self.selected_paths(entity_opt).collect();
+                if let [left, right] = paths.as_slice() {
+                    if left.is_dir() != right.is_dir() {
+                        return self
+                            .toasts
+                            .push(widget::toaster::Toast::new(fl!("compare-mismatch")))
+                            .map(cosmic::Action::App);
+                    }
+
+                    let mut settings = window::Settings {
+                        decorations: true,
+                        min_size: Some(Size::new(480.0, 240.0)),
+                        resizable: true,
+                        size: Size::new(960.0, 640.0),
+                        transparent: true,
+                        ..Default::default()
+                    };
+
+                    #[cfg(target_os = "linux")]
+                    {
+                        // Use the dialog ID to make it float
+                        settings.platform_specific.application_id =
+                            "com.system76.CosmicFilesDialog".to_string();
+                    }
+
+                    let (id, command) = window::open(settings);
+                    self.windows.insert(
+                        id,
+                        Window::new(WindowKind::Compare(Box::new(Compare::new(
+                            left.clone(),
+                            right.clone(),
+                        )))),
+                    );
+                    return Task::batch([
+                        command.map(|_id| cosmic::action::none()),
+                        Self::compare_task(id, left.clone(), right.clone()),
+                    ]);
+                }
+            }
+            Message::CompareCopy(id, path, from) => {
+                if let Some(WindowKind::Compare(compare)) =
+                    self.windows.get(&id).map(|window| &window.kind)
+                {
+                    let source = compare.root(from).join(&path);
+                    let target = compare.root(from.other()).join(&path);
+                    if let Some(to) = target.parent() {
+                        let to = to.to_path_buf();
+                        // Create the parent folders first if they are missing on the other side
+                        if !to.is_dir() {
+                            return cosmic::task::future(async move {
+                                let result =
+                                    tokio::task::spawn_blocking(move || fs::create_dir_all(&to))
+                                        .await
+                                        .map_err(|err| err.to_string())
+                                        .and_then(|result| result.map_err(|err| err.to_string()));
+                                Message::CompareCopyFolder(id, path, from, result)
+                            });
+                        }
+                        self.compare_operations
+                            .insert(self.pending_operation_id, id);
+                        return self.operation(Operation::Copy {
+                            paths: vec![source],
+                            to,
+                            preserve: self.config.preserve_metadata,
+                            verify: self.config.verify_copies,
+                        });
+                    }
+                }
+            }
+            Message::CompareCopyFolder(id, path, from, result) => match result {
+                Ok(()) => return self.update(Message::CompareCopy(id, path, from)),
+                Err(error) => {
+                    let folder = path
+                        .parent()
+                        .map(|parent| parent.display().to_string())
+                        .unwrap_or_default();
+                    return self
+                        .toasts
+                        .push(widget::toaster::Toast::new(fl!(
+                            "compare-copy-failed",
+                            folder = folder,
+                            error = error
+                        )))
+                        .map(cosmic::Action::App);
+                }
+            },
+            Message::CompareResult(id, result) => {
+                if let Some(window) = self.windows.get_mut(&id) {
+                    if let WindowKind::Compare(compare) = &mut window.kind {
+                        compare.result_opt = Some(result);
+                    }
+                }
+            }
             Message::CloseToast(id) => {
                 self.toasts.remove(id);
             }
@@ -2761,7 +4413,13 @@ impl Application for App {
                 }
             }
             Message::DialogCancel => {
-                if let Some((_page, task)) = self.dialog_pages.pop_front() {
+                if let Some((page, task)) = self.dialog_pages.pop_front() {
+                    if let DialogPage::ExtractPassword { id, .. } = page {
+                        if self.cli_operation_opt == Some(id) {
+                            eprintln!("cosmic-files: no password given to extract the archive");
+                            process::exit(1);
+                        }
+                    }
                     return task;
                 }
             }
@@ -2769,6 +4427,9 @@ impl Application for App {
                 if let Some((dialog_page, task)) = self.dialog_pages.pop_front() {
                     let mut tasks = vec![task];
                     match dialog_page {
+                        DialogPage::ChecksumReplace { kind, files, .. } => {
+                            tasks.push(Self::write_checksum_files(kind, files));
+                        }
                         DialogPage::Compress {
                             paths,
                             to,
@@ -2792,6 +4453,7 @@ impl Application for App {
                         DialogPage::FailedOperation(id) => {
                             log::warn!("TODO: retry operation {id}");
                         }
+                        DialogPage::VerifyFailed(_) => {}
                         DialogPage::ExtractPassword { id, password } => {
                             let (operation, _, _err) = self.failed_operations.get(&id).unwrap();
                             let new_op = match &operation {
@@ -2802,6 +4464,9 @@ impl Application for App {
                                 },
                                 _ => unreachable!(),
                             };
+                            if self.cli_operation_opt == Some(id) {
+                                self.cli_operation_opt = Some(self.pending_operation_id);
+                            }
                             tasks.push(self.operation(new_op));
                         }
                         DialogPage::MountError {
@@ -2855,12 +4520,12 @@ impl Application for App {
                                 {
                                     match spawn_detached(&mut command) {
                                         Ok(()) => {
-                                            let _ = recently_used_xbel::update_recently_used(
+                                            tasks.push(Self::add_recents(vec![RecentUse::new(
                                                 &path,
-                                                Self::APP_ID.to_string(),
-                                                "cosmic-files".to_string(),
-                                                None,
-                                            );
+                                                &mime,
+                                                &app.name,
+                                                app.exec.as_deref().unwrap_or_default(),
+                                            )]));
                                         }
                                         Err(err) => {
                                             log::warn!(
@@ -2880,9 +4545,23 @@ impl Application for App {
                                 }
                             }
                         }
-                        DialogPage::PermanentlyDelete { paths } => {
+                        DialogPage::PermanentlyDelete { paths, .. } => {
                             tasks.push(self.operation(Operation::PermanentlyDelete { paths }));
                         }
+                        #[cfg(unix)]
+                        DialogPage::Properties(properties) => match properties.change() {
+                            Ok(change) => {
+                                if !change.is_empty() {
+                                    tasks.push(self.operation(Operation::SetPermissions {
+                                        paths: properties.paths.into_vec(),
+                                        change,
+                                    }));
+                                }
+                            }
+                            Err(err) => {
+                                log::warn!("invalid properties: {err}");
+                            }
+                        },
                         DialogPage::RenameItem {
                             from, parent, name, ..
                         } => {
@@ -2892,6 +4571,12 @@ impl Application for App {
                         DialogPage::Replace { .. } => {
                             log::warn!("replace dialog should be completed with replace result");
                         }
+                        DialogPage::SecureDelete { paths } => {
+                            tasks.push(self.operation(Operation::SecureDelete {
+                                paths,
+                                passes: self.config.secure_delete_passes,
+                            }));
+                        }
                         DialogPage::SetExecutableAndLaunch { path } => {
                             tasks.push(self.operation(Operation::SetExecutableAndLaunch { path }));
                         }
@@ -2905,6 +4590,9 @@ impl Application for App {
                                 tasks.push(self.update_config());
                             }
                         }
+                        DialogPage::TabGroup { entity, name } => {
+                            self.set_tab_group(entity, Some(name.trim().to_string()));
+                        }
                     }
                     return Task::batch(tasks);
                 }
@@ -3072,21 +4760,20 @@ impl Application for App {
                     let home_location = Location::Path(home_dir());
                     let entities: Box<[_]> = self.tab_model.iter().collect();
                     for entity in entities {
-                        let title_opt = self.tab_model.data_mut::<Tab>(entity).and_then(|tab| {
-                            unmounted
-                                .iter()
-                                .any(|unmounted| {
+                        let moved_home =
+                            self.tab_model.data_mut::<Tab>(entity).is_some_and(|tab| {
+                                let moved_home = unmounted.iter().any(|unmounted| {
                                     tab.location
                                         .path_opt()
                                         .is_some_and(|location| location.starts_with(unmounted))
-                                })
-                                .then(|| {
+                                });
+                                if moved_home {
                                     tab.change_location(&home_location, None);
-                                    tab.title()
-                                })
-                        });
-                        if let Some(title) = title_opt {
-                            self.tab_model.text_set(entity, title);
+                                }
+                                moved_home
+                            });
+                        if moved_home {
+                            self.update_tab_text(entity);
                             commands.push(self.update_tab(entity, home_location.clone(), None));
                         }
                     }
@@ -3106,6 +4793,9 @@ impl Application for App {
                 // Update desktop tabs
                 commands.push(self.update_desktop());
 
+                // Mounts may have been added or removed
+                commands.push(self.reload_remote_mounts());
+
                 return Task::batch(commands);
             }
             Message::MountResult(mounter_key, item, res) => match res {
@@ -3219,11 +4909,20 @@ impl Application for App {
                 let entities: Box<[_]> = self.tab_model.iter().collect();
                 for entity in entities {
                     if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
+                        let mut changed_previews = Vec::new();
                         if let Some(path) = tab.location.path_opt() {
                             let mut contains_change = false;
                             for event in &events {
                                 for event_path in &event.paths {
                                     if event_path.starts_with(path) {
+                                        // Changes inside a folder shown with a preview only
+                                        // invalidate that preview
+                                        if let Some(parent) = event_path.parent() {
+                                            if parent != path && tab.has_folder_preview(parent) {
+                                                changed_previews.push(parent.to_path_buf());
+                                                continue;
+                                            }
+                                        }
                                         if let notify::EventKind::Modify(
                                             notify::event::ModifyKind::Metadata(_)
                                             | notify::event::ModifyKind::Data(_),
@@ -3270,6 +4969,9 @@ impl Application for App {
                                 needs_reload.push((entity, tab.location.clone()));
                             }
                         }
+                        for folder in changed_previews {
+                            tab.invalidate_folder_preview(&folder);
+                        }
                     }
                 }
 
@@ -3451,11 +5153,15 @@ impl Application for App {
                         ClipboardKind::Copy => self.operation(Operation::Copy {
                             paths: contents.paths,
                             to,
+                            preserve: self.config.preserve_metadata,
+                            verify: self.config.verify_copies,
                         }),
                         ClipboardKind::Cut { is_dnd } => self.operation(Operation::Move {
                             paths: contents.paths,
                             to,
                             cross_device_copy: is_dnd,
+                            preserve: self.config.preserve_metadata,
+                            verify: self.config.verify_copies,
                         }),
                     };
                 }
@@ -3472,8 +5178,40 @@ impl Application for App {
                     self.progress_operations.remove(id);
                 }
             }
-            Message::PendingComplete(id, op_sel) => {
-                let mut commands = Vec::with_capacity(4);
+            Message::PendingComplete(id, mut op_sel) => {
+                let mut commands = Vec::with_capacity(5);
+                // Report problems that did not stop the operation
+                let warnings = std::mem::take(&mut op_sel.warnings);
+                let verify_failed = std::mem::take(&mut op_sel.verify_failed);
+                if self.cli_operation_opt == Some(id) {
+                    for warning in &warnings {
+                        eprintln!("cosmic-files: {warning}");
+                    }
+                    for path in &verify_failed {
+                        eprintln!(
+                            "cosmic-files: {}",
+                            fl!("verify-failed-cli", path = path.display().to_string())
+                        );
+                    }
+                    process::exit(i32::from(!verify_failed.is_empty()));
+                }
+                if !verify_failed.is_empty() {
+                    commands.push(self.push_dialog(
+                        DialogPage::VerifyFailed(verify_failed.into_boxed_slice()),
+                        None,
+                    ));
+                }
+                if let Some(warning) = warnings.first() {
+                    commands.push(
+                        self.toasts
+                            .push(widget::toaster::Toast::new(fl!(
+                                "operation-warnings",
+                                items = warnings.len(),
+                                warning = warning.as_str()
+                            )))
+                            .map(cosmic::Action::App),
+                    );
+                }
                 if let Some((op, _)) = self.pending_operations.remove(&id) {
                     // Show toast for some operations
                     if let Some(description) = op.toast() {
@@ -3489,6 +5227,22 @@ impl Application for App {
                                     )
                                     .map(cosmic::Action::App),
                             );
+                        } else if let Operation::Rotate {
+                            ref paths,
+                            rotation,
+                        } = op
+                        {
+                            let paths = paths.clone();
+                            commands.push(
+                                self.toasts
+                                    .push(widget::toaster::Toast::new(description).action(
+                                        fl!("undo"),
+                                        move |tid| {
+                                            Message::UndoRotate(tid, paths.clone(), rotation)
+                                        },
+                                    ))
+                                    .map(cosmic::Action::App),
+                            );
                         } else {
                             commands.push(
                                 self.toasts
@@ -3516,10 +5270,56 @@ impl Application for App {
                         }
                     }
 
+                    // Files we created are recent files, like files we opened
+                    if matches!(
+                        op,
+                        Operation::Compress { .. }
+                            | Operation::Extract { .. }
+                            | Operation::NewFile { .. }
+                    ) {
+                        let recent_uses = op_sel
+                            .selected
+                            .iter()
+                            .map(|path| {
+                                let mime = mime_icon::mime_for_path(path, None, false);
+                                RecentUse::new(path, &mime, Self::APP_ID, "cosmic-files")
+                            })
+                            .collect();
+                        commands.push(Self::add_recents(recent_uses));
+                    }
+
                     if matches!(op, Operation::RemoveFromRecents { .. }) {
                         commands.push(self.rescan_recents());
                     }
 
+                    // Compare windows showing the copied items are out of date
+                    let mut compare_ids: Vec<_> =
+                        self.compare_operations.remove(&id).into_iter().collect();
+                    if let Operation::Copy { ref to, .. } = op {
+                        for (window_id, window) in &self.windows {
+                            if let WindowKind::Compare(compare) = &window.kind {
+                                if (to.starts_with(&compare.left) || to.starts_with(&compare.right))
+                                    && !compare_ids.contains(window_id)
+                                {
+                                    compare_ids.push(*window_id);
+                                }
+                            }
+                        }
+                    }
+                    for window_id in compare_ids {
+                        commands.push(self.refresh_compare(window_id));
+                    }
+
+                    // Thumbnails and gallery images of rotated photos are out of date
+                    if let Operation::Rotate { ref paths, .. } = op {
+                        let entities: Box<[_]> = self.tab_model.iter().collect();
+                        for entity in entities {
+                            if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
+                                tab.reload_images(paths);
+                            }
+                        }
+                    }
+
                     self.complete_operations.insert(id, op);
                 }
                 // Close progress notification if all relevant operations are finished
@@ -3543,6 +5343,13 @@ impl Application for App {
                 self.progress_operations.clear();
             }
             Message::PendingError(id, err) => {
+                // Archives that need a password ask for it before the operation fails
+                if self.cli_operation_opt == Some(id)
+                    && !matches!(err.kind, OperationErrorType::PasswordRequired)
+                {
+                    eprintln!("cosmic-files: {err}");
+                    process::exit(1);
+                }
                 let mut tasks = Vec::new();
                 if let Some((op, controller)) = self.pending_operations.remove(&id) {
                     // Only show dialog if not cancelled
@@ -3557,6 +5364,11 @@ impl Application for App {
                     }
                     tasks.push(widget::text_input::focus(self.dialog_text_input.clone()));
 
+                    // Items may have been partially copied before the failure
+                    if let Some(window_id) = self.compare_operations.remove(&id) {
+                        tasks.push(self.refresh_compare(window_id));
+                    }
+
                     // Remove from progress
                     self.progress_operations.remove(&id);
                     self.failed_operations
@@ -3596,11 +5408,36 @@ impl Application for App {
                 let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
                 if !paths.is_empty() {
                     return self.push_dialog(
-                        DialogPage::PermanentlyDelete { paths },
+                        DialogPage::PermanentlyDelete {
+                            paths,
+                            no_trash: false,
+                        },
+                        Some(PERMANENT_DELETE_BUTTON_ID.clone()),
+                    );
+                }
+            }
+            Message::SecureDelete(entity_opt) => {
+                let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
+                if !paths.is_empty() {
+                    return self.push_dialog(
+                        DialogPage::SecureDelete { paths },
                         Some(PERMANENT_DELETE_BUTTON_ID.clone()),
                     );
                 }
             }
+            Message::Properties(entity_opt) => {
+                #[cfg(unix)]
+                {
+                    let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
+                    if !paths.is_empty() {
+                        return Self::load_properties(paths);
+                    }
+                }
+            }
+            #[cfg(unix)]
+            Message::PropertiesLoaded(properties) => {
+                return self.push_dialog(DialogPage::Properties(properties), None);
+            }
             Message::Preview(entity_opt) => {
                 match self.mode {
                     Mode::App => {
@@ -3643,6 +5480,20 @@ impl Application for App {
                     }
                 }
             }
+            Message::RemoteMounts(mounts) => {
+                self.remote_mounts = mounts;
+                self.update_remote_thumbnails();
+            }
+            Message::RemoteThumbnails(source, policy) => {
+                let mut remote_thumbnails = self.config.remote_thumbnails.clone();
+                if policy == RemoteThumbnails::default() {
+                    remote_thumbnails.remove(&source);
+                } else {
+                    remote_thumbnails.insert(source, policy);
+                }
+                config_set!(remote_thumbnails, remote_thumbnails);
+                return self.update_config();
+            }
             Message::RemoveFromRecents(entity_opt) => {
                 let paths: Box<[_]> = self.selected_paths(entity_opt).collect();
                 return self.operation(Operation::RemoveFromRecents { paths });
@@ -3717,22 +5568,52 @@ impl Application for App {
                     }
                 }
             }
-            Message::RestoreFromTrash(entity_opt) => {
-                let mut trash_items = Vec::new();
-                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
-                if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
-                    if let Some(items) = tab.items_opt() {
-                        for item in items {
-                            if item.selected {
-                                if let ItemMetadata::Trash { entry, .. } = &item.metadata {
-                                    trash_items.push(entry.clone());
-                                } else {
-                                    //TODO: error on trying to restore non-trash file?
-                                }
-                            }
+            Message::RestoreTo(entity_opt) => {
+                let trash_items = self.selected_trash_items(entity_opt);
+                if let Some(first) = trash_items.first() {
+                    let destination = if first.original_parent.is_dir() {
+                        first.original_parent.clone()
+                    } else {
+                        home_dir()
+                    };
+                    let (mut dialog, dialog_task) = Dialog::new(
+                        DialogSettings::new()
+                            .kind(DialogKind::OpenFolder)
+                            .path(destination),
+                        Message::FileDialogMessage,
+                        Message::RestoreToResult,
+                    );
+                    let set_title_task = dialog.set_title(fl!("restore-to-title"));
+                    dialog.set_accept_label(fl!("restore-here"));
+                    self.windows.insert(
+                        dialog.window_id(),
+                        Window::new(WindowKind::RestoreToDialog(trash_items)),
+                    );
+                    self.file_dialog_opt = Some(dialog);
+                    return Task::batch([set_title_task, dialog_task]);
+                }
+            }
+            Message::RestoreToResult(result) => {
+                let mut trash_items = None;
+                if let Some(file_dialog) = self.file_dialog_opt.take() {
+                    if let Some(window) = self.windows.remove(&file_dialog.window_id()) {
+                        if let WindowKind::RestoreToDialog(items) = window.kind {
+                            trash_items = Some(items);
                         }
                     }
                 }
+                if let (DialogResult::Open(selected_paths), Some(items)) = (result, trash_items) {
+                    if let Some(to) = selected_paths.into_iter().next() {
+                        return self.operation(Operation::RestoreTo {
+                            items,
+                            to,
+                            preserve: self.config.preserve_metadata,
+                        });
+                    }
+                }
+            }
+            Message::RestoreFromTrash(entity_opt) => {
+                let trash_items = self.selected_trash_items(entity_opt);
                 if !trash_items.is_empty() {
                     return self.operation(Operation::Restore { items: trash_items });
                 }
@@ -3757,6 +5638,10 @@ impl Application for App {
             Message::SearchInput(input) => {
                 return self.search_set_active(Some(input));
             }
+            Message::SetPreserveMetadata(preserve_metadata) => {
+                config_set!(preserve_metadata, preserve_metadata);
+                return self.update_config();
+            }
             Message::SetShowDetails(show_details) => {
                 config_set!(show_details, show_details);
                 return self.update_config();
@@ -3765,9 +5650,112 @@ impl Application for App {
                 config_set!(type_to_search, type_to_search);
                 return self.update_config();
             }
+            Message::SetFolderPreviews(folder_previews) => {
+                config_set!(
+                    thumb_cfg,
+                    ThumbCfg {
+                        folder_previews,
+                        ..self.config.thumb_cfg
+                    }
+                );
+                let task = self.update_config();
+                let entities: Box<[_]> = self.tab_model.iter().collect();
+                for entity in entities {
+                    if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
+                        tab.reset_folder_previews();
+                    }
+                }
+                return Task::batch([task, self.update_watcher()]);
+            }
+            Message::SetRestoreSession(restore_session) => {
+                config_set!(restore_session, restore_session);
+                if !restore_session {
+                    self.clear_sessions();
+                }
+                return self.update_config();
+            }
+            Message::SetSecureDeletePasses(index) => {
+                if let Some(passes) = shred::SHRED_PASSES.get(index) {
+                    config_set!(secure_delete_passes, *passes);
+                    return self.update_config();
+                }
+            }
+            Message::SetVerifyCopies(verify_copies) => {
+                config_set!(verify_copies, verify_copies);
+                return self.update_config();
+            }
             Message::SystemThemeModeChange => {
                 return self.update_config();
             }
+            Message::ThumbnailCacheClean => {
+                self.thumbnail_cache_busy = true;
+                let max_size = self.thumbnail_cache_max_size();
+                return cosmic::task::future(async move {
+                    let result = tokio::task::spawn_blocking(move || {
+                        thumbnail_cacher::clean_cache(max_size)
+                    })
+                    .await
+                    .map_err(|err| err.to_string())
+                    .and_then(|result| result.map_err(|err| err.to_string()));
+                    Message::ThumbnailCacheCleaned(result)
+                });
+            }
+            Message::ThumbnailCacheCleaned(result) => {
+                self.thumbnail_cache_busy = false;
+                let text = match result {
+                    Ok(stats) => fl!(
+                        "thumbnail-cache-cleaned",
+                        count = stats.removed,
+                        size = tab::format_size(stats.freed)
+                    ),
+                    Err(err) => {
+                        log::warn!("failed to clean thumbnail cache: {err}");
+                        err
+                    }
+                };
+                return Task::batch([
+                    self.thumbnail_cache_stats(),
+                    self.toasts
+                        .push(widget::toaster::Toast::new(text))
+                        .map(cosmic::Action::App),
+                ]);
+            }
+            Message::ThumbnailCacheClearFailed => {
+                self.thumbnail_cache_busy = true;
+                return cosmic::task::future(async move {
+                    let result = tokio::task::spawn_blocking(thumbnail_cacher::clear_fail_markers)
+                        .await
+                        .map_err(|err| err.to_string())
+                        .and_then(|result| result.map_err(|err| err.to_string()));
+                    Message::ThumbnailCacheCleaned(result)
+                });
+            }
+            Message::ThumbnailCacheMaxSize(index) => {
+                if let Some(max_cache_mb) = THUMBNAIL_CACHE_SIZES_MB
+                    .get(index)
+                    .and_then(|&mb| NonZeroU16::new(mb))
+                {
+                    config_set!(
+                        thumb_cfg,
+                        ThumbCfg {
+                            max_cache_mb,
+                            ..self.config.thumb_cfg
+                        }
+                    );
+                    return Task::batch([self.update_config(), self.thumbnail_cache_enforce()]);
+                }
+            }
+            Message::ThumbnailCacheStats(stats_opt) => match stats_opt {
+                Some(stats) => {
+                    self.thumbnail_cache_stats = Some(stats);
+                }
+                None => {
+                    // Reload if the settings page is open
+                    if self.core.window.show_context && self.context_page == ContextPage::Settings {
+                        return self.thumbnail_cache_stats();
+                    }
+                }
+            },
             Message::TabActivate(entity) => {
                 let mut tasks = Vec::new();
 
@@ -3826,35 +5814,164 @@ impl Application for App {
                 }
             }
             Message::TabClose(entity_opt) => {
-                let mut tasks = Vec::with_capacity(2);
-
                 let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
 
-                // If the last tab is closed, close the window
-                // Otherwise, activate closest item
-                if self.tab_model.len() == 1 {
-                    tasks.push(Task::future(async move {
-                        cosmic::action::app(Message::WindowClose)
-                    }));
-                } else if let Some(position) = self.tab_model.position(entity) {
-                    let new_position = if position > 0 {
-                        position - 1
-                    } else {
-                        position + 1
-                    };
-
-                    if let Some(new_entity) = self.tab_model.entity_at(new_position) {
-                        tasks.push(self.update(Message::TabActivate(new_entity)));
-                    }
+                // Pinned tabs have to be unpinned before closing them
+                if !self.tab_pinned(entity) {
+                    return self.close_tab(entity);
                 }
+            }
+            Message::TabContext(entity) => {
+                self.tab_context_id = entity;
 
-                // Remove item
-                self.tab_model.remove(entity);
-
-                tasks.push(self.update_watcher());
-
+                // Close the context menu of the active tab
+                let active = self.tab_model.active();
+                if let Some(tab) = self.tab_model.data_mut::<Tab>(active) {
+                    tab.edit_location = None;
+                    tab.location_context_menu_index = None;
+                    return self.update(Message::TabMessage(
+                        Some(active),
+                        tab::Message::ContextMenu(None, None),
+                    ));
+                }
+            }
+            Message::TabDragCancel => {
+                // The tab was not dropped on a tab bar, so it is moved to a new window
+                if let Some((entity, _)) = self.tab_drag_opt.take() {
+                    return self.update(Message::TabMoveToNewWindow(Some(entity)));
+                }
+            }
+            Message::TabDragFinish => {
+                // The tab is only closed if another window confirms that it opened it, so it is
+                // kept when dropped on this window or when the other window failed to open it
+                if let Some((entity, id)) = self.tab_drag_opt {
+                    return cosmic::task::future(async move {
+                        match tokio::task::spawn_blocking(move || wait_tab_drop(id)).await {
+                            Ok(true) => Message::TabDropConfirmed(entity),
+                            Ok(false) => Message::None,
+                            Err(err) => {
+                                log::warn!("failed to wait for tab drop: {err}");
+                                Message::None
+                            }
+                        }
+                    });
+                }
+            }
+            Message::TabDragStart(entity, id) => {
+                self.tab_drag_opt = Some((entity, id));
+                self.tab_drags += 1;
+            }
+            Message::TabDropConfirmed(entity) => {
+                if self
+                    .tab_drag_opt
+                    .is_some_and(|(dragged, _)| dragged == entity)
+                {
+                    self.tab_drag_opt = None;
+                }
+                return self.close_tab(entity);
+            }
+            Message::TabDuplicate(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                let Some(tab) = self.tab_model.data::<Tab>(entity) else {
+                    return Task::none();
+                };
+                let location = tab.location.clone();
+                let group_opt = tab.group_opt.clone();
+                let history = tab.history.clone();
+                let history_i = tab.history_i;
+                let config = tab.config;
+                let scroll_opt = tab.scroll_opt;
+                let selection_paths: Vec<_> = tab
+                    .items_opt()
+                    .into_iter()
+                    .flatten()
+                    .filter(|item| item.selected)
+                    .filter_map(|item| item.path_opt().cloned())
+                    .collect();
+                let position = self.tab_model.position(entity).unwrap_or_default();
+
+                let (new_entity, task) = self.open_tab_entity(
+                    location,
+                    true,
+                    (!selection_paths.is_empty()).then_some(selection_paths),
+                    self.scrollable_id.clone(),
+                    None,
+                );
+                if let Some(tab) = self.tab_model.data_mut::<Tab>(new_entity) {
+                    tab.history = history;
+                    tab.history_i = history_i;
+                    tab.config = config;
+                }
+                if let Some(offset) = scroll_opt {
+                    self.restore_scroll.insert(new_entity, offset);
+                }
+                // Open the copy next to the original, but after the pinned tabs
+                let pinned_count = self
+                    .tab_model
+                    .iter()
+                    .filter(|&entity| self.tab_pinned(entity))
+                    .count() as u16;
+                self.tab_model
+                    .position_set(new_entity, (position + 1).max(pinned_count));
+                self.set_tab_group(new_entity, group_opt);
+                return task;
+            }
+            Message::TabGroupClose(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                let Some(group) = self.tab_group(entity) else {
+                    return Task::none();
+                };
+                let entities: Vec<_> = self
+                    .tab_model
+                    .iter()
+                    .filter(|&other| self.tab_group(other) == Some(group))
+                    .collect();
+                let tasks: Vec<_> = entities
+                    .into_iter()
+                    .map(|entity| self.close_tab(entity))
+                    .collect();
                 return Task::batch(tasks);
             }
+            Message::TabGroupNew(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                if !self.tab_pinned(entity) {
+                    return Task::batch([
+                        self.dialog_pages.push_back(DialogPage::TabGroup {
+                            entity,
+                            name: String::new(),
+                        }),
+                        widget::text_input::focus(self.dialog_text_input.clone()),
+                    ]);
+                }
+            }
+            Message::TabGroupRemove(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                self.set_tab_group(entity, None);
+            }
+            Message::TabMoveLeft(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                if let Some(position) = self.tab_model.position(entity) {
+                    self.swap_tabs(entity, position.checked_sub(1));
+                }
+            }
+            Message::TabMoveRight(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                if let Some(position) = self.tab_model.position(entity) {
+                    self.swap_tabs(entity, position.checked_add(1));
+                }
+            }
+            Message::TabMoveToNewWindow(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                if let Some(tab) = self.tab_model.data::<Tab>(entity) {
+                    if Self::open_in_new_window(&tab.location) {
+                        return self.close_tab(entity);
+                    }
+                }
+            }
+            Message::TabPin(entity_opt) => {
+                let entity = entity_opt.unwrap_or_else(|| self.tab_model.active());
+                self.set_tab_pinned(entity, !self.tab_pinned(entity));
+            }
             Message::TabConfig(config) => {
                 if config != self.config.tab {
                     config_set!(tab, config);
@@ -3866,6 +5983,15 @@ impl Application for App {
                 config.folders_first = !config.folders_first;
                 return self.update(Message::TabConfig(config));
             }
+            Message::TrashPurge => {
+                return self.trash_purge();
+            }
+            Message::TrashPurgeDays(index) => {
+                if let Some(days) = trash_folder::PURGE_DAYS.get(index) {
+                    config_set!(trash_purge_days, *days);
+                    return Task::batch([self.update_config(), self.trash_purge()]);
+                }
+            }
             Message::ToggleShowHidden => {
                 let mut config = self.config.tab;
                 config.show_hidden = !config.show_hidden;
@@ -3908,10 +6034,10 @@ impl Application for App {
                                 self.auto_scroll_speed = None;
                             }
                         }
-                        tab::Command::ChangeLocation(tab_title, tab_path, selection_paths) => {
+                        tab::Command::ChangeLocation(_tab_title, tab_path, selection_paths) => {
                             self.activate_nav_model_location(&tab_path);
 
-                            self.tab_model.text_set(entity, tab_title);
+                            self.update_tab_text(entity);
                             commands.push(Task::batch([
                                 self.update_title(),
                                 self.update_watcher(),
@@ -4038,12 +6164,24 @@ impl Application for App {
                             self.context_page = ContextPage::Preview(Some(entity), kind);
                             self.set_show_context(true);
                         }
+                        tab::Command::RemoveFromRecents(paths) => {
+                            commands.push(self.operation(Operation::RemoveFromRecents {
+                                paths: paths.into_boxed_slice(),
+                            }));
+                        }
+                        tab::Command::Rotate(paths, rotation) => {
+                            commands.push(self.operation(Operation::Rotate { paths, rotation }));
+                        }
                         tab::Command::SetOpenWith(mime, id) => {
                             //TODO: this will block for a few ms, run in background?
                             self.mime_app_cache.set_default(mime, id);
                         }
+                        #[cfg(unix)]
                         tab::Command::SetPermissions(path, mode) => {
-                            commands.push(self.operation(Operation::SetPermissions { path, mode }));
+                            commands.push(self.operation(Operation::SetPermissions {
+                                paths: vec![path],
+                                change: PermissionsChange::mode(mode),
+                            }));
                         }
                         tab::Command::WindowDrag => {
                             if let Some(window_id) = self.core.main_window_id() {
@@ -4055,6 +6193,27 @@ impl Application for App {
                                 commands.push(window::toggle_maximize(window_id));
                             }
                         }
+                        tab::Command::UpdateWatcher => {
+                            commands.push(self.update_watcher());
+                        }
+                        tab::Command::SetDesktopLayout(display, layout) => {
+                            let changed = if layout == DesktopLayout::default() {
+                                self.state.desktop_layouts.remove(&display).is_some()
+                            } else {
+                                self.state
+                                    .desktop_layouts
+                                    .insert(display, layout.clone())
+                                    .is_none_or(|old| old != layout)
+                            };
+
+                            if !self.must_save_desktop_layouts && changed {
+                                self.must_save_desktop_layouts = true;
+                                return cosmic::Task::future(async move {
+                                    tokio::time::sleep(Duration::from_secs(1)).await;
+                                    cosmic::action::app(Message::SaveDesktopLayouts)
+                                });
+                            }
+                        }
                         tab::Command::SetSort(location, heading_options, direction) => {
                             let default_sort = tab::SORT_OPTION_FALLBACK
                                 .get(&location)
@@ -4106,6 +6265,7 @@ impl Application for App {
             }
             Message::TabRescan(entity, mut location, parent_item_opt, items, selection_paths) => {
                 location = location.normalize();
+                let active = self.tab_model.active();
                 if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
                     tab.location = tab.location.normalize();
                     if location == tab.location {
@@ -4125,7 +6285,15 @@ impl Application for App {
                         if let Some(selection_paths) = selection_paths {
                             tab.select_paths(selection_paths);
                         }
-                        return clipboard::read_data::<ClipboardPaste>().map(|p| {
+                        let mut tasks = Vec::with_capacity(2);
+                        if let Some(offset) = self.restore_scroll.remove(&entity) {
+                            tab.scroll_opt = Some(offset);
+                            if entity == active {
+                                tasks
+                                    .push(scrollable::scroll_to(tab.scrollable_id.clone(), offset));
+                            }
+                        }
+                        tasks.push(clipboard::read_data::<ClipboardPaste>().map(|p| {
                             cosmic::action::app(Message::CutPaths(match p {
                                 Some(s) => match s.kind {
                                     ClipboardKind::Copy => Vec::new(),
@@ -4133,7 +6301,8 @@ impl Application for App {
                                 },
                                 None => Vec::new(),
                             }))
-                        });
+                        }));
+                        return Task::batch(tasks);
                     }
                 }
             }
@@ -4165,6 +6334,12 @@ impl Application for App {
                     self.set_show_context(true);
                 }
                 self.context_page = context_page;
+                if self.context_page == ContextPage::Settings && self.core.window.show_context {
+                    return Task::batch([
+                        self.thumbnail_cache_stats(),
+                        self.reload_remote_mounts(),
+                    ]);
+                }
                 // Preview status is preserved across restarts
                 if matches!(self.context_page, ContextPage::Preview(_, _)) {
                     return cosmic::task::message(cosmic::action::app(Message::SetShowDetails(
@@ -4175,6 +6350,13 @@ impl Application for App {
             Message::Undo(_id) => {
                 // TODO: undo
             }
+            Message::UndoRotate(id, paths, rotation) => {
+                self.toasts.remove(id);
+                return self.operation(Operation::Rotate {
+                    paths,
+                    rotation: rotation.inverse(),
+                });
+            }
             Message::UndoTrash(id, recently_trashed) => {
                 self.toasts.remove(id);
 
@@ -4210,6 +6392,9 @@ impl Application for App {
             }
             Message::WindowClose => {
                 if let Some(window_id) = self.core.main_window_id() {
+                    if matches!(self.mode, Mode::App) {
+                        self.save_session();
+                    }
                     self.core.set_main_window_id(None);
                     return Task::batch([
                         window::close(window_id),
@@ -4224,7 +6409,8 @@ impl Application for App {
                 return window::maximize(id, maximized);
             }
             Message::WindowNew => match env::current_exe() {
-                Ok(exe) => match process::Command::new(&exe).spawn() {
+                // The new window must not restore the session
+                Ok(exe) => match process::Command::new(&exe).arg("--new-window").spawn() {
                     Ok(_child) => {}
                     Err(err) => {
                         log::error!("failed to execute {}: {}", exe.display(), err);
@@ -4304,15 +6490,9 @@ impl Application for App {
                 {
                     self.nav_dnd_hover = None;
                     let entity = self.tab_model.active();
-                    let title_opt = match self.tab_model.data_mut::<Tab>(entity) {
-                        Some(tab) => {
-                            tab.change_location(&location, None);
-                            Some(tab.title())
-                        }
-                        None => None,
-                    };
-                    if let Some(title) = title_opt {
-                        self.tab_model.text_set(entity, title);
+                    if let Some(tab) = self.tab_model.data_mut::<Tab>(entity) {
+                        tab.change_location(&location, None);
+                        self.update_tab_text(entity);
                         return Task::batch([
                             self.update_title(),
                             self.update_watcher(),
@@ -4332,6 +6512,11 @@ impl Application for App {
             }
             Message::DndDropTab(entity, data, action) => {
                 self.nav_dnd_hover = None;
+                let data = match data {
+                    Some(TabDrop::Paths(data)) => Some(data),
+                    Some(TabDrop::Tab(drag)) => return self.drop_tab(entity, *drag),
+                    None => None,
+                };
                 if let Some((tab, data)) = self.tab_model.data::<Tab>(entity).zip(data) {
                     let kind = match action {
                         DndAction::Move => ClipboardKind::Cut { is_dnd: true },
@@ -4461,45 +6646,9 @@ impl Application for App {
                 }
 
                 // Open the selected path in a new cosmic-files window.
-                NavMenuAction::OpenInNewWindow(entity) => 'open_in_new_window: {
+                NavMenuAction::OpenInNewWindow(entity) => {
                     if let Some(location) = self.nav_model.data::<Location>(entity) {
-                        match env::current_exe() {
-                            Ok(exe) => {
-                                let mut command = process::Command::new(&exe);
-                                match location {
-                                    Location::Path(path) => {
-                                        command.arg(path);
-                                    }
-                                    Location::Trash => {
-                                        command.arg("--trash");
-                                    }
-                                    Location::Network(uri, _, Some(_)) => {
-                                        command.arg(uri);
-                                    }
-                                    Location::Network(..) => {
-                                        command.arg("--network");
-                                    }
-                                    Location::Recents => {
-                                        command.arg("--recents");
-                                    }
-                                    _ => {
-                                        log::error!(
-                                            "unsupported location for open in new window: {location:?}"
-                                        );
-                                        break 'open_in_new_window;
-                                    }
-                                }
-                                match command.spawn() {
-                                    Ok(_child) => {}
-                                    Err(err) => {
-                                        log::error!("failed to execute {}: {}", exe.display(), err);
-                                    }
-                                }
-                            }
-                            Err(err) => {
-                                log::error!("failed to get current executable path: {err}");
-                            }
-                        }
+                        Self::open_in_new_window(location);
                     }
                 }
 
@@ -4701,6 +6850,17 @@ impl Application for App {
                     cosmic::app::Action::Surface(action),
                 ));
             }
+            Message::SaveDesktopLayouts => {
+                self.must_save_desktop_layouts = false;
+                if let Some(state_handler) = self.state_handler.as_ref() {
+                    if let Err(err) = state_handler.set::<&FxOrderMap<String, DesktopLayout>>(
+                        "desktop_layouts",
+                        &self.state.desktop_layouts,
+                    ) {
+                        log::warn!("Failed to save desktop layouts: {err:?}");
+                    }
+                }
+            }
             Message::SaveSortNames => {
                 self.must_save_sort_names = false;
                 if let Some(state_handler) = self.state_handler.as_ref() {
@@ -4736,6 +6896,41 @@ impl Application for App {
                 |url| Message::LaunchUrl(url.to_string()),
                 Message::ToggleContextPage(ContextPage::About),
             ),
+            ContextPage::Checksums => {
+                let running = self.checksums_running();
+                let kind_index = ChecksumKind::ALL
+                    .iter()
+                    .position(|&kind| kind == self.checksum_write_kind);
+                let mut write_button = widget::button::standard(fl!("checksum-write"));
+                if !running
+                    && self
+                        .checksum_entries
+                        .iter()
+                        .any(|entry| matches!(entry.result_opt, Some(Ok(_))))
+                {
+                    write_button = write_button.on_press(Message::ChecksumWrite);
+                }
+                context_drawer::context_drawer(
+                    self.checksums(),
+                    Message::ToggleContextPage(ContextPage::Checksums),
+                )
+                .title(fl!("checksums"))
+                .header(
+                    widget::text_input(fl!("checksum-expected"), &self.checksum_expected)
+                        .on_input(Message::ChecksumExpected),
+                )
+                .footer(
+                    widget::row::with_children([
+                        widget::dropdown(&ChecksumKind::ALL, kind_index, |index| {
+                            Message::ChecksumWriteKind(ChecksumKind::ALL[index])
+                        })
+                        .into(),
+                        widget::horizontal_space().into(),
+                        write_button.into(),
+                    ])
+                    .align_y(Alignment::Center),
+                )
+            }
             ContextPage::EditHistory => context_drawer::context_drawer(
                 self.edit_history(),
                 Message::ToggleContextPage(ContextPage::EditHistory),
@@ -4920,6 +7115,15 @@ impl Application for App {
 
                 dialog
             }
+            DialogPage::ChecksumReplace { existing, .. } => widget::dialog()
+                .title(fl!("checksum-replace-title"))
+                .body(fl!("checksum-replace-warning", items = *existing))
+                .primary_action(
+                    widget::button::suggested(fl!("replace")).on_press(Message::DialogComplete),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                ),
             DialogPage::EmptyTrash => widget::dialog()
                 .title(fl!("empty-trash-title"))
                 .body(fl!("empty-trash-warning"))
@@ -4945,6 +7149,18 @@ impl Application for App {
                         widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                     )
             }
+            DialogPage::VerifyFailed(paths) => widget::dialog()
+                .title(fl!("verify-failed"))
+                .body(fl!("verify-failed-description", items = paths.len()))
+                .icon(icon::from_name("dialog-error").size(64))
+                .control(widget::scrollable(widget::column::with_children(
+                    paths
+                        .iter()
+                        .map(|path| widget::text::body(path.display().to_string()).into()),
+                )))
+                .primary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                ),
             DialogPage::ExtractPassword { id, password } => widget::dialog()
                 .title(fl!("extract-password-required"))
                 .icon(icon::from_name("dialog-error").size(64))
@@ -5289,33 +7505,59 @@ impl Application for App {
 
                 dialog
             }
-            DialogPage::PermanentlyDelete { paths } => {
-                let target = if paths.len() == 1 {
-                    format!(
-                        "\"{}\"",
-                        paths[0].file_name().map_or_else(
-                            || paths[0].to_string_lossy(),
-                            std::ffi::OsStr::to_string_lossy
-                        )
+            DialogPage::PermanentlyDelete { paths, no_trash } => {
+                let target = Self::delete_target(paths);
+
+                let warning = if *no_trash {
+                    fl!(
+                        "permanently-delete-no-trash-warning",
+                        target = target,
+                        items = paths.len()
                     )
                 } else {
-                    fl!("selected-items", items = paths.len())
+                    fl!("permanently-delete-warning", target = target)
                 };
 
                 widget::dialog()
                     .title(fl!("permanently-delete-question"))
                     .primary_action(
-                        widget::button::destructive(fl!("delete"))
-                            .on_press(Message::DialogComplete)
-                            .id(PERMANENT_DELETE_BUTTON_ID.clone()),
+                        widget::button::destructive(if *no_trash {
+                            fl!("delete-permanently")
+                        } else {
+                            fl!("delete")
+                        })
+                        .on_press(Message::DialogComplete)
+                        .id(PERMANENT_DELETE_BUTTON_ID.clone()),
                     )
                     .secondary_action(
                         widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
                     )
-                    .control(widget::text(fl!(
-                        "permanently-delete-warning",
-                        target = target
-                    )))
+                    .control(widget::text(warning))
+            }
+            #[cfg(unix)]
+            DialogPage::Properties(properties) => {
+                let mut dialog = widget::dialog().title(properties.title());
+
+                let complete_maybe = match properties.change() {
+                    Ok(_) => Some(Message::DialogComplete),
+                    Err(err) => {
+                        dialog = dialog.tertiary_action(widget::text::body(err));
+                        None
+                    }
+                };
+
+                dialog
+                    .primary_action(
+                        widget::button::suggested(fl!("apply")).on_press_maybe(complete_maybe),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+                    .control(properties.view().map(move |message| {
+                        let mut properties = properties.clone();
+                        properties.update(message);
+                        Message::DialogUpdate(DialogPage::Properties(properties))
+                    }))
             }
             DialogPage::RenameItem {
                 from,
@@ -5454,6 +7696,29 @@ impl Application for App {
                         )
                 }
             }
+            DialogPage::SecureDelete { paths } => widget::dialog()
+                .title(fl!("secure-delete-question"))
+                .icon(icon::from_name("dialog-warning").size(64))
+                .primary_action(
+                    widget::button::destructive(fl!("secure-delete"))
+                        .on_press(Message::DialogComplete)
+                        .id(PERMANENT_DELETE_BUTTON_ID.clone()),
+                )
+                .secondary_action(
+                    widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                )
+                .control(
+                    widget::column::with_children([
+                        widget::text(fl!(
+                            "secure-delete-warning",
+                            target = Self::delete_target(paths),
+                            passes = self.config.secure_delete_passes
+                        ))
+                        .into(),
+                        widget::text::caption(fl!("secure-delete-storage-warning")).into(),
+                    ])
+                    .spacing(space_s),
+                ),
             DialogPage::SetExecutableAndLaunch { path } => {
                 let name = match path.file_name() {
                     Some(file_name) => file_name.to_str(),
@@ -5492,6 +7757,34 @@ impl Application for App {
                 .secondary_action(
                     widget::button::standard(fl!("keep")).on_press(Message::DialogCancel),
                 ),
+            DialogPage::TabGroup { entity, name } => {
+                let complete_maybe = (!name.trim().is_empty()).then_some(Message::DialogComplete);
+                widget::dialog()
+                    .title(fl!("create-tab-group"))
+                    .primary_action(
+                        widget::button::suggested(fl!("create"))
+                            .on_press_maybe(complete_maybe.clone()),
+                    )
+                    .secondary_action(
+                        widget::button::standard(fl!("cancel")).on_press(Message::DialogCancel),
+                    )
+                    .control(
+                        widget::column::with_children([
+                            widget::text::body(fl!("tab-group-name")).into(),
+                            widget::text_input("", name.as_str())
+                                .id(self.dialog_text_input.clone())
+                                .on_input(move |name| {
+                                    Message::DialogUpdate(DialogPage::TabGroup {
+                                        entity: *entity,
+                                        name,
+                                    })
+                                })
+                                .on_submit_maybe(complete_maybe.map(|maybe| move |_| maybe.clone()))
+                                .into(),
+                        ])
+                        .spacing(space_xxs),
+                    )
+            }
         };
         Some(dialog.into())
     }
@@ -5678,24 +7971,45 @@ impl Application for App {
         }
 
         if self.tab_model.len() > 1 {
-            tab_column = tab_column.push(
-                widget::container(
-                    widget::tab_bar::horizontal(&self.tab_model)
-                        .button_height(32)
-                        .button_spacing(space_xxs)
-                        .on_activate(Message::TabActivate)
-                        .on_close(|entity| Message::TabClose(Some(entity)))
-                        .on_dnd_enter(|entity, _| Message::DndEnterTab(entity))
-                        .on_dnd_leave(|_| Message::DndExitTab)
-                        .on_dnd_drop(|entity, data, action| {
-                            Message::DndDropTab(entity, data, action)
-                        })
-                        .drag_id(self.tab_drag_id),
-                )
-                .class(style::Container::Background)
-                .width(Length::Fill)
-                .padding([0, space_s]),
-            );
+            let tab_bar = widget::container(
+                widget::tab_bar::horizontal(&self.tab_model)
+                    .button_height(32)
+                    .button_spacing(space_xxs)
+                    .on_activate(Message::TabActivate)
+                    .on_close(|entity| Message::TabClose(Some(entity)))
+                    .on_context(Message::TabContext)
+                    .context_menu(self.tab_context_menu(self.tab_context_id))
+                    .on_dnd_enter(|entity, _| Message::DndEnterTab(entity))
+                    .on_dnd_leave(|_| Message::DndExitTab)
+                    .on_dnd_drop(|entity, data, action| Message::DndDropTab(entity, data, action))
+                    .drag_id(self.tab_drag_id),
+            )
+            .class(style::Container::Background)
+            .width(Length::Fill)
+            .padding([0, space_s]);
+            // Pressing a tab activates it, so the active tab is the one dragged. The drag
+            // remembers the tab its contents were made from, in case another tab is activated
+            // before the drag finishes.
+            let entity = self.tab_model.active();
+            let id = self.tab_drags;
+            let drag_opt = self.session_tab(entity).map(|tab| TabDrag {
+                pid: process::id(),
+                id,
+                tab,
+            });
+            tab_column = tab_column.push(match drag_opt {
+                Some(drag) => Element::from(
+                    widget::DndSource::<Message, TabDrag>::with_id(
+                        tab_bar,
+                        widget::Id::new("tab-bar"),
+                    )
+                    .drag_content(move || drag.clone())
+                    .on_start(Some(Message::TabDragStart(entity, id)))
+                    .on_cancel(Some(Message::TabDragCancel))
+                    .on_finish(Some(Message::TabDragFinish)),
+                ),
+                None => tab_bar.into(),
+            });
         }
 
         let entity = self.tab_model.active();
@@ -5768,6 +8082,7 @@ impl Application for App {
                         tab_column.into()
                     };
                 }
+                WindowKind::Compare(compare) => self.compare_view(id, compare),
                 WindowKind::DesktopViewOptions => self.desktop_view_options(),
                 WindowKind::Dialogs(id) => match self.dialog() {
                     Some(element) => return widget::autosize::autosize(element, id.clone()).into(),
@@ -5776,13 +8091,14 @@ impl Application for App {
                 WindowKind::Preview(entity_opt, kind) => self
                     .preview(entity_opt, kind, false)
                     .map(|x| Message::TabMessage(*entity_opt, x)),
-                WindowKind::FileDialog(..) => match &self.file_dialog_opt {
-                    Some(dialog) => return dialog.view(id),
-                    None => widget::text("Unknown window ID").into(),
-                },
+                WindowKind::FileDialog(..) | WindowKind::RestoreToDialog(..) => {
+                    match &self.file_dialog_opt {
+                        Some(dialog) => return dialog.view(id),
+                        None => widget::text("Unknown window ID").into(),
+                    }
+                }
             },
             None => {
-                //TODO: distinct views per monitor in desktop mode
                 return self.view_main().map(|message| match message {
                     cosmic::Action::App(app) => app,
                     cosmic::Action::Cosmic(cosmic) => Message::Cosmic(cosmic),
@@ -6095,6 +8411,12 @@ impl Application for App {
             ),
         ];
 
+        if self.config.trash_purge_days.is_some() {
+            subscriptions.push(
+                iced::time::every(time::Duration::from_secs(60 * 60)).map(|_| Message::TrashPurge),
+            );
+        }
+
         if let Some(scroll_speed) = self.auto_scroll_speed {
             subscriptions.push(
                 iced::time::every(time::Duration::from_millis(10))
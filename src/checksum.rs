@@ -0,0 +1,385 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use md5::{Digest, Md5};
+use sha1::Sha1;
+use sha2::Sha256;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::operation::{Controller, OperationError};
+
+/// Checksum files larger than this are not read when looking for an expected checksum
+const MAX_CHECKSUM_FILE_LEN: u64 = 1024 * 1024;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChecksumKind {
+    Md5,
+    Sha1,
+    Sha256,
+    Blake3,
+}
+
+impl ChecksumKind {
+    pub const ALL: [Self; 4] = [Self::Md5, Self::Sha1, Self::Sha256, Self::Blake3];
+
+    /// Extension used by coreutils style checksum files, like `file.iso.sha256sum`
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Md5 => "md5sum",
+            Self::Sha1 => "sha1sum",
+            Self::Sha256 => "sha256sum",
+            Self::Blake3 => "b3sum",
+        }
+    }
+
+    /// Name of the checksum file listing a whole directory, like `SHA256SUMS`
+    pub const fn list_name(self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5SUMS",
+            Self::Sha1 => "SHA1SUMS",
+            Self::Sha256 => "SHA256SUMS",
+            Self::Blake3 => "B3SUMS",
+        }
+    }
+
+    /// Length of the hex encoded digest
+    pub const fn hex_len(self) -> usize {
+        match self {
+            Self::Md5 => 32,
+            Self::Sha1 => 40,
+            Self::Sha256 | Self::Blake3 => 64,
+        }
+    }
+}
+
+impl fmt::Display for ChecksumKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+impl AsRef<str> for ChecksumKind {
+    fn as_ref(&self) -> &str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Blake3 => "BLAKE3",
+        }
+    }
+}
+
+/// State of a file in the checksums panel
+#[derive(Clone, Debug)]
+pub struct ChecksumEntry {
+    pub path: PathBuf,
+    pub result_opt: Option<Result<Digests, String>>,
+    /// Expected checksum found in a sibling checksum file, filled in once it was looked for
+    pub expected_opt: Option<(ChecksumKind, String)>,
+}
+
+impl ChecksumEntry {
+    pub const fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            result_opt: None,
+            expected_opt: None,
+        }
+    }
+}
+
+/// Hex encoded digests of a file, indexed by [`ChecksumKind`]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Digests([String; 4]);
+
+impl Digests {
+    pub fn get(&self, kind: ChecksumKind) -> &str {
+        &self.0[kind as usize]
+    }
+
+    /// Find which digest matches a hex encoded checksum, ignoring case and whitespace
+    pub fn matches(&self, expected: &str) -> Option<ChecksumKind> {
+        let expected = expected.trim();
+        ChecksumKind::ALL
+            .into_iter()
+            .find(|&kind| !expected.is_empty() && self.get(kind).eq_ignore_ascii_case(expected))
+    }
+}
+
+struct Hashers {
+    md5: Md5,
+    sha1: Sha1,
+    sha256: Sha256,
+    blake3: blake3::Hasher,
+}
+
+impl Hashers {
+    fn new() -> Self {
+        Self {
+            md5: Md5::new(),
+            sha1: Sha1::new(),
+            sha256: Sha256::new(),
+            blake3: blake3::Hasher::new(),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.md5.update(data);
+        self.sha1.update(data);
+        self.sha256.update(data);
+        self.blake3.update(data);
+    }
+
+    fn finalize(self) -> Digests {
+        Digests([
+            format!("{:x}", self.md5.finalize()),
+            format!("{:x}", self.sha1.finalize()),
+            format!("{:x}", self.sha256.finalize()),
+            self.blake3.finalize().to_hex().to_string(),
+        ])
+    }
+}
+
+/// Compute all digests of a reader, calling `on_read` with the number of bytes read so far.
+/// Reading stops with the error returned by `on_read`.
+pub fn hash_reader<R: Read, E: From<io::Error>>(
+    mut reader: R,
+    mut on_read: impl FnMut(u64) -> Result<(), E>,
+) -> Result<Digests, E> {
+    let mut hashers = Hashers::new();
+    let mut buf = vec![0; 1024 * 1024];
+    let mut current = 0;
+    loop {
+        let count = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        hashers.update(&buf[..count]);
+        current += count as u64;
+        on_read(current)?;
+    }
+    Ok(hashers.finalize())
+}
+
+/// Compute all digests of the files in `paths`, calling `on_result` for each file.
+/// Directories are skipped. Progress and cancellation are handled through `controller`.
+pub fn hash_paths(
+    paths: &[PathBuf],
+    controller: &Controller,
+    mut on_result: impl FnMut(PathBuf, Result<Digests, String>),
+) -> Result<(), OperationError> {
+    let total = paths.len();
+    for (i, path) in paths.iter().enumerate() {
+        futures::executor::block_on(async {
+            controller
+                .check()
+                .await
+                .map_err(|s| OperationError::from_state(s, controller))
+        })?;
+
+        controller.set_progress((i as f32) / (total as f32));
+
+        if path.is_dir() {
+            continue;
+        }
+
+        let file = match File::open(path) {
+            Ok(ok) => ok,
+            Err(err) => {
+                on_result(path.clone(), Err(err.to_string()));
+                continue;
+            }
+        };
+        let len = file.metadata().map_or(0, |metadata| metadata.len());
+        let result = hash_reader(file, |current| -> Result<(), HashError> {
+            futures::executor::block_on(async {
+                controller
+                    .check()
+                    .await
+                    .map_err(|s| HashError::Operation(OperationError::from_state(s, controller)))
+            })?;
+            if len > 0 {
+                let file_progress = (current as f32 / len as f32).min(1.0);
+                controller.set_progress((i as f32 + file_progress) / (total as f32));
+            }
+            Ok(())
+        });
+        match result {
+            Ok(digests) => on_result(path.clone(), Ok(digests)),
+            Err(HashError::Io(err)) => on_result(path.clone(), Err(err.to_string())),
+            Err(HashError::Operation(err)) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+enum HashError {
+    Io(io::Error),
+    Operation(OperationError),
+}
+
+impl From<io::Error> for HashError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Parse a coreutils style checksum file, returning the checksum and file name of each line.
+/// Both `checksum  name` (text mode) and `checksum *name` (binary mode) are supported.
+pub fn parse_checksum_file(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (checksum, name) = line.split_once(char::is_whitespace)?;
+            if checksum.is_empty() || !checksum.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return None;
+            }
+            let name = name.strip_prefix([' ', '*']).unwrap_or(name);
+            Some((checksum.to_ascii_lowercase(), name.to_string()))
+        })
+        .collect()
+}
+
+/// Format a line of a coreutils style checksum file
+pub fn format_checksum_line(checksum: &str, name: &str) -> String {
+    format!("{checksum}  {name}\n")
+}
+
+/// Look for an expected checksum of `path` in a sibling checksum file, either
+/// `name.sha256sum` or a `SHA256SUMS` list, for each supported algorithm
+pub fn find_sibling_checksum(path: &Path) -> Option<(ChecksumKind, String)> {
+    let name = path.file_name()?.to_str()?;
+    let parent = path.parent()?;
+    for kind in ChecksumKind::ALL {
+        for sum_path in [
+            parent.join(format!("{name}.{}", kind.extension())),
+            parent.join(kind.list_name()),
+        ] {
+            let Ok(contents) = read_checksum_file(&sum_path) else {
+                continue;
+            };
+            let entries = parse_checksum_file(&contents);
+            let found = entries
+                .iter()
+                .find(|(_, entry_name)| entry_name == name)
+                // A single line file next to the file may use a different name
+                .or_else(|| {
+                    (entries.len() == 1 && sum_path.extension().is_some()).then(|| &entries[0])
+                });
+            if let Some((checksum, _)) = found {
+                if checksum.len() == kind.hex_len() {
+                    return Some((kind, checksum.clone()));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn read_checksum_file(path: &Path) -> io::Result<String> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() > MAX_CHECKSUM_FILE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "checksum file is too large",
+        ));
+    }
+    let mut contents = String::new();
+    file.take(MAX_CHECKSUM_FILE_LEN)
+        .read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+fn file_name(path: &Path) -> io::Result<&str> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))
+}
+
+/// Path of the `name.ext` checksum file next to `path`
+pub fn checksum_file_path(path: &Path, kind: ChecksumKind) -> io::Result<PathBuf> {
+    let name = file_name(path)?;
+    Ok(path.with_file_name(format!("{name}.{}", kind.extension())))
+}
+
+/// Write a `name.ext` checksum file next to `path`, returning the path written to
+pub fn write_checksum_file(path: &Path, kind: ChecksumKind, checksum: &str) -> io::Result<PathBuf> {
+    let name = file_name(path)?;
+    let sum_path = checksum_file_path(path, kind)?;
+    let mut file = File::create(&sum_path)?;
+    file.write_all(format_checksum_line(checksum, name).as_bytes())?;
+    Ok(sum_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::{ChecksumKind, Digests, hash_reader, parse_checksum_file};
+
+    fn hash(data: &[u8]) -> Digests {
+        hash_reader(data, |_| -> Result<(), io::Error> { Ok(()) }).unwrap()
+    }
+
+    #[test]
+    fn known_digests() {
+        let digests = hash(b"abc");
+        assert_eq!(
+            digests.get(ChecksumKind::Md5),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            digests.get(ChecksumKind::Sha1),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            digests.get(ChecksumKind::Sha256),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            digests.get(ChecksumKind::Blake3),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+        );
+        for kind in ChecksumKind::ALL {
+            assert_eq!(digests.get(kind).len(), kind.hex_len());
+        }
+    }
+
+    #[test]
+    fn matches_pasted() {
+        let digests = hash(b"abc");
+        assert_eq!(
+            digests.matches(" A9993E364706816ABA3E25717850C26C9CD0D89D\n"),
+            Some(ChecksumKind::Sha1)
+        );
+        assert_eq!(digests.matches("a9993e36"), None);
+        assert_eq!(digests.matches(""), None);
+    }
+
+    #[test]
+    fn parse_file() {
+        let entries = parse_checksum_file(
+            "# comment\n\
+             ABCDEF01  text mode.iso\n\
+             0123abcd *binary mode.iso\n\
+             not a checksum\n",
+        );
+        assert_eq!(
+            entries,
+            [
+                ("abcdef01".to_string(), "text mode.iso".to_string()),
+                ("0123abcd".to_string(), "binary mode.iso".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,223 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{fs, path::PathBuf, time::Instant};
+
+use crate::{
+    fl,
+    tab::{Location, View},
+};
+
+/// Operation given on the command line, the application exits with its result
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CliOperationKind {
+    Copy,
+    Move,
+    Extract,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CliOperation {
+    pub kind: CliOperationKind,
+    pub paths: Vec<PathBuf>,
+    pub to: PathBuf,
+}
+
+/// State the application is opened in, parsed from the command line
+#[derive(Clone, Debug, Default)]
+pub struct Args {
+    /// Stay attached to the terminal instead of running in the background
+    pub no_daemon: bool,
+    pub locations: Vec<Location>,
+    pub uris: Vec<url::Url>,
+    /// Files and folders to select in a tab of their parent folder
+    pub selections: Vec<PathBuf>,
+    /// Files and folders to show the properties of
+    pub properties: Vec<PathBuf>,
+    /// Open a window with a single tab, even if the last session is restored otherwise
+    pub new_window: bool,
+    pub view_opt: Option<View>,
+    pub show_hidden: bool,
+    pub operation_opt: Option<CliOperation>,
+}
+
+impl Args {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--no-daemon" => parsed.no_daemon = true,
+                "--trash" => parsed.locations.push(Location::Trash),
+                "--recents" => parsed.locations.push(Location::Recents),
+                "--network" => parsed.locations.push(Location::Network(
+                    "network:///".to_string(),
+                    fl!("networks"),
+                    None,
+                )),
+                "--new-window" => parsed.new_window = true,
+                "--show-hidden" => parsed.show_hidden = true,
+                "--select" => {
+                    let path = canonicalize(value(&mut args, &arg)?)?;
+                    parsed.selections.push(path);
+                }
+                "--properties" => {
+                    let path = canonicalize(value(&mut args, &arg)?)?;
+                    parsed.properties.push(path);
+                }
+                "--search" => {
+                    let path = canonicalize(value(&mut args, &arg)?)?;
+                    if !path.is_dir() {
+                        return Err(format!("{} is not a folder", path.display()));
+                    }
+                    let term = value(&mut args, &arg)?;
+                    parsed
+                        .locations
+                        .push(Location::Search(path, term, false, Instant::now()));
+                }
+                "--view" => {
+                    parsed.view_opt = Some(match value(&mut args, &arg)?.as_str() {
+                        "list" => View::List,
+                        "grid" => View::Grid,
+                        view => return Err(format!("unknown view {view:?}, use list or grid")),
+                    });
+                }
+                "--copy" | "--move" | "--extract" => {
+                    if parsed.operation_opt.is_some() {
+                        return Err("only one of --copy, --move and --extract can be used".into());
+                    }
+                    let kind = match arg.as_str() {
+                        "--copy" => CliOperationKind::Copy,
+                        "--move" => CliOperationKind::Move,
+                        _ => CliOperationKind::Extract,
+                    };
+                    // Sources are followed by the destination folder
+                    let mut paths = Vec::new();
+                    while let Some(path) = args.next_if(|arg| !arg.starts_with("--")) {
+                        paths.push(canonicalize(path)?);
+                    }
+                    let to = match paths.pop() {
+                        Some(to) if !paths.is_empty() => to,
+                        _ => return Err(format!("{arg} needs sources and a destination folder")),
+                    };
+                    if !to.is_dir() {
+                        return Err(format!("{} is not a folder", to.display()));
+                    }
+                    parsed.operation_opt = Some(CliOperation { kind, paths, to });
+                }
+                _ if arg.starts_with("--") => return Err(format!("unknown option {arg:?}")),
+                _ => {
+                    //TODO: support more URLs
+                    let path = match url::Url::parse(&arg) {
+                        Ok(url) if url.scheme() == "file" => {
+                            if let Ok(path) = url.to_file_path() {
+                                path
+                            } else {
+                                log::warn!("invalid argument {arg:?}");
+                                continue;
+                            }
+                        }
+                        Ok(url) => {
+                            parsed.uris.push(url);
+                            continue;
+                        }
+                        _ => PathBuf::from(arg),
+                    };
+                    match fs::canonicalize(&path) {
+                        Ok(absolute) => parsed.locations.push(Location::Path(absolute)),
+                        Err(err) => {
+                            log::warn!("failed to canonicalize {}: {}", path.display(), err);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Whether nothing was given to open, so the tabs of the last session can be restored
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+            && self.uris.is_empty()
+            && self.selections.is_empty()
+            && self.properties.is_empty()
+            && !self.new_window
+            && self.operation_opt.is_none()
+    }
+}
+
+fn value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("{option} needs a value"))
+}
+
+fn canonicalize(path: String) -> Result<PathBuf, String> {
+    fs::canonicalize(&path).map_err(|err| format!("failed to find {path}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<S: AsRef<str>>(args: &[S]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.as_ref().to_string()))
+    }
+
+    #[test]
+    fn parses_view_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_str = dir.path().to_str().unwrap();
+        let args = parse(&[
+            "--view",
+            "list",
+            "--show-hidden",
+            "--search",
+            dir_str,
+            "report",
+            "--no-daemon",
+        ])
+        .unwrap();
+        assert_eq!(args.view_opt, Some(View::List));
+        assert!(args.show_hidden);
+        assert!(args.no_daemon);
+        match &args.locations[..] {
+            [Location::Search(path, term, ..)] => {
+                assert_eq!(*path, fs::canonicalize(dir.path()).unwrap());
+                assert_eq!(term, "report");
+            }
+            locations => panic!("unexpected locations {locations:?}"),
+        }
+        assert!(!args.is_empty());
+
+        assert!(parse(&["--view", "table"]).is_err());
+        assert!(parse(&["--search", dir_str]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+    }
+
+    #[test]
+    fn parses_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = fs::canonicalize(dir.path()).unwrap();
+        let a = dir.join("a.txt");
+        let b = dir.join("b.txt");
+        let to = dir.join("to");
+        fs::write(&a, b"a").unwrap();
+        fs::write(&b, b"b").unwrap();
+        fs::create_dir(&to).unwrap();
+        let [a_str, b_str, to_str] = [&a, &b, &to].map(|path| path.to_str().unwrap());
+
+        let args = parse(&["--copy", a_str, b_str, to_str, "--no-daemon"]).unwrap();
+        assert!(args.no_daemon);
+        assert_eq!(
+            args.operation_opt,
+            Some(CliOperation {
+                kind: CliOperationKind::Copy,
+                paths: vec![a.clone(), b.clone()],
+                to: to.clone(),
+            })
+        );
+
+        // At least one source is needed, and the destination is a folder
+        assert!(parse(&["--move", to_str]).is_err());
+        assert!(parse(&["--move", to_str, a_str]).is_err());
+        assert!(parse(&["--copy", a_str, to_str, "--extract", b_str, to_str]).is_err());
+    }
+}
@@ -2,14 +2,21 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use cosmic::iced::clipboard::mime::{AllowedMimeTypes, AsMimeTypes};
+use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
     error::Error,
+    fs,
     path::{Path, PathBuf},
-    str,
+    process, str, thread,
+    time::Duration,
 };
 use url::Url;
 
+use crate::config::SessionTab;
+
+const TAB_MIME: &str = "application/x-cosmic-files-tab";
+
 #[derive(Clone, Copy, Debug)]
 pub enum ClipboardKind {
     Copy,
@@ -162,3 +169,136 @@ impl TryFrom<(Vec<u8>, String)> for ClipboardPaste {
         Ok(Self { kind, paths })
     }
 }
+
+/// Tab dragged from the tab bar
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TabDrag {
+    /// Process of the window the tab is dragged from, as each window is its own process
+    pub pid: u32,
+    /// Identifies the drag within that process
+    pub id: u64,
+    pub tab: SessionTab,
+}
+
+impl TabDrag {
+    /// Tell the window the tab was dragged from that the tab was opened, so it closes its tab
+    pub fn confirm_drop(&self) {
+        if let Some(path) = tab_drop_confirmation(self.pid, self.id) {
+            if let Err(err) = fs::File::create(&path) {
+                log::warn!("failed to create {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+/// File created by the window that opened a tab dragged from another process
+fn tab_drop_confirmation(pid: u32, id: u64) -> Option<PathBuf> {
+    dirs::runtime_dir().map(|dir| dir.join(format!("cosmic-files-tab-drop-{pid}-{id}")))
+}
+
+/// Wait briefly for another window to confirm that it opened a tab dragged from this process,
+/// as the drag may finish before the drop is handled. This blocks, so it should be called from a
+/// blocking task.
+pub fn wait_tab_drop(id: u64) -> bool {
+    let Some(path) = tab_drop_confirmation(process::id(), id) else {
+        return false;
+    };
+    for _ in 0..20 {
+        if fs::remove_file(&path).is_ok() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    false
+}
+
+impl AsMimeTypes for TabDrag {
+    fn available(&self) -> Cow<'static, [String]> {
+        Cow::from(vec![TAB_MIME.to_string()])
+    }
+
+    fn as_bytes(&self, mime_type: &str) -> Option<Cow<'static, [u8]>> {
+        if mime_type != TAB_MIME {
+            return None;
+        }
+        match ron::to_string(self) {
+            Ok(text) => Some(Cow::from(text.into_bytes())),
+            Err(err) => {
+                log::warn!("failed to serialize dragged tab: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// Data dropped on a tab of the tab bar
+#[derive(Clone, Debug)]
+pub enum TabDrop {
+    Paths(ClipboardPaste),
+    Tab(Box<TabDrag>),
+}
+
+impl AllowedMimeTypes for TabDrop {
+    fn allowed() -> Cow<'static, [String]> {
+        let mut allowed = vec![TAB_MIME.to_string()];
+        allowed.extend_from_slice(&ClipboardPaste::allowed());
+        Cow::from(allowed)
+    }
+}
+
+impl TryFrom<(Vec<u8>, String)> for TabDrop {
+    type Error = Box<dyn Error>;
+    fn try_from(value: (Vec<u8>, String)) -> Result<Self, Self::Error> {
+        if value.1 == TAB_MIME {
+            let text = str::from_utf8(&value.0)?;
+            Ok(Self::Tab(Box::new(ron::from_str(text)?)))
+        } else {
+            ClipboardPaste::try_from(value).map(Self::Paths)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{SessionLocation, TabConfig};
+
+    #[test]
+    fn tab_drag_round_trip() {
+        let drag = TabDrag {
+            pid: 42,
+            id: 7,
+            tab: SessionTab {
+                history: vec![
+                    SessionLocation::Path(PathBuf::from("/home/user")),
+                    SessionLocation::Trash,
+                ],
+                history_i: 1,
+                config: TabConfig::default(),
+                scroll_y: 120,
+                selected: vec![PathBuf::from("/home/user/file.txt")],
+                active: true,
+                pinned: false,
+                group: Some("Work".to_string()),
+            },
+        };
+        let bytes = drag.as_bytes(TAB_MIME).unwrap();
+        let Ok(TabDrop::Tab(dropped)) =
+            TabDrop::try_from((bytes.into_owned(), TAB_MIME.to_string()))
+        else {
+            panic!("tab drop not recognized");
+        };
+        assert_eq!(dropped.pid, 42);
+        assert_eq!(dropped.id, 7);
+        assert_eq!(dropped.tab, drag.tab);
+
+        let paths = ClipboardCopy::new(ClipboardKind::Copy, [Path::new("/tmp/a")]);
+        let bytes = paths.as_bytes("text/uri-list").unwrap();
+        let Ok(TabDrop::Paths(paste)) =
+            TabDrop::try_from((bytes.into_owned(), "text/uri-list".to_string()))
+        else {
+            panic!("path drop not recognized");
+        };
+        assert_eq!(paste.paths, [PathBuf::from("/tmp/a")]);
+    }
+}
@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use similar::{DiffTag, TextDiff};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use walkdir::WalkDir;
+
+use crate::err_str;
+
+/// Largest file that is compared line by line
+const MAX_TEXT_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Time after which the line diff falls back to a less minimal result
+const DIFF_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Which of the two compared items
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub const fn other(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+/// Two files or folders being compared, shown in their own window
+#[derive(Clone, Debug)]
+pub struct Compare {
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// Result of the comparison, `None` while it is running
+    pub result_opt: Option<Result<Comparison, String>>,
+}
+
+impl Compare {
+    pub const fn new(left: PathBuf, right: PathBuf) -> Self {
+        Self {
+            left,
+            right,
+            result_opt: None,
+        }
+    }
+
+    pub fn root(&self, side: Side) -> &Path {
+        match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    /// Lines of two text files, side by side
+    Text(Vec<DiffLine>),
+    /// Files that are not text or too large, which are only checked for equality
+    Binary { identical: bool },
+    /// Differences between two folders, identical files are left out
+    Folder(Vec<FolderEntry>),
+}
+
+/// Row of a side by side diff, with the line number and text of each side
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiffLine {
+    pub left: Option<(usize, String)>,
+    pub right: Option<(usize, String)>,
+    pub changed: bool,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Difference {
+    OnlyLeft,
+    OnlyRight,
+    /// A file on one side and a folder on the other
+    Kind,
+    Size,
+    /// Same size, but the checksums differ
+    Content,
+    /// Same contents, but modified at different times
+    Modified,
+}
+
+impl Difference {
+    /// Side that the item can be copied from to make both sides the same
+    pub const fn can_copy_from(self, side: Side) -> bool {
+        match self {
+            Self::OnlyLeft => matches!(side, Side::Left),
+            Self::OnlyRight => matches!(side, Side::Right),
+            Self::Kind => false,
+            Self::Size | Self::Content | Self::Modified => true,
+        }
+    }
+}
+
+/// Item that differs between two folders. Folders that exist on one side only are listed without
+/// their contents.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FolderEntry {
+    /// Path relative to the compared folders
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub difference: Difference,
+}
+
+/// Compare two files or two folders. This reads them completely, so it should be called from a
+/// blocking task.
+pub fn compare(left: &Path, right: &Path) -> Result<Comparison, String> {
+    let left_metadata = fs::metadata(left).map_err(err_str)?;
+    let right_metadata = fs::metadata(right).map_err(err_str)?;
+    if left_metadata.is_dir() && right_metadata.is_dir() {
+        return Ok(Comparison::Folder(compare_folders(left, right)));
+    }
+
+    if left_metadata.len() <= MAX_TEXT_SIZE && right_metadata.len() <= MAX_TEXT_SIZE {
+        let left_data = fs::read(left).map_err(err_str)?;
+        let right_data = fs::read(right).map_err(err_str)?;
+        if let (Some(left_text), Some(right_text)) = (as_text(&left_data), as_text(&right_data)) {
+            return Ok(Comparison::Text(diff_lines(left_text, right_text)));
+        }
+        return Ok(Comparison::Binary {
+            identical: left_data == right_data,
+        });
+    }
+
+    let identical = left_metadata.len() == right_metadata.len()
+        && hash_file(left).map_err(err_str)? == hash_file(right).map_err(err_str)?;
+    Ok(Comparison::Binary { identical })
+}
+
+fn as_text(data: &[u8]) -> Option<&str> {
+    if data.contains(&0) {
+        return None;
+    }
+    std::str::from_utf8(data).ok()
+}
+
+fn hash_file(path: &Path) -> io::Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(File::open(path)?)?;
+    Ok(hasher.finalize())
+}
+
+/// Side by side diff of two texts. Changed lines are paired up where possible.
+pub fn diff_lines(left: &str, right: &str) -> Vec<DiffLine> {
+    let diff = TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(left, right);
+    let left_lines = diff.old_slices();
+    let right_lines = diff.new_slices();
+    let line = |lines: &[&str], i: usize| {
+        let text = lines[i].trim_end_matches(['\n', '\r']);
+        (i + 1, text.to_string())
+    };
+
+    let mut rows = Vec::new();
+    for op in diff.ops() {
+        let (tag, left_range, right_range) = op.as_tag_tuple();
+        let changed = tag != DiffTag::Equal;
+        let count = left_range.len().max(right_range.len());
+        for offset in 0..count {
+            let left_i = left_range.start + offset;
+            let right_i = right_range.start + offset;
+            rows.push(DiffLine {
+                left: (left_i < left_range.end).then(|| line(left_lines, left_i)),
+                right: (right_i < right_range.end).then(|| line(right_lines, right_i)),
+                changed,
+            });
+        }
+    }
+    rows
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TreeEntry {
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Items below a folder by their relative paths. Items that cannot be read are skipped.
+fn tree(root: &Path) -> BTreeMap<PathBuf, TreeEntry> {
+    let mut entries = BTreeMap::new();
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = match entry {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::warn!("failed to read entry in {}: {}", root.display(), err);
+                continue;
+            }
+        };
+        let metadata = match entry.metadata() {
+            Ok(ok) => ok,
+            Err(err) => {
+                log::warn!(
+                    "failed to read metadata of {}: {}",
+                    entry.path().display(),
+                    err
+                );
+                continue;
+            }
+        };
+        let Ok(path) = entry.path().strip_prefix(root) else {
+            continue;
+        };
+        entries.insert(
+            path.to_path_buf(),
+            TreeEntry {
+                is_dir: metadata.is_dir(),
+                size: metadata.len(),
+                modified: metadata.modified().ok(),
+            },
+        );
+    }
+    entries
+}
+
+pub fn compare_folders(left: &Path, right: &Path) -> Vec<FolderEntry> {
+    let left_tree = tree(left);
+    let right_tree = tree(right);
+    // Paths sort by component, so the contents of a folder directly follow it
+    let paths: BTreeSet<&PathBuf> = left_tree.keys().chain(right_tree.keys()).collect();
+
+    let mut entries = Vec::new();
+    let mut one_sided_opt: Option<&Path> = None;
+    for path in paths {
+        if one_sided_opt.is_some_and(|one_sided| path.starts_with(one_sided)) {
+            continue;
+        }
+        let (is_dir, difference) = match (left_tree.get(path), right_tree.get(path)) {
+            (Some(entry), None) => (entry.is_dir, Difference::OnlyLeft),
+            (None, Some(entry)) => (entry.is_dir, Difference::OnlyRight),
+            (Some(left_entry), Some(right_entry)) => {
+                match file_difference(&left.join(path), &right.join(path), left_entry, right_entry)
+                {
+                    Some(difference) => (left_entry.is_dir, difference),
+                    None => continue,
+                }
+            }
+            (None, None) => continue,
+        };
+        if is_dir && matches!(difference, Difference::OnlyLeft | Difference::OnlyRight) {
+            one_sided_opt = Some(path);
+        }
+        entries.push(FolderEntry {
+            path: path.clone(),
+            is_dir,
+            difference,
+        });
+    }
+    entries
+}
+
+/// Difference between items that exist on both sides, by size, then by modification time, then
+/// by checksum
+fn file_difference(
+    left_path: &Path,
+    right_path: &Path,
+    left: &TreeEntry,
+    right: &TreeEntry,
+) -> Option<Difference> {
+    if left.is_dir != right.is_dir {
+        return Some(Difference::Kind);
+    }
+    if left.is_dir {
+        return None;
+    }
+    if left.size != right.size {
+        return Some(Difference::Size);
+    }
+    if left.modified == right.modified {
+        return None;
+    }
+    match (hash_file(left_path), hash_file(right_path)) {
+        (Ok(left_hash), Ok(right_hash)) if left_hash == right_hash => Some(Difference::Modified),
+        (Ok(_), Ok(_)) => Some(Difference::Content),
+        (Err(err), _) | (_, Err(err)) => {
+            log::warn!(
+                "failed to compare {} and {}: {}",
+                left_path.display(),
+                right_path.display(),
+                err
+            );
+            Some(Difference::Content)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_diff() {
+        let rows = diff_lines("a\nb\nc\nd\n", "a\nB\nc\nd\ne\n");
+        let text = |side: &Option<(usize, String)>| side.as_ref().map(|(_, text)| text.as_str());
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| (text(&row.left), text(&row.right), row.changed))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (Some("a"), Some("a"), false),
+                (Some("b"), Some("B"), true),
+                (Some("c"), Some("c"), false),
+                (Some("d"), Some("d"), false),
+                (None, Some("e"), true),
+            ]
+        );
+        assert_eq!(rows[4].right, Some((5, "e".to_string())));
+    }
+
+    #[test]
+    fn folder_diff() {
+        let left = tempfile::tempdir().unwrap();
+        let right = tempfile::tempdir().unwrap();
+        let old = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let write = |path: PathBuf, contents: &str, modified_opt: Option<SystemTime>| {
+            fs::write(&path, contents).unwrap();
+            if let Some(modified) = modified_opt {
+                File::options()
+                    .write(true)
+                    .open(&path)
+                    .unwrap()
+                    .set_modified(modified)
+                    .unwrap();
+            }
+        };
+        write(left.path().join("same"), "same", Some(old));
+        write(right.path().join("same"), "same", Some(old));
+        write(left.path().join("size"), "short", None);
+        write(right.path().join("size"), "longer", None);
+        write(left.path().join("left"), "", None);
+        fs::create_dir_all(right.path().join("dir/nested")).unwrap();
+        write(right.path().join("dir/nested/file"), "", None);
+        write(left.path().join("content"), "aaaa", Some(old));
+        write(right.path().join("content"), "bbbb", None);
+        write(left.path().join("touched"), "data", Some(old));
+        write(right.path().join("touched"), "data", None);
+
+        let entries = compare_folders(left.path(), right.path());
+        let summary: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.path.to_str().unwrap(), entry.difference))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("content", Difference::Content),
+                ("dir", Difference::OnlyRight),
+                ("left", Difference::OnlyLeft),
+                ("size", Difference::Size),
+                ("touched", Difference::Modified),
+            ]
+        );
+        assert!(entries[1].is_dir);
+    }
+}
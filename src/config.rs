@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
-use std::{any::TypeId, num::NonZeroU16, path::PathBuf};
+use std::{any::TypeId, collections::BTreeMap, num::NonZeroU16, path::PathBuf};
 
 use cosmic::{
     Application,
@@ -102,6 +102,20 @@ impl Favorite {
     }
 }
 
+/// When to generate thumbnails for files on a network or GVFS mount
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum RemoteThumbnails {
+    #[default]
+    Never,
+    /// Only when the server is on the local network
+    LocalNetwork,
+    Always,
+}
+
+impl RemoteThumbnails {
+    pub const ALL: [Self; 3] = [Self::Never, Self::LocalNetwork, Self::Always];
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum TypeToSearch {
     Recursive,
@@ -112,6 +126,10 @@ pub enum TypeToSearch {
 #[serde(default)]
 pub struct State {
     pub sort_names: FxOrderMap<String, (HeadingOptions, bool)>,
+    /// Desktop icon arrangement by output name
+    pub desktop_layouts: FxOrderMap<String, DesktopLayout>,
+    /// Windows closed since the session was last restored, if restoring the session is enabled
+    pub sessions: Vec<SessionWindow>,
 }
 
 impl Default for State {
@@ -123,6 +141,8 @@ impl Default for State {
                     (HeadingOptions::Modified, false),
                 )
             })),
+            desktop_layouts: FxOrderMap::default(),
+            sessions: Vec::new(),
         }
     }
 }
@@ -165,9 +185,19 @@ pub struct Config {
     pub desktop: DesktopConfig,
     pub thumb_cfg: ThumbCfg,
     pub favorites: Vec<Favorite>,
+    pub preserve_metadata: bool,
+    /// Thumbnail policy for remote mounts, keyed by mount source
+    pub remote_thumbnails: FxOrderMap<String, RemoteThumbnails>,
+    /// Reopen the tabs of the last closed window when launched without arguments
+    pub restore_session: bool,
+    /// Number of times file contents are overwritten when securely deleting
+    pub secure_delete_passes: u32,
     pub show_details: bool,
     pub tab: TabConfig,
+    /// Permanently delete items that were trashed more than this many days ago
+    pub trash_purge_days: Option<u32>,
     pub type_to_search: TypeToSearch,
+    pub verify_copies: bool,
 }
 
 impl Config {
@@ -204,9 +234,12 @@ impl Config {
         TabConfig {
             folders_first: self.dialog.folders_first,
             icon_sizes: self.dialog.icon_sizes,
+            hover_playback: false,
             military_time: self.tab.military_time,
+            recents_limit: self.tab.recents_limit,
             show_hidden: self.dialog.show_hidden,
             single_click: false,
+            trash_by_origin: self.tab.trash_by_origin,
             view: self.dialog.view,
         }
     }
@@ -227,9 +260,15 @@ impl Default for Config {
                 Favorite::Pictures,
                 Favorite::Videos,
             ],
+            preserve_metadata: false,
+            remote_thumbnails: FxOrderMap::default(),
+            restore_session: false,
+            secure_delete_passes: 3,
             show_details: false,
             tab: TabConfig::default(),
+            trash_purge_days: None,
             type_to_search: TypeToSearch::Recursive,
+            verify_copies: false,
         }
     }
 }
@@ -242,6 +281,8 @@ pub struct DesktopConfig {
     pub show_content: bool,
     pub show_mounted_drives: bool,
     pub show_trash: bool,
+    /// Group loose files into piles
+    pub stack_by: StackBy,
 }
 
 impl Default for DesktopConfig {
@@ -252,6 +293,7 @@ impl Default for DesktopConfig {
             show_content: true,
             show_mounted_drives: false,
             show_trash: false,
+            stack_by: StackBy::Off,
         }
     }
 }
@@ -262,6 +304,94 @@ impl DesktopConfig {
     }
 }
 
+/// How files on the desktop are grouped into piles
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StackBy {
+    #[default]
+    Off,
+    /// Images, documents, archives and installers
+    Kind,
+    /// Day the file was modified
+    Date,
+}
+
+/// Arrangement of the desktop icons on one display
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct DesktopLayout {
+    /// Top left corners of icons moved by the user, in logical pixels, by item name
+    pub positions: BTreeMap<String, (i32, i32)>,
+    /// Sort of the icons without a position
+    pub sort: Option<(HeadingOptions, bool)>,
+}
+
+/// Location of a tab saved in the session. Searches are saved as the folder they searched.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum SessionLocation {
+    Network(String, String, Option<PathBuf>),
+    Path(PathBuf),
+    Recents,
+    Trash,
+}
+
+impl SessionLocation {
+    /// Returns `None` for desktop locations, which are not part of the session
+    pub fn new(location: &Location) -> Option<Self> {
+        match location {
+            Location::Desktop(..) => None,
+            Location::Network(uri, name, path_opt) => {
+                Some(Self::Network(uri.clone(), name.clone(), path_opt.clone()))
+            }
+            Location::Path(path) | Location::Search(path, ..) => Some(Self::Path(path.clone())),
+            Location::Recents => Some(Self::Recents),
+            Location::Trash => Some(Self::Trash),
+        }
+    }
+
+    pub fn location(&self) -> Location {
+        match self {
+            Self::Network(uri, name, path_opt) => {
+                Location::Network(uri.clone(), name.clone(), path_opt.clone())
+            }
+            Self::Path(path) => Location::Path(path.clone()),
+            Self::Recents => Location::Recents,
+            Self::Trash => Location::Trash,
+        }
+    }
+}
+
+/// Tab saved when the window closes, to be reopened on the next launch without arguments
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SessionTab {
+    /// Locations visited in the tab, with the current one at `history_i`
+    pub history: Vec<SessionLocation>,
+    pub history_i: usize,
+    pub config: TabConfig,
+    /// Vertical scroll position in logical pixels
+    pub scroll_y: u32,
+    pub selected: Vec<PathBuf>,
+    pub active: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Name of the tab group the tab belongs to
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+impl SessionTab {
+    pub fn location(&self) -> Option<Location> {
+        self.history
+            .get(self.history_i)
+            .map(SessionLocation::location)
+    }
+}
+
+/// Window saved when it closes. Each window is its own process, so every window adds itself.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SessionWindow {
+    pub tabs: Vec<SessionTab>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq, CosmicConfigEntry, Deserialize, Serialize)]
 #[serde(default)]
 pub struct DialogConfig {
@@ -294,6 +424,12 @@ pub struct ThumbCfg {
     pub jobs: NonZeroU16,
     pub max_mem_mb: NonZeroU16,
     pub max_size_mb: NonZeroU16,
+    /// Maximum size of the thumbnail cache, enforced by removing least recently used thumbnails
+    pub max_cache_mb: NonZeroU16,
+    /// Thumbnail jobs for files on remote mounts, reduced further on slow connections
+    pub remote_jobs: NonZeroU16,
+    /// Show folders with a preview of their contents in the grid view
+    pub folder_previews: bool,
 }
 
 impl Default for ThumbCfg {
@@ -302,6 +438,9 @@ impl Default for ThumbCfg {
             jobs: 4.try_into().unwrap(),
             max_mem_mb: 2000.try_into().unwrap(),
             max_size_mb: 64.try_into().unwrap(),
+            max_cache_mb: 1024.try_into().unwrap(),
+            remote_jobs: 2.try_into().unwrap(),
+            folder_previews: false,
         }
     }
 }
@@ -318,14 +457,20 @@ pub struct TabConfig {
     pub folders_first: bool,
     /// Icon zoom
     pub icon_sizes: IconSizes,
+    /// Play videos muted while hovering over them in the grid view
+    pub hover_playback: bool,
     #[serde(skip)]
     /// 24 hour clock; this is neither serialized nor deserialized because we use the user's global
     /// preference rather than save it
     pub military_time: bool,
+    /// Most recent files shown in Recents
+    pub recents_limit: usize,
     /// Show hidden files and folders
     pub show_hidden: bool,
     /// Single click to open
     pub single_click: bool,
+    /// List trashed items under the folder they were deleted from
+    pub trash_by_origin: bool,
     /// Selected view, grid or list
     pub view: View,
 }
@@ -335,9 +480,12 @@ impl Default for TabConfig {
         Self {
             folders_first: true,
             icon_sizes: IconSizes::default(),
+            hover_playback: false,
             military_time: false,
+            recents_limit: 50,
             show_hidden: false,
             single_click: false,
+            trash_by_origin: true,
             view: View::List,
         }
     }
@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::collections::{BTreeMap, HashSet};
+
+/// Cells that desktop icons are placed in when they have no position of their own. Points are
+/// the top left corners of icons, in logical pixels from the top left of the desktop.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Grid {
+    /// Space before the first row and column
+    pub padding: i32,
+    pub item_width: i32,
+    pub item_height: i32,
+    /// Distance between the left edges of neighbouring columns
+    pub column_step: i32,
+    /// Distance between the top edges of neighbouring rows
+    pub row_step: i32,
+    pub cols: usize,
+    pub rows: usize,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Grid {
+    /// Cell at `index` when filling columns from top to bottom. When all cells are used, the
+    /// next page continues below the first.
+    pub fn flow_cell(&self, index: usize) -> (usize, usize) {
+        let rows = self.rows.max(1);
+        let page_size = rows * self.cols.max(1);
+        let page = index / page_size;
+        let within = index % page_size;
+        (page * rows + within % rows, within / rows)
+    }
+
+    pub fn cell_point(&self, (row, col): (usize, usize)) -> (i32, i32) {
+        (
+            self.padding + col as i32 * self.column_step,
+            self.padding + row as i32 * self.row_step,
+        )
+    }
+
+    /// Cell whose top left corner is closest to `point`
+    pub fn nearest_cell(&self, (x, y): (i32, i32)) -> (usize, usize) {
+        let nearest = |offset: i32, step: i32| {
+            let offset = offset - self.padding;
+            if offset <= 0 || step <= 0 {
+                0
+            } else {
+                ((offset + step / 2) / step) as usize
+            }
+        };
+        let row = nearest(y, self.row_step);
+        let col = nearest(x, self.column_step).min(self.cols.saturating_sub(1));
+        (row, col)
+    }
+
+    /// Move `point` so that the icon is shown completely, for positions saved on a larger
+    /// display or with larger icons
+    pub fn clamp(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (
+            x.min(self.width - self.item_width).max(0),
+            y.min(self.height - self.item_height).max(0),
+        )
+    }
+}
+
+/// Points and cells of items in order. Items with a saved position keep it, the others fill the
+/// free cells in order.
+pub fn arrange(grid: &Grid, saved: &[Option<(i32, i32)>]) -> Vec<((i32, i32), (usize, usize))> {
+    let mut used = HashSet::new();
+    for point in saved.iter().flatten() {
+        used.insert(grid.nearest_cell(grid.clamp(*point)));
+    }
+
+    let mut index = 0;
+    saved
+        .iter()
+        .map(|saved_opt| match saved_opt {
+            Some(point) => {
+                let point = grid.clamp(*point);
+                (point, grid.nearest_cell(point))
+            }
+            None => loop {
+                let cell = grid.flow_cell(index);
+                index += 1;
+                if used.insert(cell) {
+                    break (grid.cell_point(cell), cell);
+                }
+            },
+        })
+        .collect()
+}
+
+/// Move each position to the closest cell that no other position was moved to
+pub fn align(
+    grid: &Grid,
+    positions: &BTreeMap<String, (i32, i32)>,
+) -> BTreeMap<String, (i32, i32)> {
+    // Align from the top left, so that icons near the corner keep their place
+    let mut order: Vec<_> = positions
+        .iter()
+        .map(|(name, point)| (name, grid.clamp(*point)))
+        .collect();
+    order.sort_by_key(|(_, (x, y))| (*x, *y));
+
+    let rows = (0..positions.len())
+        .map(|index| grid.flow_cell(index).0 + 1)
+        .max()
+        .unwrap_or(0)
+        .max(grid.rows);
+    let mut used = HashSet::new();
+    let mut aligned = BTreeMap::new();
+    for (name, (x, y)) in order {
+        let distance = |cell: (usize, usize)| {
+            let (cell_x, cell_y) = grid.cell_point(cell);
+            i64::from(cell_x - x).pow(2) + i64::from(cell_y - y).pow(2)
+        };
+        let cell = (0..rows)
+            .flat_map(|row| (0..grid.cols.max(1)).map(move |col| (row, col)))
+            .filter(|cell| !used.contains(cell))
+            .min_by_key(|cell| distance(*cell));
+        if let Some(cell) = cell {
+            used.insert(cell);
+            aligned.insert(name.clone(), grid.cell_point(cell));
+        }
+    }
+    aligned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: Grid = Grid {
+        padding: 10,
+        item_width: 80,
+        item_height: 100,
+        column_step: 100,
+        row_step: 120,
+        cols: 3,
+        rows: 2,
+        width: 320,
+        height: 260,
+    };
+
+    #[test]
+    fn flow_fills_columns() {
+        let cells: Vec<_> = (0..8).map(|index| GRID.flow_cell(index)).collect();
+        assert_eq!(
+            cells,
+            [
+                (0, 0),
+                (1, 0),
+                (0, 1),
+                (1, 1),
+                (0, 2),
+                (1, 2),
+                (2, 0),
+                (3, 0)
+            ]
+        );
+        assert_eq!(GRID.cell_point((1, 2)), (210, 130));
+        assert_eq!(GRID.nearest_cell((170, 80)), (1, 2));
+        assert_eq!(GRID.nearest_cell((900, -5)), (0, 2));
+    }
+
+    #[test]
+    fn saved_positions_are_kept() {
+        let arranged = arrange(&GRID, &[None, Some((15, 20)), None, Some((1000, 1000))]);
+        assert_eq!(
+            arranged,
+            [
+                ((10, 130), (1, 0)),
+                ((15, 20), (0, 0)),
+                ((110, 10), (0, 1)),
+                ((240, 160), (1, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn align_to_free_cells() {
+        let positions = BTreeMap::from([
+            ("a".to_string(), (20, 15)),
+            ("b".to_string(), (30, 20)),
+            ("c".to_string(), (190, 140)),
+        ]);
+        let aligned = align(&GRID, &positions);
+        assert_eq!(aligned["a"], (10, 10));
+        assert_eq!(aligned["b"], (110, 10));
+        assert_eq!(aligned["c"], (210, 130));
+    }
+}
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use mime_guess::{Mime, mime};
+use std::{fs::Metadata, path::Path, time::SystemTime};
+
+use crate::{config::StackBy, fl, mime_icon::mime_for_path};
+
+/// Pile that loose files on the desktop are grouped into
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Stack {
+    Images,
+    Documents,
+    Archives,
+    Installers,
+    Today,
+    Yesterday,
+    ThisWeek,
+    ThisMonth,
+    Older,
+}
+
+impl Stack {
+    pub fn name(self) -> String {
+        match self {
+            Self::Images => fl!("stack-images"),
+            Self::Documents => fl!("stack-documents"),
+            Self::Archives => fl!("stack-archives"),
+            Self::Installers => fl!("stack-installers"),
+            Self::Today => fl!("today"),
+            Self::Yesterday => fl!("stack-yesterday"),
+            Self::ThisWeek => fl!("stack-this-week"),
+            Self::ThisMonth => fl!("stack-this-month"),
+            Self::Older => fl!("stack-older"),
+        }
+    }
+
+    pub const fn icon_name(self) -> &'static str {
+        match self {
+            Self::Images => "image-x-generic",
+            Self::Documents => "x-office-document",
+            Self::Archives => "package-x-generic",
+            Self::Installers => "system-software-install",
+            Self::Today | Self::Yesterday | Self::ThisWeek | Self::ThisMonth | Self::Older => {
+                "x-office-calendar"
+            }
+        }
+    }
+}
+
+/// Pile for a file in the desktop folder, folders are never stacked
+pub fn stack_for(path: &Path, metadata: &Metadata, stack_by: StackBy) -> Option<Stack> {
+    if metadata.is_dir() {
+        return None;
+    }
+    match stack_by {
+        StackBy::Off => None,
+        StackBy::Kind => kind_stack(&mime_for_path(path, Some(metadata), false)),
+        StackBy::Date => {
+            let modified = metadata.modified().ok()?;
+            Some(date_stack(modified, Local::now().date_naive()))
+        }
+    }
+}
+
+/// Pile for files of a kind, other files are left loose
+pub fn kind_stack(mime: &Mime) -> Option<Stack> {
+    if mime.type_() == mime::IMAGE {
+        return Some(Stack::Images);
+    }
+    let essence = mime.essence_str();
+    if mime.type_() == mime::TEXT
+        || essence == "application/pdf"
+        || essence == "application/rtf"
+        || essence == "application/epub+zip"
+        || essence == "application/msword"
+        || essence.starts_with("application/vnd.ms-")
+        || essence.starts_with("application/vnd.oasis.opendocument.")
+        || essence.starts_with("application/vnd.openxmlformats-officedocument.")
+    {
+        return Some(Stack::Documents);
+    }
+    match essence {
+        "application/vnd.debian.binary-package"
+        | "application/x-rpm"
+        | "application/vnd.flatpak"
+        | "application/vnd.flatpak.ref"
+        | "application/vnd.appimage"
+        | "application/x-iso9660-appimage"
+        | "application/x-msi"
+        | "application/x-msdownload"
+        | "application/x-ms-dos-executable" => Some(Stack::Installers),
+        "application/zip"
+        | "application/gzip"
+        | "application/x-7z-compressed"
+        | "application/x-bzip"
+        | "application/x-bzip2"
+        | "application/x-compressed-tar"
+        | "application/x-bzip2-compressed-tar"
+        | "application/x-xz"
+        | "application/x-xz-compressed-tar"
+        | "application/x-zstd-compressed-tar"
+        | "application/zstd"
+        | "application/x-tar"
+        | "application/vnd.rar"
+        | "application/x-rar-compressed" => Some(Stack::Archives),
+        _ => None,
+    }
+}
+
+/// Pile for files by the day they were modified
+pub fn date_stack(modified: SystemTime, today: NaiveDate) -> Stack {
+    let date = DateTime::<Local>::from(modified).date_naive();
+    let days = today.signed_duration_since(date).num_days();
+    if days <= 0 {
+        Stack::Today
+    } else if days == 1 {
+        Stack::Yesterday
+    } else if days < 7 {
+        Stack::ThisWeek
+    } else if date.year() == today.year() && date.month() == today.month() {
+        Stack::ThisMonth
+    } else {
+        Stack::Older
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+
+    #[test]
+    fn stacks_by_kind() {
+        let stack = |mime: &str| kind_stack(&mime.parse().unwrap());
+        assert_eq!(stack("image/png"), Some(Stack::Images));
+        assert_eq!(stack("text/plain"), Some(Stack::Documents));
+        assert_eq!(
+            stack("application/vnd.oasis.opendocument.text"),
+            Some(Stack::Documents)
+        );
+        assert_eq!(stack("application/x-compressed-tar"), Some(Stack::Archives));
+        assert_eq!(
+            stack("application/vnd.debian.binary-package"),
+            Some(Stack::Installers)
+        );
+        assert_eq!(stack("audio/ogg"), None);
+    }
+
+    #[test]
+    fn stacks_by_date() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap();
+        let stack = |year, month, day| {
+            let date = NaiveDate::from_ymd_opt(year, month, day).unwrap();
+            let time = Local
+                .from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap()))
+                .unwrap();
+            date_stack(time.into(), today)
+        };
+        assert_eq!(stack(2024, 5, 20), Stack::Today);
+        assert_eq!(stack(2024, 5, 19), Stack::Yesterday);
+        assert_eq!(stack(2024, 5, 14), Stack::ThisWeek);
+        assert_eq!(stack(2024, 5, 2), Stack::ThisMonth);
+        assert_eq!(stack(2024, 4, 30), Stack::Older);
+    }
+}
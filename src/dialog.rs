@@ -26,7 +26,6 @@ use notify_debouncer_full::{
     DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
     notify::{self, RecommendedWatcher},
 };
-use recently_used_xbel::update_recently_used;
 use rustc_hash::{FxHashMap, FxHashSet};
 use std::{
     any::TypeId,
@@ -46,6 +45,7 @@ use crate::{
     localize::LANGUAGE_SORTER,
     menu,
     mounter::{MOUNTERS, MounterItem, MounterItems, MounterKey, MounterMessage},
+    recents::{self, RecentUse},
     tab::{self, ItemMetadata, Location, Tab},
     zoom::{zoom_in_view, zoom_out_view, zoom_to_default},
 };
@@ -1564,17 +1564,18 @@ impl Application for App {
             },
             Message::Open => {
                 let mut paths = Vec::new();
+                let mut recent_uses = Vec::new();
                 if let Some(items) = self.tab.items_opt() {
                     for item in items {
                         if item.selected {
                             if let Some(path) = item.path_opt() {
                                 paths.push(path.clone());
-                                let _ = update_recently_used(
+                                recent_uses.push(RecentUse::new(
                                     path,
-                                    Self::APP_ID.to_string(),
-                                    "cosmic-files".to_string(),
-                                    None,
-                                );
+                                    &item.mime,
+                                    Self::APP_ID,
+                                    "cosmic-files",
+                                ));
                             }
                         }
                     }
@@ -1601,7 +1602,19 @@ impl Application for App {
                 // If there are proper matching items, return them
                 if !paths.is_empty() {
                     self.result_opt = Some(DialogResult::Open(paths));
-                    return window::close(self.flags.window_id);
+                    // Register the chosen files in the background, as this writes
+                    // `recently-used.xbel`
+                    let add_recents = cosmic::task::future(async move {
+                        if let Err(err) = tokio::task::spawn_blocking(move || {
+                            recents::add_recents(&recent_uses);
+                        })
+                        .await
+                        {
+                            log::warn!("failed to add recent files: {err}");
+                        }
+                        Message::None
+                    });
+                    return Task::batch([add_recents, window::close(self.flags.window_id)]);
                 }
 
                 // If we are in directory mode, return the current directory
@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use image::{
+    DynamicImage, ImageReader, RgbaImage,
+    imageops::{self, FilterType},
+};
+use mime_guess::mime;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    mime_icon::mime_for_path,
+    photo,
+    thumbnail_cacher::{CachedThumbnail, ThumbnailCacher, ThumbnailSize},
+};
+
+/// Pictures that replace the preview of the folder containing them
+const CUSTOM_NAMES: [&str; 4] = [".folder.png", ".folder.jpg", "folder.png", "folder.jpg"];
+
+/// Most images shown in a mosaic
+const MOSAIC_IMAGES: usize = 4;
+
+/// Entries examined when looking for images, so that large folders stay cheap
+const MAX_ENTRIES: usize = 1000;
+
+/// Picture shown for a folder in the grid view
+#[derive(Debug)]
+pub enum FolderPreview {
+    /// Icon name from the `Icon=` key of a `.directory` file
+    Icon(String),
+    Svg(PathBuf),
+    Image(RgbaImage),
+}
+
+impl FolderPreview {
+    /// Custom folder picture if there is one, otherwise a mosaic of the folder's images
+    pub fn new(path: &Path, size: u32, max_size: u64) -> Option<Self> {
+        match custom_picture(path) {
+            Some(Custom::Icon(name)) => return Some(Self::Icon(name)),
+            Some(Custom::File(file)) => {
+                return if file.extension().is_some_and(|ext| ext == "svg") {
+                    Some(Self::Svg(file))
+                } else {
+                    let image = decode(&file, max_size)?;
+                    Some(Self::Image(image.thumbnail(size, size).into_rgba8()))
+                };
+            }
+            None => {}
+        }
+
+        let tiles: Vec<_> = mosaic_images(path)
+            .iter()
+            .filter_map(|image_path| tile(image_path, max_size))
+            .take(MOSAIC_IMAGES)
+            .collect();
+        if tiles.is_empty() {
+            None
+        } else {
+            Some(Self::Image(mosaic(&tiles, size)))
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum Custom {
+    File(PathBuf),
+    Icon(String),
+}
+
+/// Custom folder picture, or an icon from a `.directory` file
+fn custom_picture(path: &Path) -> Option<Custom> {
+    for name in CUSTOM_NAMES {
+        let picture = path.join(name);
+        if picture.is_file() {
+            return Some(Custom::File(picture));
+        }
+    }
+
+    let directory_file = path.join(".directory");
+    if !directory_file.is_file() {
+        return None;
+    }
+    let entry = match freedesktop_entry_parser::parse_entry(&directory_file) {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::warn!("failed to parse {}: {}", directory_file.display(), err);
+            return None;
+        }
+    };
+    let icon = entry.section("Desktop Entry").attr("Icon")?.trim();
+    if icon.is_empty() {
+        None
+    } else if icon.contains('/') {
+        // Paths may be relative to the folder
+        Some(Custom::File(path.join(icon)))
+    } else {
+        Some(Custom::Icon(icon.to_string()))
+    }
+}
+
+/// First images in the folder by name
+fn mosaic_images(path: &Path) -> Vec<PathBuf> {
+    let entries = match fs::read_dir(path) {
+        Ok(ok) => ok,
+        Err(err) => {
+            log::debug!("failed to read directory {}: {}", path.display(), err);
+            return Vec::new();
+        }
+    };
+    let mut images: Vec<_> = entries
+        .flatten()
+        .take(MAX_ENTRIES)
+        .filter(|entry| {
+            !entry.file_name().as_encoded_bytes().starts_with(b".")
+                && entry.file_type().is_ok_and(|file_type| file_type.is_file())
+        })
+        .map(|entry| entry.path())
+        // Only the extension is used, reading every file would be too slow
+        .filter(|entry_path| mime_for_path(entry_path, None, true).type_() == mime::IMAGE)
+        .collect();
+    images.sort();
+    // Keep a few spare in case some fail to decode
+    images.truncate(MOSAIC_IMAGES * 2);
+    images
+}
+
+/// Image for one tile of the mosaic, using an existing thumbnail if possible
+fn tile(path: &Path, max_size: u64) -> Option<DynamicImage> {
+    if let Ok(cacher) = ThumbnailCacher::new(path, ThumbnailSize::Normal) {
+        if let CachedThumbnail::Valid((thumbnail_path, _)) = cacher.get_cached_thumbnail() {
+            match image::open(&thumbnail_path) {
+                Ok(image) => return Some(image),
+                Err(err) => {
+                    log::debug!("failed to open {}: {}", thumbnail_path.display(), err);
+                }
+            }
+        }
+    }
+    decode(path, max_size)
+}
+
+fn decode(path: &Path, max_size: u64) -> Option<DynamicImage> {
+    let size = fs::metadata(path).ok()?.len();
+    if size > max_size {
+        log::debug!(
+            "skipping {} in folder preview: file size {} is larger than {}",
+            path.display(),
+            size,
+            max_size
+        );
+        return None;
+    }
+    let result = ImageReader::open(path)
+        .and_then(ImageReader::with_guessed_format)
+        .map_err(image::ImageError::IoError)
+        .and_then(photo::decode_oriented);
+    match result {
+        Ok(image) => Some(image),
+        Err(err) => {
+            log::debug!("failed to decode {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Arrange up to four images in a square: one fills it, two are side by side, three have one
+/// on the left and two stacked on the right, and four make a grid
+fn mosaic(tiles: &[DynamicImage], size: u32) -> RgbaImage {
+    let gap = (size / 32).max(1);
+    let half = (size - gap) / 2;
+    let far = half + gap;
+    let cells: &[(u32, u32, u32, u32)] = match tiles.len() {
+        1 => &[(0, 0, size, size)],
+        2 => &[(0, 0, half, size), (far, 0, half, size)],
+        3 => &[
+            (0, 0, half, size),
+            (far, 0, half, half),
+            (far, far, half, half),
+        ],
+        _ => &[
+            (0, 0, half, half),
+            (far, 0, half, half),
+            (0, far, half, half),
+            (far, far, half, half),
+        ],
+    };
+
+    let mut canvas = RgbaImage::new(size, size);
+    for (tile, &(x, y, width, height)) in tiles.iter().zip(cells) {
+        let tile = tile
+            .resize_to_fill(width, height, FilterType::Triangle)
+            .into_rgba8();
+        imageops::overlay(&mut canvas, &tile, x.into(), y.into());
+    }
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn custom_pictures() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(custom_picture(dir.path()), None);
+
+        fs::write(
+            dir.path().join(".directory"),
+            "[Desktop Entry]\nIcon=folder-music\n",
+        )
+        .unwrap();
+        assert_eq!(
+            custom_picture(dir.path()),
+            Some(Custom::Icon("folder-music".to_string()))
+        );
+
+        fs::write(
+            dir.path().join(".directory"),
+            "[Desktop Entry]\nIcon=./cover.svg\n",
+        )
+        .unwrap();
+        assert_eq!(
+            custom_picture(dir.path()),
+            Some(Custom::File(dir.path().join("./cover.svg")))
+        );
+
+        fs::write(dir.path().join("folder.jpg"), b"").unwrap();
+        assert_eq!(
+            custom_picture(dir.path()),
+            Some(Custom::File(dir.path().join("folder.jpg")))
+        );
+    }
+
+    #[test]
+    fn mosaic_layout() {
+        let red = DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba([255, 0, 0, 255])));
+        let blue = DynamicImage::ImageRgba8(RgbaImage::from_pixel(8, 8, Rgba([0, 0, 255, 255])));
+
+        let canvas = mosaic(&[red.clone()], 64);
+        assert_eq!(canvas.dimensions(), (64, 64));
+        assert_eq!(canvas.get_pixel(63, 63), &Rgba([255, 0, 0, 255]));
+
+        let canvas = mosaic(&[red.clone(), blue.clone()], 64);
+        assert_eq!(canvas.get_pixel(0, 63), &Rgba([255, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(63, 63), &Rgba([0, 0, 255, 255]));
+
+        let canvas = mosaic(&[red.clone(), blue.clone(), red], 64);
+        assert_eq!(canvas.get_pixel(0, 63), &Rgba([255, 0, 0, 255]));
+        assert_eq!(canvas.get_pixel(63, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(canvas.get_pixel(63, 63), &Rgba([255, 0, 0, 255]));
+        // Gap between the tiles is transparent
+        assert_eq!(canvas.get_pixel(32, 0)[3], 0);
+    }
+}
@@ -0,0 +1,226 @@
+//! A container that places each child at its own point, such as icons arranged on the desktop.
+
+use cosmic::{
+    Element, Renderer, Theme,
+    iced_core::{
+        Clipboard, Layout, Length, Point, Rectangle, Shell, Size, Vector, Widget,
+        event::{self, Event},
+        layout, mouse, overlay, renderer,
+        widget::{Operation, Tree},
+    },
+};
+
+/// Places children at points relative to its top left corner. Children are drawn in order, so
+/// later children are on top where they overlap.
+#[allow(missing_debug_implementations)]
+pub struct FreeLayout<'a, Message> {
+    children: Vec<Element<'a, Message>>,
+    points: Vec<Point>,
+    width: Length,
+    height: Length,
+}
+
+impl<'a, Message> FreeLayout<'a, Message> {
+    pub fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            points: Vec::new(),
+            width: Length::Fill,
+            height: Length::Fill,
+        }
+    }
+
+    #[must_use]
+    pub fn push(mut self, point: Point, child: impl Into<Element<'a, Message>>) -> Self {
+        self.children.push(child.into());
+        self.points.push(point);
+        self
+    }
+
+    #[must_use]
+    pub fn width(mut self, width: impl Into<Length>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    #[must_use]
+    pub fn height(mut self, height: impl Into<Length>) -> Self {
+        self.height = height.into();
+        self
+    }
+}
+
+impl<Message> Default for FreeLayout<'_, Message> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Message> Widget<Message, Theme, Renderer> for FreeLayout<'_, Message> {
+    fn children(&self) -> Vec<Tree> {
+        self.children.iter().map(Tree::new).collect()
+    }
+
+    fn diff(&mut self, tree: &mut Tree) {
+        tree.diff_children(&mut self.children);
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size::new(self.width, self.height)
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let limits = limits.width(self.width).height(self.height);
+        let child_limits = layout::Limits::new(Size::ZERO, Size::INFINITY);
+        let nodes = self
+            .children
+            .iter()
+            .zip(&mut tree.children)
+            .zip(&self.points)
+            .map(|((child, state), point)| {
+                child
+                    .as_widget()
+                    .layout(state, renderer, &child_limits)
+                    .move_to(*point)
+            })
+            .collect();
+        layout::Node::with_children(limits.resolve(self.width, self.height, Size::ZERO), nodes)
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn Operation,
+    ) {
+        operation.container(None, layout.bounds(), &mut |operation| {
+            for ((child, state), layout) in self
+                .children
+                .iter()
+                .zip(&mut tree.children)
+                .zip(layout.children())
+            {
+                child
+                    .as_widget()
+                    .operate(state, layout, renderer, operation);
+            }
+        });
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        self.children
+            .iter_mut()
+            .zip(&mut tree.children)
+            .zip(layout.children())
+            .map(|((child, state), layout)| {
+                child.as_widget_mut().on_event(
+                    state,
+                    event.clone(),
+                    layout,
+                    cursor,
+                    renderer,
+                    clipboard,
+                    shell,
+                    viewport,
+                )
+            })
+            .fold(event::Status::Ignored, event::Status::merge)
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.children
+            .iter()
+            .zip(&tree.children)
+            .zip(layout.children())
+            .map(|((child, state), layout)| {
+                child
+                    .as_widget()
+                    .mouse_interaction(state, layout, cursor, viewport, renderer)
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        for ((child, state), layout) in self
+            .children
+            .iter()
+            .zip(&tree.children)
+            .zip(layout.children())
+        {
+            // Only icons that are scrolled into view are drawn
+            if layout.bounds().intersects(viewport) {
+                child
+                    .as_widget()
+                    .draw(state, renderer, theme, style, layout, cursor, viewport);
+            }
+        }
+    }
+
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        translation: Vector,
+    ) -> Option<overlay::Element<'b, Message, Theme, Renderer>> {
+        overlay::from_children(&mut self.children, tree, layout, renderer, translation)
+    }
+
+    fn drag_destinations(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        dnd_rectangles: &mut cosmic::iced_core::clipboard::DndDestinationRectangles,
+    ) {
+        for ((child, state), layout) in self
+            .children
+            .iter()
+            .zip(&tree.children)
+            .zip(layout.children())
+        {
+            child
+                .as_widget()
+                .drag_destinations(state, layout, renderer, dnd_rectangles);
+        }
+    }
+}
+
+impl<'a, Message: 'a> From<FreeLayout<'a, Message>> for Element<'a, Message> {
+    fn from(free_layout: FreeLayout<'a, Message>) -> Self {
+        Element::new(free_layout)
+    }
+}
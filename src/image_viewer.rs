@@ -0,0 +1,459 @@
+//! An image that can be zoomed with the mouse wheel or by pinching, and panned by dragging.
+
+use cosmic::{
+    Element, Renderer, Theme,
+    iced::ContentFit,
+    iced_core::{
+        Clipboard, Layout, Length, Point, Rectangle, Shell, Size, Vector, Widget,
+        event::{self, Event},
+        layout, mouse,
+        renderer::{self, Renderer as _},
+        touch,
+        widget::{Tree, tree},
+    },
+    widget,
+};
+
+/// Largest zoom, in logical pixels per image pixel
+const MAX_SCALE: f32 = 16.0;
+
+/// Zoom factor of one step of the mouse wheel
+const WHEEL_FACTOR: f32 = 1.2;
+
+/// Pixel scroll deltas that count as one step of the mouse wheel
+const WHEEL_PIXELS: f32 = 50.0;
+
+/// How an image is scaled to the view
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ImageZoom {
+    /// Shrink the image to fit the view, never enlarging it
+    #[default]
+    Fit,
+    /// Logical pixels per image pixel, and the point of the image, from 0.0 to 1.0 on each axis,
+    /// that is shown at the center of the view
+    Scale { scale: f32, center: Point },
+}
+
+impl ImageZoom {
+    /// Show one image pixel per logical pixel, centered
+    pub const ORIGINAL: Self = Self::Scale {
+        scale: 1.0,
+        center: Point::new(0.5, 0.5),
+    };
+
+    /// Scale the image is shown at in a view of the given size
+    pub fn scale(self, image: Size, view: Size) -> f32 {
+        match self {
+            Self::Fit => fit_scale(image, view),
+            Self::Scale { scale, .. } => scale,
+        }
+    }
+
+    /// Zoom by `factor`, keeping the image point under `anchor` in place
+    fn zoom_at(self, factor: f32, anchor: Point, image: Size, view: Size) -> Self {
+        let scale = self.scale(image, view) * factor;
+        if scale <= fit_scale(image, view) {
+            return Self::Fit;
+        }
+        let scale = scale.min(MAX_SCALE);
+
+        let placed = placement(self, image, view);
+        let point = Point::new(
+            (anchor.x - placed.x) / placed.width,
+            (anchor.y - placed.y) / placed.height,
+        );
+        let size = image * scale;
+        let origin = Point::new(
+            anchor.x - point.x * size.width,
+            anchor.y - point.y * size.height,
+        );
+        Self::Scale {
+            scale,
+            center: Point::new(
+                (view.width / 2.0 - origin.x) / size.width,
+                (view.height / 2.0 - origin.y) / size.height,
+            ),
+        }
+        .clamped(image, view)
+    }
+
+    /// Move the image by `delta` logical pixels
+    fn pan(self, delta: Vector, image: Size, view: Size) -> Self {
+        match self {
+            Self::Fit => Self::Fit,
+            Self::Scale { scale, center } => {
+                let size = image * scale;
+                Self::Scale {
+                    scale,
+                    center: Point::new(
+                        center.x - delta.x / size.width,
+                        center.y - delta.y / size.height,
+                    ),
+                }
+                .clamped(image, view)
+            }
+        }
+    }
+
+    /// Move the center to where the image actually ends up, so that panning past an edge does
+    /// not have to be undone before the image moves again
+    fn clamped(self, image: Size, view: Size) -> Self {
+        match self {
+            Self::Fit => Self::Fit,
+            Self::Scale { scale, .. } => {
+                let placed = placement(self, image, view);
+                Self::Scale {
+                    scale,
+                    center: Point::new(
+                        (view.width / 2.0 - placed.x) / placed.width,
+                        (view.height / 2.0 - placed.y) / placed.height,
+                    ),
+                }
+            }
+        }
+    }
+}
+
+fn fit_scale(image: Size, view: Size) -> f32 {
+    (view.width / image.width)
+        .min(view.height / image.height)
+        .min(1.0)
+}
+
+/// Bounds of the image relative to the view. Images smaller than the view are centered, larger
+/// ones always cover it.
+fn placement(zoom: ImageZoom, image: Size, view: Size) -> Rectangle {
+    let size = image * zoom.scale(image, view);
+    let center = match zoom {
+        ImageZoom::Fit => Point::new(0.5, 0.5),
+        ImageZoom::Scale { center, .. } => center,
+    };
+    let axis = |view: f32, size: f32, center: f32| {
+        if size <= view {
+            (view - size) / 2.0
+        } else {
+            (view / 2.0 - center * size).clamp(view - size, 0.0)
+        }
+    };
+    Rectangle::new(
+        Point::new(
+            axis(view.width, size.width, center.x),
+            axis(view.height, size.height, center.y),
+        ),
+        size,
+    )
+}
+
+/// Local state of the [`ImageViewer`].
+#[derive(Default)]
+struct State {
+    /// Last cursor position while dragging
+    drag: Option<Point>,
+    /// Positions of the fingers touching the image
+    fingers: Vec<(touch::Finger, Point)>,
+    /// Zoom published since the widget was last rebuilt
+    pending: Option<ImageZoom>,
+}
+
+/// Shows an image at an [`ImageZoom`], reporting changes made with the mouse or touch.
+#[allow(missing_debug_implementations)]
+pub struct ImageViewer<'a, Message> {
+    content: Element<'a, Message>,
+    image_size: Size,
+    zoom: ImageZoom,
+    on_zoom: Option<Box<dyn Fn(ImageZoom) -> Message + 'a>>,
+}
+
+impl<'a, Message: 'static> ImageViewer<'a, Message> {
+    /// Creates an [`ImageViewer`] for an image with the given original size. The handle may have
+    /// a lower resolution, it is scaled as if it had the original size.
+    pub fn new(handle: widget::image::Handle, image_size: Size) -> Self {
+        Self {
+            content: widget::image(handle)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .content_fit(ContentFit::Fill)
+                .into(),
+            image_size,
+            zoom: ImageZoom::Fit,
+            on_zoom: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn zoom(mut self, zoom: ImageZoom) -> Self {
+        self.zoom = zoom;
+        self
+    }
+
+    /// The message to emit when the image is zoomed or panned.
+    #[must_use]
+    pub fn on_zoom(mut self, on_zoom: impl Fn(ImageZoom) -> Message + 'a) -> Self {
+        self.on_zoom = Some(Box::new(on_zoom));
+        self
+    }
+
+    fn publish(&self, state: &mut State, shell: &mut Shell<'_, Message>, zoom: ImageZoom) {
+        if let Some(on_zoom) = &self.on_zoom {
+            if state.pending.unwrap_or(self.zoom) != zoom {
+                state.pending = Some(zoom);
+                shell.publish(on_zoom(zoom));
+            }
+        }
+    }
+
+    /// The image is larger than the view, so it can be panned
+    fn pannable(&self, zoom: ImageZoom, view: Size) -> bool {
+        let placed = placement(zoom, self.image_size, view);
+        placed.width > view.width || placed.height > view.height
+    }
+}
+
+impl<Message: 'static> Widget<Message, Theme, Renderer> for ImageViewer<'_, Message> {
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&mut self, tree: &mut Tree) {
+        // The zoom passed in is up to date again
+        tree.state.downcast_mut::<State>().pending = None;
+        tree.diff_children(std::slice::from_mut(&mut self.content));
+    }
+
+    fn size(&self) -> Size<Length> {
+        Size::new(Length::Fill, Length::Fill)
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let view = limits.width(Length::Fill).height(Length::Fill).max();
+        let placed = placement(self.zoom, self.image_size, view);
+        let content = self.content.as_widget().layout(
+            &mut tree.children[0],
+            renderer,
+            &layout::Limits::new(placed.size(), placed.size()),
+        );
+        layout::Node::with_children(view, vec![content.move_to(placed.position())])
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _renderer: &Renderer,
+        _clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        _viewport: &Rectangle,
+    ) -> event::Status {
+        if self.on_zoom.is_none() || self.image_size.width <= 0.0 || self.image_size.height <= 0.0 {
+            return event::Status::Ignored;
+        }
+
+        let bounds = layout.bounds();
+        let view = bounds.size();
+        let image = self.image_size;
+        let state = tree.state.downcast_mut::<State>();
+        let zoom = state.pending.unwrap_or(self.zoom);
+
+        match event {
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                let Some(position) = cursor.position_in(bounds) else {
+                    return event::Status::Ignored;
+                };
+                let steps = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / WHEEL_PIXELS,
+                };
+                let new_zoom = zoom.zoom_at(WHEEL_FACTOR.powf(steps), position, image, view);
+                self.publish(state, shell, new_zoom);
+                event::Status::Captured
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                match cursor.position_over(bounds) {
+                    Some(position) if self.pannable(zoom, view) => {
+                        state.drag = Some(position);
+                        event::Status::Captured
+                    }
+                    _ => event::Status::Ignored,
+                }
+            }
+            Event::Mouse(mouse::Event::CursorMoved { position }) => {
+                let Some(last) = state.drag else {
+                    return event::Status::Ignored;
+                };
+                state.drag = Some(position);
+                self.publish(state, shell, zoom.pan(position - last, image, view));
+                event::Status::Captured
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                if state.drag.take().is_some() {
+                    event::Status::Captured
+                } else {
+                    event::Status::Ignored
+                }
+            }
+            Event::Touch(touch::Event::FingerPressed { id, position }) => {
+                if !bounds.contains(position) {
+                    return event::Status::Ignored;
+                }
+                state.fingers.push((id, position));
+                event::Status::Captured
+            }
+            Event::Touch(touch::Event::FingerMoved { id, position }) => {
+                let Some(index) = state.fingers.iter().position(|(finger, _)| *finger == id) else {
+                    return event::Status::Ignored;
+                };
+                let last = state.fingers[index].1;
+                state.fingers[index].1 = position;
+                let new_zoom = match state.fingers.as_slice() {
+                    [_] => zoom.pan(position - last, image, view),
+                    [_, _] => {
+                        // Pinch around the point between both fingers
+                        let other = state.fingers[1 - index].1;
+                        let last_distance = last.distance(other);
+                        if last_distance <= 0.0 {
+                            return event::Status::Captured;
+                        }
+                        let factor = position.distance(other) / last_distance;
+                        let anchor = Point::new(
+                            (position.x + other.x) / 2.0 - bounds.x,
+                            (position.y + other.y) / 2.0 - bounds.y,
+                        );
+                        zoom.zoom_at(factor, anchor, image, view).pan(
+                            (position - last) * 0.5,
+                            image,
+                            view,
+                        )
+                    }
+                    _ => return event::Status::Captured,
+                };
+                self.publish(state, shell, new_zoom);
+                event::Status::Captured
+            }
+            Event::Touch(
+                touch::Event::FingerLifted { id, .. } | touch::Event::FingerLost { id, .. },
+            ) => {
+                let count = state.fingers.len();
+                state.fingers.retain(|(finger, _)| *finger != id);
+                if state.fingers.len() == count {
+                    event::Status::Ignored
+                } else {
+                    event::Status::Captured
+                }
+            }
+            _ => event::Status::Ignored,
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        _viewport: &Rectangle,
+        _renderer: &Renderer,
+    ) -> mouse::Interaction {
+        let state = tree.state.downcast_ref::<State>();
+        let bounds = layout.bounds();
+        if state.drag.is_some() {
+            mouse::Interaction::Grabbing
+        } else if cursor.is_over(bounds) && self.pannable(self.zoom, bounds.size()) {
+            mouse::Interaction::Grab
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        renderer_style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        let Some(clip) = layout.bounds().intersection(viewport) else {
+            return;
+        };
+        let Some(content_layout) = layout.children().next() else {
+            return;
+        };
+        // The zoomed image extends past the view
+        renderer.start_layer(clip);
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            renderer_style,
+            content_layout,
+            cursor,
+            &clip,
+        );
+        renderer.end_layer();
+    }
+}
+
+impl<'a, Message: 'static> From<ImageViewer<'a, Message>> for Element<'a, Message> {
+    fn from(viewer: ImageViewer<'a, Message>) -> Self {
+        Element::new(viewer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE: Size = Size::new(4000.0, 2000.0);
+    const VIEW: Size = Size::new(1000.0, 1000.0);
+
+    #[test]
+    fn fit_placement() {
+        assert_eq!(ImageZoom::Fit.scale(IMAGE, VIEW), 0.25);
+        assert_eq!(
+            placement(ImageZoom::Fit, IMAGE, VIEW),
+            Rectangle::new(Point::new(0.0, 250.0), Size::new(1000.0, 500.0))
+        );
+        // Small images are not enlarged
+        let small = Size::new(100.0, 50.0);
+        assert_eq!(ImageZoom::Fit.scale(small, VIEW), 1.0);
+    }
+
+    #[test]
+    fn zoom_and_pan() {
+        // Zooming at the left edge keeps it in place
+        let zoom = ImageZoom::Fit.zoom_at(2.0, Point::new(0.0, 500.0), IMAGE, VIEW);
+        let placed = placement(zoom, IMAGE, VIEW);
+        assert_eq!(zoom.scale(IMAGE, VIEW), 0.5);
+        assert_eq!(placed.position(), Point::new(0.0, 0.0));
+
+        // Panning stops at the edges of the image
+        let zoom = zoom.pan(Vector::new(500.0, 0.0), IMAGE, VIEW);
+        assert_eq!(placement(zoom, IMAGE, VIEW).x, 0.0);
+        let zoom = zoom.pan(Vector::new(-5000.0, 0.0), IMAGE, VIEW);
+        assert_eq!(placement(zoom, IMAGE, VIEW).x, -1000.0);
+
+        // Zooming out past the fitting size goes back to fit
+        assert_eq!(
+            zoom.zoom_at(0.1, Point::ORIGIN, IMAGE, VIEW),
+            ImageZoom::Fit
+        );
+        let zoom = ImageZoom::ORIGINAL.zoom_at(100.0, Point::ORIGIN, IMAGE, VIEW);
+        assert_eq!(zoom.scale(IMAGE, VIEW), MAX_SCALE);
+    }
+}
@@ -60,6 +60,8 @@ pub fn key_binds(mode: &tab::Mode) -> HashMap<KeyBind, Action> {
         bind!([Ctrl], Key::Character("t".into()), TabNew);
         bind!([Ctrl], Key::Named(Named::Tab), TabNext);
         bind!([Ctrl, Shift], Key::Named(Named::Tab), TabPrev);
+        bind!([Ctrl, Shift], Key::Named(Named::PageUp), TabMoveLeft);
+        bind!([Ctrl, Shift], Key::Named(Named::PageDown), TabMoveRight);
         bind!([Ctrl], Key::Character("q".into()), WindowClose);
         bind!([Ctrl], Key::Character("n".into()), WindowNew);
     }
@@ -73,6 +75,7 @@ pub fn key_binds(mode: &tab::Mode) -> HashMap<KeyBind, Action> {
         bind!([Shift], Key::Named(Named::Enter), OpenInNewWindow);
         bind!([Ctrl], Key::Character("v".into()), Paste);
         bind!([], Key::Named(Named::F2), Rename);
+        bind!([Alt], Key::Named(Named::Enter), Properties);
     }
 
     // App and dialog only keys
@@ -280,14 +280,18 @@ pub async fn decode_large_image(
                         limits.max_alloc = Some(GALLERY_MEMORY_LIMIT_MB * DECIMAL_MB_TO_BYTES);
                         reader.limits(limits);
 
-                        match reader.decode() {
+                        match crate::photo::decode_oriented(reader) {
                             Ok(img) => {
                                 let rgba = img.into_rgba8();
                                 let orig_width = rgba.width();
                                 let orig_height = rgba.height();
 
                                 // Resize if target dimensions provided
-                                let (final_img, width, height) = if let Some((target_w, target_h)) = target_dimensions {
+                                let (final_img, width, height) = if let Some((mut target_w, mut target_h)) = target_dimensions {
+                                    // Target dimensions are calculated before EXIF orientation is applied
+                                    if (orig_width > orig_height) != (target_w > target_h) {
+                                        std::mem::swap(&mut target_w, &mut target_h);
+                                    }
                                     log::info!(
                                         "Resizing {}x{} -> {}x{} for memory optimization: {}",
                                         orig_width, orig_height, target_w, target_h,
@@ -363,6 +367,9 @@ pub struct LargeImageManager {
     /// When a new decode is started for the same path, the generation is incremented.
     /// Only decodes matching the current generation are accepted when they complete.
     decode_generations: HashMap<PathBuf, u64>,
+    /// Images changed on disk since they were shown, like rotated photos. These are always
+    /// decoded, as the renderer keeps showing what it loaded from a path before.
+    modified_images: HashSet<PathBuf>,
 }
 
 impl LargeImageManager {
@@ -427,6 +434,23 @@ impl LargeImageManager {
         self.decode_errors.remove(path);
     }
 
+    pub fn is_modified(&self, path: &Path) -> bool {
+        self.modified_images.contains(path)
+    }
+
+    /// Forget the decoded image of a file that was changed on disk
+    pub fn invalidate(&mut self, path: &Path) {
+        self.decoded_images.remove(path);
+        self.decoded_display_sizes.remove(path);
+        self.decode_errors.remove(path);
+        self.decoding_images.remove(path);
+        // Discard decodes of the old contents that are still running
+        if let Some(generation) = self.decode_generations.get_mut(path) {
+            *generation += 1;
+        }
+        self.modified_images.insert(path.to_path_buf());
+    }
+
     pub fn clear_cache(&mut self) {
         log::info!(
             "Clearing {} cached images from large image manager",
@@ -2,32 +2,53 @@
 // SPDX-License-Identifier: GPL-3.0-only
 
 use cosmic::{app::Settings, iced::Limits};
-use std::{env, fs, path::PathBuf, process};
+use std::{env, path::PathBuf, process};
 
 use app::{App, Flags};
+use cli::Args;
 pub mod app;
 mod archive;
+mod checksum;
+pub mod cli;
 pub mod clipboard;
+mod compare;
+mod desktop_layout;
+mod desktop_stack;
 use config::Config;
 pub mod config;
 pub mod dialog;
+mod folder_preview;
+mod free_layout;
+mod image_viewer;
 mod key_bind;
 pub(crate) mod large_image;
 mod localize;
+mod media;
 mod menu;
 mod mime_app;
 pub mod mime_icon;
 mod mounter;
 mod mouse_area;
 pub mod operation;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod photo;
+#[cfg(feature = "playback")]
+mod playback;
+#[cfg(unix)]
+mod properties;
+mod recents;
+mod remote;
+mod shred;
 mod spawn_detached;
-use tab::Location;
 mod zoom;
 
 use crate::config::State;
 pub mod tab;
+mod text_preview;
 mod thumbnail_cacher;
 mod thumbnailer;
+mod trash_folder;
 
 pub(crate) type FxOrderMap<K, V> = ordermap::OrderMap<K, V, rustc_hash::FxBuildHasher>;
 
@@ -95,8 +116,7 @@ pub fn desktop() -> Result<(), Box<dyn std::error::Error>> {
         state_handler,
         state,
         mode: app::Mode::Desktop,
-        locations,
-        uris: Vec::new()
+        args: Args { locations, ..Args::default() }
     };
     cosmic::app::run::<App>(settings, flags)?;
 
@@ -113,44 +133,16 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (config_handler, config) = Config::load();
     let (state_handler, state) = State::load();
 
-    let mut daemonize = true;
-    let mut locations = Vec::new();
-    let mut uris = Vec::new();
-    for arg in env::args().skip(1) {
-        let location = if &arg == "--no-daemon" {
-            daemonize = false;
-            continue;
-        } else if &arg == "--trash" {
-            Location::Trash
-        } else if &arg == "--recents" {
-            Location::Recents
-        } else if &arg == "--network" {
-            Location::Network("network:///".to_string(), fl!("networks"), None)
-        } else {
-            //TODO: support more URLs
-            let path = match url::Url::parse(&arg) {
-                Ok(url) if url.scheme() == "file" => if let Ok(path) = url.to_file_path() { path } else {
-                    log::warn!("invalid argument {arg:?}");
-                    continue;
-                },
-                Ok(url) => {
-                    uris.push(url);
-                    continue;
-                }
-                _ => PathBuf::from(arg),
-            };
-            match fs::canonicalize(&path) {
-                Ok(absolute) => Location::Path(absolute),
-                Err(err) => {
-                    log::warn!("failed to canonicalize {}: {}", path.display(), err);
-                    continue;
-                }
-            }
-        };
-        locations.push(location);
-    }
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("cosmic-files: {err}");
+            process::exit(1);
+        }
+    };
 
-    if daemonize {
+    // Operations stay attached to the terminal to report their exit status
+    if !args.no_daemon && args.operation_opt.is_none() {
         #[cfg(all(unix, not(target_os = "redox")))]
         match fork::daemon(true, true) {
             Ok(fork::Fork::Child) => (),
@@ -178,8 +170,7 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
         state_handler,
         state,
         mode: app::Mode::App,
-        locations,
-        uris
+        args
     };
     cosmic::app::run::<App>(settings, flags)?;
 
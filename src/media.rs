@@ -0,0 +1,786 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use image::DynamicImage;
+use lofty::{
+    file::FileType,
+    picture::PictureType,
+    prelude::{Accessor, AudioFile, TaggedFileExt},
+};
+use mime_guess::{Mime, mime};
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+/// Largest `moov` box that will be read from an MP4 file
+const MAX_MOOV_SIZE: u64 = 16 * 1024 * 1024;
+/// Largest header element or chunk that will be read from a Matroska or AVI file
+const MAX_HEADER_SIZE: u64 = 16 * 1024 * 1024;
+
+// Matroska element IDs
+const EBML_HEADER: u32 = 0x1A45_DFA3;
+const MKV_SEGMENT: u32 = 0x1853_8067;
+const MKV_CLUSTER: u32 = 0x1F43_B675;
+const MKV_INFO: u32 = 0x1549_A966;
+const MKV_TIMECODE_SCALE: u32 = 0x2A_D7B1;
+const MKV_DURATION: u32 = 0x4489;
+const MKV_TRACKS: u32 = 0x1654_AE6B;
+const MKV_TRACK_ENTRY: u32 = 0xAE;
+const MKV_TRACK_TYPE: u32 = 0x83;
+const MKV_CODEC_ID: u32 = 0x86;
+const MKV_VIDEO: u32 = 0xE0;
+const MKV_PIXEL_WIDTH: u32 = 0xB0;
+const MKV_PIXEL_HEIGHT: u32 = 0xBA;
+
+/// Technical information and tags of an audio or video file. Fields that could not be
+/// determined are left empty.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MediaInfo {
+    pub duration: Option<Duration>,
+    pub codec: Option<String>,
+    /// Bitrate in kilobits per second
+    pub bitrate: Option<u32>,
+    pub resolution: Option<(u32, u32)>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub title: Option<String>,
+}
+
+impl MediaInfo {
+    /// Read media information using built-in parsers. Audio files are read with their tags, video
+    /// files are supported for MP4, QuickTime, Matroska, WebM and AVI containers.
+    pub fn read(path: &Path, mime: &Mime) -> Self {
+        let result = if mime.type_() == mime::AUDIO {
+            read_audio(path)
+        } else if is_mp4(mime) {
+            read_mp4(path).map_err(|err| err.to_string())
+        } else if is_matroska(mime) {
+            read_matroska(path).map_err(|err| err.to_string())
+        } else if is_avi(mime) {
+            read_avi(path).map_err(|err| err.to_string())
+        } else {
+            return Self::default();
+        };
+        result.unwrap_or_else(|err| {
+            log::debug!("failed to read media info of {}: {}", path.display(), err);
+            Self::default()
+        })
+    }
+}
+
+/// Returns true for files that [`MediaInfo::read`] may return information for
+pub fn is_media(mime: &Mime) -> bool {
+    mime.type_() == mime::AUDIO || is_mp4(mime) || is_matroska(mime) || is_avi(mime)
+}
+
+/// Returns true for audio and video files, which may be played in the preview
+pub fn is_playable(mime: &Mime) -> bool {
+    mime.type_() == mime::AUDIO || mime.type_() == mime::VIDEO
+}
+
+fn is_mp4(mime: &Mime) -> bool {
+    mime.type_() == mime::VIDEO
+        && matches!(
+            mime.subtype().as_str(),
+            "mp4" | "quicktime" | "x-m4v" | "3gpp" | "3gpp2"
+        )
+}
+
+fn is_matroska(mime: &Mime) -> bool {
+    mime.type_() == mime::VIDEO && matches!(mime.subtype().as_str(), "x-matroska" | "webm")
+}
+
+fn is_avi(mime: &Mime) -> bool {
+    mime.type_() == mime::VIDEO
+        && matches!(
+            mime.subtype().as_str(),
+            "x-msvideo" | "avi" | "vnd.avi" | "msvideo"
+        )
+}
+
+/// Format a duration as `m:ss`, or `h:mm:ss` if it is an hour or longer
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, (secs / 60) % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+/// Decode the cover art embedded in the tags of an audio file, preferring the front cover
+pub fn cover_art(path: &Path) -> Result<Option<DynamicImage>, String> {
+    let tagged_file = lofty::read_from_path(path)
+        .map_err(|err| format!("failed to read tags of {}: {}", path.display(), err))?;
+    let pictures = tagged_file.tags().iter().flat_map(|tag| tag.pictures());
+    let Some(picture) = pictures
+        .clone()
+        .find(|picture| picture.pic_type() == PictureType::CoverFront)
+        .or_else(|| pictures.clone().next())
+    else {
+        return Ok(None);
+    };
+    image::load_from_memory(picture.data())
+        .map(Some)
+        .map_err(|err| format!("failed to decode cover art of {}: {}", path.display(), err))
+}
+
+fn read_audio(path: &Path) -> Result<MediaInfo, String> {
+    let tagged_file = lofty::read_from_path(path).map_err(|err| err.to_string())?;
+    let properties = tagged_file.properties();
+    let mut info = MediaInfo {
+        duration: Some(properties.duration()).filter(|duration| !duration.is_zero()),
+        codec: Some(codec_name(tagged_file.file_type())),
+        bitrate: properties
+            .audio_bitrate()
+            .or_else(|| properties.overall_bitrate())
+            .filter(|&bitrate| bitrate > 0),
+        ..Default::default()
+    };
+    if let Some(tag) = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag())
+    {
+        info.artist = tag_text(tag.artist());
+        info.album = tag_text(tag.album());
+        info.title = tag_text(tag.title());
+    }
+    Ok(info)
+}
+
+fn tag_text(value: Option<Cow<'_, str>>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn codec_name(file_type: FileType) -> String {
+    match file_type {
+        FileType::Aac => "AAC".to_string(),
+        FileType::Aiff => "AIFF".to_string(),
+        FileType::Ape => "Monkey's Audio".to_string(),
+        FileType::Flac => "FLAC".to_string(),
+        FileType::Mpeg => "MP3".to_string(),
+        FileType::Mp4 => "MPEG-4 Audio".to_string(),
+        FileType::Mpc => "Musepack".to_string(),
+        FileType::Opus => "Opus".to_string(),
+        FileType::Speex => "Speex".to_string(),
+        FileType::Vorbis => "Vorbis".to_string(),
+        FileType::Wav => "WAV".to_string(),
+        FileType::WavPack => "WavPack".to_string(),
+        other => format!("{other:?}"),
+    }
+}
+
+fn read_mp4(path: &Path) -> io::Result<MediaInfo> {
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+    let mut pos = 0;
+    while pos + 8 <= file_len {
+        file.seek(SeekFrom::Start(pos))?;
+        let mut header = [0; 16];
+        file.read_exact(&mut header[..8])?;
+        let mut size = u64::from(u32::from_be_bytes(header[..4].try_into().unwrap()));
+        let mut header_len = 8;
+        if size == 1 {
+            file.read_exact(&mut header[8..])?;
+            size = u64::from_be_bytes(header[8..].try_into().unwrap());
+            header_len = 16;
+        } else if size == 0 {
+            size = file_len - pos;
+        }
+        if size < header_len {
+            break;
+        }
+        if &header[4..8] == b"moov" {
+            let moov_len = size - header_len;
+            if moov_len > MAX_MOOV_SIZE {
+                return Err(io::Error::other("moov box is too large"));
+            }
+            let mut moov = vec![0; moov_len as usize];
+            file.read_exact(&mut moov)?;
+            let mut info = parse_moov(&moov);
+            set_overall_bitrate(&mut info, file_len);
+            return Ok(info);
+        }
+        pos += size;
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "no moov box found",
+    ))
+}
+
+/// Estimate the bitrate of a video file from its size and duration
+fn set_overall_bitrate(info: &mut MediaInfo, file_len: u64) {
+    if let Some(duration) = info.duration.filter(|duration| !duration.is_zero()) {
+        info.bitrate =
+            u32::try_from((file_len as f64 * 8.0 / duration.as_secs_f64() / 1000.0).round() as u64)
+                .ok();
+    }
+}
+
+fn join_codecs(video: Option<String>, audio: Option<String>) -> Option<String> {
+    match (video, audio) {
+        (Some(video), Some(audio)) => Some(format!("{video}, {audio}")),
+        (video, audio) => video.or(audio),
+    }
+}
+
+/// Iterate over the boxes in `data`, yielding their type and contents
+fn mp4_boxes(mut data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        let size = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+        let kind: [u8; 4] = data.get(4..8)?.try_into().ok()?;
+        let (header_len, size) = match size {
+            0 => (8, data.len()),
+            1 => (
+                16,
+                usize::try_from(u64::from_be_bytes(data.get(8..16)?.try_into().ok()?)).ok()?,
+            ),
+            _ => (8, size),
+        };
+        let contents = data.get(header_len..size)?;
+        data = &data[size..];
+        Some((kind, contents))
+    })
+}
+
+fn mp4_child<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    mp4_boxes(data).find_map(|(k, contents)| (&k == kind).then_some(contents))
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn be_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(offset..offset + 8)?.try_into().ok()?,
+    ))
+}
+
+fn parse_moov(moov: &[u8]) -> MediaInfo {
+    let mut info = MediaInfo::default();
+
+    if let Some(mvhd) = mp4_child(moov, b"mvhd") {
+        let (timescale, duration) = if mvhd.first() == Some(&1) {
+            (be_u32(mvhd, 20), be_u64(mvhd, 24))
+        } else {
+            (be_u32(mvhd, 12), be_u32(mvhd, 16).map(u64::from))
+        };
+        if let (Some(timescale), Some(duration)) = (timescale, duration) {
+            if timescale > 0 && duration != u64::MAX && duration != u64::from(u32::MAX) {
+                info.duration = Some(Duration::from_secs_f64(
+                    duration as f64 / f64::from(timescale),
+                ));
+            }
+        }
+    }
+
+    let mut audio_codec = None;
+    for (kind, trak) in mp4_boxes(moov) {
+        if &kind != b"trak" {
+            continue;
+        }
+        let Some(mdia) = mp4_child(trak, b"mdia") else {
+            continue;
+        };
+        let handler = mp4_child(mdia, b"hdlr").and_then(|hdlr| hdlr.get(8..12));
+        let format = mp4_child(mdia, b"minf")
+            .and_then(|minf| mp4_child(minf, b"stbl"))
+            .and_then(|stbl| mp4_child(stbl, b"stsd"))
+            .and_then(|stsd| stsd.get(12..16));
+        match handler {
+            Some(b"vide") if info.codec.is_none() => {
+                info.codec = format.map(fourcc_name);
+                if let Some(tkhd) = mp4_child(trak, b"tkhd") {
+                    let offset = if tkhd.first() == Some(&1) { 88 } else { 76 };
+                    // Width and height are 16.16 fixed point
+                    if let (Some(width), Some(height)) =
+                        (be_u32(tkhd, offset), be_u32(tkhd, offset + 4))
+                    {
+                        if width >> 16 > 0 && height >> 16 > 0 {
+                            info.resolution = Some((width >> 16, height >> 16));
+                        }
+                    }
+                }
+            }
+            Some(b"soun") if audio_codec.is_none() => {
+                audio_codec = format.map(fourcc_name);
+            }
+            _ => {}
+        }
+    }
+    info.codec = join_codecs(info.codec, audio_codec);
+
+    info
+}
+
+fn fourcc_name(fourcc: &[u8]) -> String {
+    match fourcc {
+        b"avc1" | b"avc3" => "H.264".to_string(),
+        b"hvc1" | b"hev1" => "H.265".to_string(),
+        b"av01" => "AV1".to_string(),
+        b"vp08" => "VP8".to_string(),
+        b"vp09" => "VP9".to_string(),
+        b"mp4v" => "MPEG-4 Visual".to_string(),
+        b"s263" => "H.263".to_string(),
+        b"mp4a" => "AAC".to_string(),
+        b"alac" => "ALAC".to_string(),
+        b"fLaC" => "FLAC".to_string(),
+        b"Opus" => "Opus".to_string(),
+        b"ac-3" => "AC-3".to_string(),
+        b"ec-3" => "E-AC-3".to_string(),
+        _ => String::from_utf8_lossy(fourcc).trim().to_string(),
+    }
+}
+
+fn read_matroska(path: &Path) -> io::Result<MediaInfo> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not a Matroska file");
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    // The segment follows the EBML header
+    let (id, header_len, size) = ebml_element_header(&mut file, 0)?.ok_or_else(invalid)?;
+    if id != EBML_HEADER {
+        return Err(invalid());
+    }
+    let mut pos = header_len + size.ok_or_else(invalid)?;
+    let (id, header_len, _) = ebml_element_header(&mut file, pos)?.ok_or_else(invalid)?;
+    if id != MKV_SEGMENT {
+        return Err(invalid());
+    }
+    pos += header_len;
+
+    // Segment information and tracks are written before the first cluster
+    let mut info = MediaInfo::default();
+    while let Some((id, header_len, size)) = ebml_element_header(&mut file, pos)? {
+        let Some(size) = size.filter(|_| id != MKV_CLUSTER) else {
+            break;
+        };
+        if id == MKV_INFO || id == MKV_TRACKS {
+            if size > MAX_HEADER_SIZE {
+                return Err(io::Error::other("Matroska header element is too large"));
+            }
+            let mut data = vec![0; size as usize];
+            file.seek(SeekFrom::Start(pos + header_len))?;
+            file.read_exact(&mut data)?;
+            if id == MKV_INFO {
+                parse_matroska_info(&data, &mut info);
+            } else {
+                parse_matroska_tracks(&data, &mut info);
+            }
+        }
+        pos += header_len + size;
+    }
+    set_overall_bitrate(&mut info, file_len);
+    Ok(info)
+}
+
+/// Read the ID, header length and size of the element at `pos`. The size is `None` if it is
+/// unknown.
+fn ebml_element_header(file: &mut File, pos: u64) -> io::Result<Option<(u32, u64, Option<u64>)>> {
+    file.seek(SeekFrom::Start(pos))?;
+    let mut header = Vec::with_capacity(12);
+    file.take(12).read_to_end(&mut header)?;
+    Ok(ebml_header(&header).map(|(id, header_len, size)| (id, header_len as u64, size)))
+}
+
+/// Parse an element ID and size, returning them with the length of the header
+fn ebml_header(data: &[u8]) -> Option<(u32, usize, Option<u64>)> {
+    let id_len = data.first()?.leading_zeros() as usize + 1;
+    if id_len > 4 {
+        return None;
+    }
+    let id = data
+        .get(..id_len)?
+        .iter()
+        .fold(0, |id, &byte| (id << 8) | u32::from(byte));
+    let size_len = data.get(id_len)?.leading_zeros() as usize + 1;
+    if size_len > 8 {
+        return None;
+    }
+    let size_bytes = data.get(id_len..id_len + size_len)?;
+    let size = size_bytes[1..].iter().fold(
+        u64::from(size_bytes[0]) & (0xFF >> size_len),
+        |size, &byte| (size << 8) | u64::from(byte),
+    );
+    // A size with all bits set is unknown
+    let unknown = size == (1 << (7 * size_len)) - 1;
+    Some((id, id_len + size_len, (!unknown).then_some(size)))
+}
+
+/// Iterate over the elements in `data`, yielding their ID and contents
+fn ebml_elements(mut data: &[u8]) -> impl Iterator<Item = (u32, &[u8])> {
+    std::iter::from_fn(move || {
+        let (id, header_len, size) = ebml_header(data)?;
+        let end = match size {
+            Some(size) => header_len.checked_add(usize::try_from(size).ok()?)?,
+            None => data.len(),
+        };
+        let contents = data.get(header_len..end)?;
+        data = &data[end..];
+        Some((id, contents))
+    })
+}
+
+fn ebml_child(data: &[u8], id: u32) -> Option<&[u8]> {
+    ebml_elements(data).find_map(|(i, contents)| (i == id).then_some(contents))
+}
+
+fn ebml_uint(data: &[u8]) -> Option<u64> {
+    (data.len() <= 8).then(|| {
+        data.iter()
+            .fold(0, |value, &byte| (value << 8) | u64::from(byte))
+    })
+}
+
+fn ebml_float(data: &[u8]) -> Option<f64> {
+    match data.len() {
+        4 => Some(f64::from(f32::from_be_bytes(data.try_into().ok()?))),
+        8 => Some(f64::from_be_bytes(data.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+fn parse_matroska_info(data: &[u8], info: &mut MediaInfo) {
+    // Durations are in units of the timecode scale, which is in nanoseconds
+    let scale = ebml_child(data, MKV_TIMECODE_SCALE)
+        .and_then(ebml_uint)
+        .unwrap_or(1_000_000);
+    if let Some(duration) = ebml_child(data, MKV_DURATION).and_then(ebml_float) {
+        info.duration = Duration::try_from_secs_f64(duration * scale as f64 / 1e9).ok();
+    }
+}
+
+fn parse_matroska_tracks(data: &[u8], info: &mut MediaInfo) {
+    let mut video_codec = None;
+    let mut audio_codec = None;
+    for (id, entry) in ebml_elements(data) {
+        if id != MKV_TRACK_ENTRY {
+            continue;
+        }
+        let codec = ebml_child(entry, MKV_CODEC_ID)
+            .map(|codec_id| matroska_codec_name(&String::from_utf8_lossy(codec_id)));
+        match ebml_child(entry, MKV_TRACK_TYPE).and_then(ebml_uint) {
+            Some(1) if video_codec.is_none() => {
+                video_codec = codec;
+                if let Some(video) = ebml_child(entry, MKV_VIDEO) {
+                    let width = ebml_child(video, MKV_PIXEL_WIDTH).and_then(ebml_uint);
+                    let height = ebml_child(video, MKV_PIXEL_HEIGHT).and_then(ebml_uint);
+                    if let (Some(width), Some(height)) = (width, height) {
+                        if let (Ok(width @ 1..), Ok(height @ 1..)) =
+                            (u32::try_from(width), u32::try_from(height))
+                        {
+                            info.resolution = Some((width, height));
+                        }
+                    }
+                }
+            }
+            Some(2) if audio_codec.is_none() => {
+                audio_codec = codec;
+            }
+            _ => {}
+        }
+    }
+    info.codec = join_codecs(video_codec, audio_codec);
+}
+
+fn matroska_codec_name(codec_id: &str) -> String {
+    let codec_id = codec_id.trim_end_matches('\0');
+    match codec_id {
+        "V_MPEG4/ISO/AVC" => "H.264".to_string(),
+        "V_MPEGH/ISO/HEVC" => "H.265".to_string(),
+        "V_AV1" => "AV1".to_string(),
+        "V_VP8" => "VP8".to_string(),
+        "V_VP9" => "VP9".to_string(),
+        "V_MPEG2" => "MPEG-2".to_string(),
+        "V_THEORA" => "Theora".to_string(),
+        "A_OPUS" => "Opus".to_string(),
+        "A_VORBIS" => "Vorbis".to_string(),
+        "A_FLAC" => "FLAC".to_string(),
+        "A_AC3" => "AC-3".to_string(),
+        "A_EAC3" => "E-AC-3".to_string(),
+        "A_MPEG/L3" => "MP3".to_string(),
+        "A_DTS" => "DTS".to_string(),
+        _ if codec_id.starts_with("V_MPEG4/ISO/") => "MPEG-4 Visual".to_string(),
+        _ if codec_id.starts_with("A_AAC") => "AAC".to_string(),
+        _ if codec_id.starts_with("A_PCM/") => "PCM".to_string(),
+        _ => codec_id.to_string(),
+    }
+}
+
+fn read_avi(path: &Path) -> io::Result<MediaInfo> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not an AVI file");
+    let mut file = File::open(path)?;
+    let file_len = file.metadata()?.len();
+
+    // The header list is the first chunk of the RIFF file
+    let mut header = [0; 24];
+    file.read_exact(&mut header)?;
+    if &header[..4] != b"RIFF" || &header[8..12] != b"AVI " || &header[12..16] != b"LIST" {
+        return Err(invalid());
+    }
+    let list_len = u64::from(u32::from_le_bytes(header[16..20].try_into().unwrap()));
+    if &header[20..24] != b"hdrl" || list_len < 4 {
+        return Err(invalid());
+    }
+    if list_len > MAX_HEADER_SIZE {
+        return Err(io::Error::other("AVI header list is too large"));
+    }
+    let mut hdrl = vec![0; list_len as usize - 4];
+    file.read_exact(&mut hdrl)?;
+    let mut info = parse_avi_header(&hdrl);
+    set_overall_bitrate(&mut info, file_len);
+    Ok(info)
+}
+
+/// Iterate over the chunks in `data`, yielding their ID and contents
+fn riff_chunks(mut data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        let id: [u8; 4] = data.get(..4)?.try_into().ok()?;
+        let size = usize::try_from(le_u32(data, 4)?).ok()?;
+        let contents = data.get(8..8 + size)?;
+        // Chunks are padded to an even size
+        data = data.get(8 + size + (size & 1)..).unwrap_or_default();
+        Some((id, contents))
+    })
+}
+
+fn riff_child<'a>(data: &'a [u8], id: &[u8; 4]) -> Option<&'a [u8]> {
+    riff_chunks(data).find_map(|(i, contents)| (&i == id).then_some(contents))
+}
+
+fn le_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn le_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn parse_avi_header(hdrl: &[u8]) -> MediaInfo {
+    let mut info = MediaInfo::default();
+
+    if let Some(avih) = riff_child(hdrl, b"avih") {
+        if let (Some(frame_micros), Some(frames)) = (le_u32(avih, 0), le_u32(avih, 16)) {
+            let duration = Duration::from_micros(u64::from(frame_micros) * u64::from(frames));
+            info.duration = Some(duration).filter(|duration| !duration.is_zero());
+        }
+        if let (Some(width @ 1..), Some(height @ 1..)) = (le_u32(avih, 32), le_u32(avih, 36)) {
+            info.resolution = Some((width, height));
+        }
+    }
+
+    let mut video_codec = None;
+    let mut audio_codec = None;
+    for (id, list) in riff_chunks(hdrl) {
+        if &id != b"LIST" || !list.starts_with(b"strl") {
+            continue;
+        }
+        let stream_type = riff_child(&list[4..], b"strh").and_then(|strh| strh.get(..4));
+        let strf = riff_child(&list[4..], b"strf");
+        match stream_type {
+            Some(b"vids") if video_codec.is_none() => {
+                // The format is a bitmap info header with the compression fourcc at offset 16
+                video_codec = strf.and_then(|strf| strf.get(16..20)).map(avi_fourcc_name);
+            }
+            Some(b"auds") if audio_codec.is_none() => {
+                // The format is a wave format header starting with the format tag
+                audio_codec = strf.and_then(|strf| le_u16(strf, 0)).map(wave_format_name);
+            }
+            _ => {}
+        }
+    }
+    info.codec = join_codecs(video_codec, audio_codec);
+
+    info
+}
+
+fn avi_fourcc_name(fourcc: &[u8]) -> String {
+    match fourcc.to_ascii_uppercase().as_slice() {
+        b"H264" | b"X264" | b"AVC1" => "H.264".to_string(),
+        b"HEVC" | b"H265" | b"HVC1" => "H.265".to_string(),
+        b"XVID" | b"DIVX" | b"DX50" | b"FMP4" | b"MP4V" => "MPEG-4 Visual".to_string(),
+        b"MJPG" => "Motion JPEG".to_string(),
+        _ => fourcc_name(fourcc),
+    }
+}
+
+fn wave_format_name(format_tag: u16) -> String {
+    match format_tag {
+        0x0001 | 0x0003 => "PCM".to_string(),
+        0x0050 => "MPEG Audio".to_string(),
+        0x0055 => "MP3".to_string(),
+        0x00FF | 0x1610 => "AAC".to_string(),
+        0x0161 => "WMA".to_string(),
+        0x2000 => "AC-3".to_string(),
+        0x2001 => "DTS".to_string(),
+        _ => format!("0x{format_tag:04X}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mp4_box(kind: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(contents.len() + 8);
+        data.extend_from_slice(&(contents.len() as u32 + 8).to_be_bytes());
+        data.extend_from_slice(kind);
+        data.extend_from_slice(contents);
+        data
+    }
+
+    fn track(handler: &[u8; 4], format: &[u8; 4], size: Option<(u32, u32)>) -> Vec<u8> {
+        let mut tkhd = vec![0; 84];
+        if let Some((width, height)) = size {
+            tkhd[76..80].copy_from_slice(&(width << 16).to_be_bytes());
+            tkhd[80..84].copy_from_slice(&(height << 16).to_be_bytes());
+        }
+        let mut hdlr = vec![0; 24];
+        hdlr[8..12].copy_from_slice(handler);
+        let mut stsd = vec![0; 8];
+        stsd[7] = 1;
+        stsd.extend(mp4_box(format, &[0; 8]));
+        let stbl = mp4_box(b"stbl", &mp4_box(b"stsd", &stsd));
+        let minf = mp4_box(b"minf", &stbl);
+        let mdia = mp4_box(b"mdia", &[mp4_box(b"hdlr", &hdlr), minf].concat());
+        mp4_box(b"trak", &[mp4_box(b"tkhd", &tkhd), mdia].concat())
+    }
+
+    #[test]
+    fn parse_mp4_video() {
+        let mut mvhd = vec![0; 100];
+        mvhd[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd[16..20].copy_from_slice(&90_500u32.to_be_bytes());
+        let moov = [
+            mp4_box(b"mvhd", &mvhd),
+            track(b"soun", b"mp4a", None),
+            track(b"vide", b"avc1", Some((1920, 1080))),
+        ]
+        .concat();
+
+        let info = parse_moov(&moov);
+        assert_eq!(info.duration, Some(Duration::from_millis(90_500)));
+        assert_eq!(info.resolution, Some((1920, 1080)));
+        assert_eq!(info.codec.as_deref(), Some("H.264, AAC"));
+    }
+
+    #[test]
+    fn parse_truncated_mp4() {
+        let moov = track(b"vide", b"hvc1", Some((640, 480)));
+        let info = parse_moov(&moov[..moov.len() - 10]);
+        assert_eq!(info, MediaInfo::default());
+    }
+
+    fn ebml_element(id: u32, contents: &[u8]) -> Vec<u8> {
+        let id_bytes = id.to_be_bytes();
+        let mut data = id_bytes[id.leading_zeros() as usize / 8..].to_vec();
+        // Sizes are written with the eight byte encoding
+        data.push(0x01);
+        data.extend_from_slice(&(contents.len() as u64).to_be_bytes()[1..]);
+        data.extend_from_slice(contents);
+        data
+    }
+
+    fn riff_chunk(id: &[u8; 4], contents: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(contents.len() + 9);
+        data.extend_from_slice(id);
+        data.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+        data.extend_from_slice(contents);
+        if contents.len() % 2 == 1 {
+            data.push(0);
+        }
+        data
+    }
+
+    #[test]
+    fn parse_matroska_video() {
+        let segment_info = [
+            ebml_element(MKV_TIMECODE_SCALE, &[0x0F, 0x42, 0x40]),
+            ebml_element(MKV_DURATION, &90_500f64.to_be_bytes()),
+        ]
+        .concat();
+        let video = [
+            ebml_element(MKV_PIXEL_WIDTH, &[0x07, 0x80]),
+            ebml_element(MKV_PIXEL_HEIGHT, &[0x04, 0x38]),
+        ]
+        .concat();
+        let tracks = [
+            ebml_element(
+                MKV_TRACK_ENTRY,
+                &[
+                    ebml_element(MKV_TRACK_TYPE, &[2]),
+                    ebml_element(MKV_CODEC_ID, b"A_OPUS"),
+                ]
+                .concat(),
+            ),
+            ebml_element(
+                MKV_TRACK_ENTRY,
+                &[
+                    ebml_element(MKV_TRACK_TYPE, &[1]),
+                    ebml_element(MKV_CODEC_ID, b"V_VP9"),
+                    ebml_element(MKV_VIDEO, &video),
+                ]
+                .concat(),
+            ),
+        ]
+        .concat();
+
+        let mut info = MediaInfo::default();
+        parse_matroska_info(&segment_info, &mut info);
+        parse_matroska_tracks(&tracks, &mut info);
+        assert_eq!(info.duration, Some(Duration::from_millis(90_500)));
+        assert_eq!(info.resolution, Some((1920, 1080)));
+        assert_eq!(info.codec.as_deref(), Some("VP9, Opus"));
+    }
+
+    #[test]
+    fn parse_avi_video() {
+        let mut avih = vec![0; 56];
+        avih[0..4].copy_from_slice(&40_000u32.to_le_bytes());
+        avih[16..20].copy_from_slice(&250u32.to_le_bytes());
+        avih[32..36].copy_from_slice(&640u32.to_le_bytes());
+        avih[36..40].copy_from_slice(&480u32.to_le_bytes());
+        let stream_list = |stream_type: &[u8; 4], format: &[u8]| {
+            let mut strh = vec![0; 56];
+            strh[..4].copy_from_slice(stream_type);
+            let strl = [riff_chunk(b"strh", &strh), riff_chunk(b"strf", format)].concat();
+            riff_chunk(b"LIST", &[b"strl".as_slice(), strl.as_slice()].concat())
+        };
+        let mut bitmap_info = vec![0; 40];
+        bitmap_info[16..20].copy_from_slice(b"XVID");
+        let mut wave_format = vec![0; 18];
+        wave_format[..2].copy_from_slice(&0x0055u16.to_le_bytes());
+        let hdrl = [
+            riff_chunk(b"avih", &avih),
+            stream_list(b"vids", &bitmap_info),
+            stream_list(b"auds", &wave_format),
+        ]
+        .concat();
+
+        let info = parse_avi_header(&hdrl);
+        assert_eq!(info.duration, Some(Duration::from_secs(10)));
+        assert_eq!(info.resolution, Some((640, 480)));
+        assert_eq!(info.codec.as_deref(), Some("MPEG-4 Visual, MP3"));
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "0:05");
+        assert_eq!(format_duration(Duration::from_secs(205)), "3:25");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1:02:03");
+    }
+}
@@ -214,10 +214,17 @@ pub fn context_menu<'a>(
                     children.push(menu_item(fl!("extract-to"), Action::ExtractTo).into());
                 }
                 children.push(menu_item(fl!("compress"), Action::Compress).into());
+                if selected_dir < selected {
+                    children.push(menu_item(fl!("checksums"), Action::Checksums).into());
+                }
+                if selected == 2 {
+                    children.push(menu_item(fl!("compare"), Action::Compare).into());
+                }
                 children.push(divider::horizontal::light().into());
 
                 //TODO: Print?
                 children.push(menu_item(fl!("show-details"), Action::Preview).into());
+                children.push(menu_item(fl!("properties"), Action::Properties).into());
                 if matches!(tab.mode, tab::Mode::App) {
                     children.push(divider::horizontal::light().into());
                     children.push(menu_item(fl!("add-to-sidebar"), Action::AddToSidebar).into());
@@ -237,6 +244,8 @@ pub fn context_menu<'a>(
                     } else {
                         children.push(menu_item(fl!("move-to-trash"), Action::Delete).into());
                     }
+                    children
+                        .push(menu_item(fl!("secure-delete-menu"), Action::SecureDelete).into());
                 } else if selected == 1 {
                     children.push(menu_item(fl!("eject"), Action::Eject).into());
                 }
@@ -271,7 +280,22 @@ pub fn context_menu<'a>(
                 children.push(sort_item(fl!("sort-by-name"), HeadingOptions::Name));
                 children.push(sort_item(fl!("sort-by-modified"), HeadingOptions::Modified));
                 children.push(sort_item(fl!("sort-by-size"), HeadingOptions::Size));
+                if tab.has_date_taken() {
+                    children.push(sort_item(
+                        fl!("sort-by-date-taken"),
+                        HeadingOptions::DateTaken,
+                    ));
+                }
                 if matches!(tab.location, Location::Desktop(..)) {
+                    // Icons are only out of order after the user moved them
+                    if !tab.desktop_layout.positions.is_empty() {
+                        children.push(
+                            menu_item(fl!("auto-arrange"), Action::DesktopAutoArrange).into(),
+                        );
+                        children.push(
+                            menu_item(fl!("align-to-grid"), Action::DesktopAlignToGrid).into(),
+                        );
+                    }
                     children.push(divider::horizontal::light().into());
                     children.push(
                         menu_item(fl!("desktop-view-options"), Action::DesktopViewOptions).into(),
@@ -342,6 +366,7 @@ pub fn context_menu<'a>(
                 children.push(divider::horizontal::light().into());
                 children
                     .push(menu_item(fl!("restore-from-trash"), Action::RestoreFromTrash).into());
+                children.push(menu_item(fl!("restore-to"), Action::RestoreTo).into());
                 children.push(divider::horizontal::light().into());
                 children.push(menu_item(fl!("delete-permanently"), Action::Delete).into());
             } else {
@@ -593,6 +618,11 @@ pub fn menu_bar<'a>(
                         ),
                         menu::Item::Divider,
                         menu_button_optional(fl!("rename"), Action::Rename, selected > 0),
+                        menu_button_optional(
+                            fl!("menu-properties"),
+                            Action::Properties,
+                            selected > 0,
+                        ),
                         menu::Item::Divider,
                         menu::Item::Button(fl!("reload-folder"), None, Action::Reload),
                         menu::Item::Divider,
@@ -607,9 +637,35 @@ pub fn menu_bar<'a>(
                             Action::RestoreFromTrash,
                             selected > 0 && in_trash,
                         ),
+                        menu_button_optional(
+                            fl!("restore-to"),
+                            Action::RestoreTo,
+                            selected > 0 && in_trash,
+                        ),
                         menu_button_optional(delete_item, delete_item_action, selected > 0),
+                        menu_button_optional(
+                            fl!("secure-delete-menu"),
+                            Action::SecureDelete,
+                            selected > 0 && !in_trash,
+                        ),
                         menu::Item::Divider,
-                        menu::Item::Button(fl!("close-tab"), None, Action::TabClose),
+                        menu::Item::Button(fl!("duplicate-tab"), None, Action::TabDuplicate),
+                        menu::Item::CheckBox(
+                            fl!("pin-tab"),
+                            None,
+                            tab_opt.is_some_and(|tab| tab.pinned),
+                            Action::TabPin,
+                        ),
+                        menu::Item::Button(
+                            fl!("move-tab-to-new-window"),
+                            None,
+                            Action::TabMoveToNewWindow,
+                        ),
+                        menu_button_optional(
+                            fl!("close-tab"),
+                            Action::TabClose,
+                            !tab_opt.is_some_and(|tab| tab.pinned),
+                        ),
                         menu::Item::Button(fl!("quit"), None, Action::WindowClose),
                     ],
                 ),
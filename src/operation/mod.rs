@@ -1,7 +1,10 @@
+#[cfg(unix)]
+use crate::properties::{self, PermissionsChange};
 use crate::{
     app::{ArchiveType, DialogPage, Message, REPLACE_BUTTON_ID},
     config::IconSizes,
     fl,
+    photo::{self, Rotation},
     spawn_detached::spawn_detached,
     tab,
 };
@@ -24,6 +27,9 @@ pub mod controller;
 pub use self::reader::OpReader;
 pub mod reader;
 
+#[cfg(unix)]
+pub mod preserve;
+
 use self::recursive::{Context, Method};
 pub mod recursive;
 
@@ -89,6 +95,52 @@ async fn copy_or_move(
     paths: Vec<PathBuf>,
     to: PathBuf,
     method: Method,
+    preserve: bool,
+    verify: bool,
+    msg_tx: &Arc<TokioMutex<Sender<Message>>>,
+    controller: Controller,
+) -> Result<OperationSelection, OperationError> {
+    log::info!(
+        "{} {:?} to {}",
+        match method {
+            Method::Copy => "Copy",
+            Method::Move { .. } => "Move",
+        },
+        paths,
+        to.display()
+    );
+
+    // Handle duplicate file names by renaming paths
+    let from_to_pairs: Vec<(PathBuf, PathBuf)> = paths
+        .into_iter()
+        .zip(std::iter::repeat(to.as_path()))
+        .filter_map(|(from, to)| {
+            if matches!(from.parent(), Some(parent) if parent == to)
+                && matches!(method, Method::Copy)
+            {
+                // `from`'s parent is equal to `to` which means we're copying to the same
+                // directory (duplicating files)
+                let to = copy_unique_path(&from, to);
+                Some((from, to))
+            } else if let Some(name) = from.file_name() {
+                let to = to.join(name);
+                Some((from, to))
+            } else {
+                //TODO: how to handle from missing file name?
+                None
+            }
+        })
+        .collect();
+
+    copy_or_move_pairs(from_to_pairs, method, preserve, verify, msg_tx, controller).await
+}
+
+/// Copy or move each path to the path it is paired with
+async fn copy_or_move_pairs(
+    mut from_to_pairs: Vec<(PathBuf, PathBuf)>,
+    method: Method,
+    preserve: bool,
+    verify: bool,
     msg_tx: &Arc<TokioMutex<Sender<Message>>>,
     controller: Controller,
 ) -> Result<OperationSelection, OperationError> {
@@ -97,37 +149,6 @@ async fn copy_or_move(
 
     compio::runtime::spawn(async move {
         let controller = controller_c;
-        log::info!(
-            "{} {:?} to {}",
-            match method {
-                Method::Copy => "Copy",
-                Method::Move { .. } => "Move",
-            },
-            paths,
-            to.display()
-        );
-
-        // Handle duplicate file names by renaming paths
-        let mut from_to_pairs: Vec<(PathBuf, PathBuf)> = paths
-            .into_iter()
-            .zip(std::iter::repeat(to.as_path()))
-            .filter_map(|(from, to)| {
-                if matches!(from.parent(), Some(parent) if parent == to)
-                    && matches!(method, Method::Copy)
-                {
-                    // `from`'s parent is equal to `to` which means we're copying to the same
-                    // directory (duplicating files)
-                    let to = copy_unique_path(&from, to);
-                    Some((from, to))
-                } else if let Some(name) = from.file_name() {
-                    let to = to.join(name);
-                    Some((from, to))
-                } else {
-                    //TODO: how to handle from missing file name?
-                    None
-                }
-            })
-            .collect();
 
         // Attempt quick and simple renames
         //TODO: allow rename to be used for directories in recursive context?
@@ -157,7 +178,9 @@ async fn copy_or_move(
             });
         }
 
-        let mut context = Context::new(controller.clone());
+        let mut context = Context::new(controller.clone())
+            .preserve(preserve)
+            .verify(verify);
 
         {
             let controller = controller.clone();
@@ -296,16 +319,112 @@ fn paths_parent_name(paths: &[PathBuf]) -> Cow<'_, str> {
     file_name(parent)
 }
 
+#[cfg(unix)]
+fn set_permissions(
+    paths: &[PathBuf],
+    change: &PermissionsChange,
+    controller: &Controller,
+) -> Result<(), OperationError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // Collect every path first so progress can be reported, along with whether it was selected
+    let mut targets = Vec::new();
+    for path in paths {
+        if change.recursive && path.is_dir() {
+            for entry in WalkDir::new(path) {
+                let entry = entry.map_err(|e| OperationError::from_err(e, controller))?;
+                targets.push((entry.depth() == 0, entry.into_path()));
+            }
+        } else {
+            targets.push((true, path.clone()));
+        }
+    }
+
+    let total = targets.len();
+    for (i, (selected, path)) in targets.into_iter().enumerate() {
+        futures::executor::block_on(async {
+            controller
+                .check()
+                .await
+                .map_err(|s| OperationError::from_state(s, controller))
+        })?;
+
+        controller.set_progress((i as f32) / (total as f32));
+
+        // Selected paths are followed, symlinks found while recursing are never followed
+        if !selected && path.is_symlink() {
+            if change.uid.is_some() || change.gid.is_some() {
+                std::os::unix::fs::lchown(&path, change.uid, change.gid)
+                    .map_err(|e| OperationError::from_err(e, controller))?;
+            }
+            continue;
+        }
+
+        if change.uid.is_some() || change.gid.is_some() {
+            std::os::unix::fs::chown(&path, change.uid, change.gid)
+                .map_err(|e| OperationError::from_err(e, controller))?;
+        }
+
+        let metadata = fs::metadata(&path).map_err(|e| OperationError::from_err(e, controller))?;
+        // The group class of a path with an extended ACL is its group entry, not the mask
+        let mode = properties::effective_mode(&path, &metadata);
+        let mode_opt = change
+            .has_mode()
+            .then(|| change.apply_mode(mode, selected, metadata.is_dir()));
+        if let Some(mode) = mode_opt {
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode))
+                .map_err(|e| OperationError::from_err(e, controller))?;
+        }
+
+        // Changing the mode of a path with an extended ACL changes the ACL mask, so the
+        // named entries are written again to keep the group class as requested
+        if mode_opt.is_some() || change.has_acl() {
+            let acl_opt =
+                properties::get_acl(&path).map_err(|e| OperationError::from_err(e, controller))?;
+            if acl_opt.is_some() || change.has_acl() {
+                let mut named: Vec<_> = acl_opt
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|entry| entry.tag.is_named())
+                    .collect();
+                change.apply_acl(&mut named);
+                properties::set_acl(&path, mode_opt.unwrap_or(mode), &named)
+                    .map_err(|e| OperationError::from_err(e, controller))?;
+            }
+        }
+
+        if selected {
+            for (name, value_opt) in &change.xattrs {
+                match value_opt {
+                    Some(value) => properties::xattr_set(&path, name, value),
+                    None => properties::xattr_remove(&path, name),
+                }
+                .map_err(|e| OperationError::from_err(e, controller))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct OperationSelection {
     // Paths to ignore if they are already selected
     pub ignored: Vec<PathBuf>,
     // Paths to select
     pub selected: Vec<PathBuf>,
+    // Problems that did not stop the operation, like metadata that could not be preserved
+    pub warnings: Vec<String>,
+    // Files whose copy did not match when read back, the copies were removed
+    pub verify_failed: Vec<PathBuf>,
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Operation {
+    /// Compute checksums of files
+    Checksum {
+        paths: Vec<PathBuf>,
+    },
     /// Compress files
     Compress {
         paths: Vec<PathBuf>,
@@ -317,6 +436,10 @@ pub enum Operation {
     Copy {
         paths: Vec<PathBuf>,
         to: PathBuf,
+        /// Preserve ownership, permissions, extended attributes and timestamps
+        preserve: bool,
+        /// Compare checksums of copied files with their sources
+        verify: bool,
     },
     /// Move items to the trash
     Delete {
@@ -339,6 +462,10 @@ pub enum Operation {
         paths: Vec<PathBuf>,
         to: PathBuf,
         cross_device_copy: bool,
+        /// Preserve ownership, permissions, extended attributes and timestamps when copying
+        preserve: bool,
+        /// Compare checksums of copied files with their sources before removing them
+        verify: bool,
     },
     NewFile {
         path: PathBuf,
@@ -361,14 +488,34 @@ pub enum Operation {
     Restore {
         items: Vec<trash::TrashItem>,
     },
+    /// Restore items from the trash to another folder than the one they were deleted from
+    RestoreTo {
+        items: Vec<trash::TrashItem>,
+        to: PathBuf,
+        /// Preserve ownership, permissions, extended attributes and timestamps when copying
+        /// to another filesystem
+        preserve: bool,
+    },
+    /// Rotate JPEG photos without recompressing them
+    Rotate {
+        paths: Vec<PathBuf>,
+        rotation: Rotation,
+    },
+    /// Overwrite files before deleting them, so their contents cannot be recovered
+    SecureDelete {
+        paths: Box<[PathBuf]>,
+        /// Number of times the contents are overwritten
+        passes: u32,
+    },
     /// Set executable and launch
     SetExecutableAndLaunch {
         path: PathBuf,
     },
-    /// Set permissions
+    /// Set permissions, ownership, ACLs and extended attributes
+    #[cfg(unix)]
     SetPermissions {
-        path: PathBuf,
-        mode: u32,
+        paths: Vec<PathBuf>,
+        change: PermissionsChange,
     },
 }
 
@@ -438,6 +585,11 @@ impl Operation {
             ControllerState::Failed => fl!("progress-failed", percent = percent),
         };
         match self {
+            Self::Checksum { paths } => fl!(
+                "computing-checksums",
+                items = paths.len(),
+                progress = progress()
+            ),
             Self::Compress { paths, to, .. } => fl!(
                 "compressing",
                 items = paths.len(),
@@ -445,7 +597,7 @@ impl Operation {
                 to = file_name(to),
                 progress = progress()
             ),
-            Self::Copy { paths, to } => fl!(
+            Self::Copy { paths, to, .. } => fl!(
                 "copying",
                 items = paths.len(),
                 from = paths_parent_name(paths),
@@ -497,28 +649,50 @@ impl Operation {
             }
             Self::RemoveFromRecents { paths } => fl!("removing-from-recents", items = paths.len()),
             Self::Restore { items } => fl!("restoring", items = items.len(), progress = progress()),
+            Self::RestoreTo { items, to, .. } => fl!(
+                "restoring-to",
+                items = items.len(),
+                to = file_name(to),
+                progress = progress()
+            ),
+            Self::Rotate { paths, .. } => fl!("rotating", items = paths.len()),
+            Self::SecureDelete { paths, .. } => fl!(
+                "securely-deleting",
+                items = paths.len(),
+                progress = progress()
+            ),
             Self::SetExecutableAndLaunch { path } => {
                 fl!("setting-executable-and-launching", name = file_name(path))
             }
-            Self::SetPermissions { path, mode } => {
-                fl!(
-                    "setting-permissions",
-                    name = file_name(path),
-                    mode = format!("{:#03o}", mode)
-                )
+            #[cfg(unix)]
+            Self::SetPermissions { paths, change } => {
+                match (paths.as_slice(), change.full_mode()) {
+                    ([path], Some(mode)) if change.is_mode_only() => fl!(
+                        "setting-permissions",
+                        name = file_name(path),
+                        mode = format!("{:#03o}", mode)
+                    ),
+                    _ => fl!(
+                        "setting-properties",
+                        items = paths.len(),
+                        from = paths_parent_name(paths),
+                        progress = progress()
+                    ),
+                }
             }
         }
     }
 
     pub fn completed_text(&self) -> String {
         match self {
+            Self::Checksum { paths } => fl!("computed-checksums", items = paths.len()),
             Self::Compress { paths, to, .. } => fl!(
                 "compressed",
                 items = paths.len(),
                 from = paths_parent_name(paths),
                 to = file_name(to)
             ),
-            Self::Copy { paths, to } => fl!(
+            Self::Copy { paths, to, .. } => fl!(
                 "copied",
                 items = paths.len(),
                 from = paths_parent_name(paths),
@@ -562,15 +736,28 @@ impl Operation {
             Self::RemoveFromRecents { paths } => fl!("removed-from-recents", items = paths.len()),
             Self::Rename { from, to } => fl!("renamed", from = file_name(from), to = file_name(to)),
             Self::Restore { items } => fl!("restored", items = items.len()),
+            Self::RestoreTo { items, to, .. } => {
+                fl!("restored-to", items = items.len(), to = file_name(to))
+            }
+            Self::Rotate { paths, .. } => fl!("rotated", items = paths.len()),
+            Self::SecureDelete { paths, .. } => fl!("securely-deleted", items = paths.len()),
             Self::SetExecutableAndLaunch { path } => {
                 fl!("set-executable-and-launched", name = file_name(path))
             }
-            Self::SetPermissions { path, mode } => {
-                fl!(
-                    "set-permissions",
-                    name = file_name(path),
-                    mode = format!("{:#03o}", mode)
-                )
+            #[cfg(unix)]
+            Self::SetPermissions { paths, change } => {
+                match (paths.as_slice(), change.full_mode()) {
+                    ([path], Some(mode)) if change.is_mode_only() => fl!(
+                        "set-permissions",
+                        name = file_name(path),
+                        mode = format!("{:#03o}", mode)
+                    ),
+                    _ => fl!(
+                        "set-properties",
+                        items = paths.len(),
+                        from = paths_parent_name(paths)
+                    ),
+                }
             }
         }
     }
@@ -578,7 +765,8 @@ impl Operation {
     pub const fn show_progress_notification(&self) -> bool {
         // Long running operations show a progress notification
         match self {
-            Self::Compress { .. }
+            Self::Checksum { .. }
+            | Self::Compress { .. }
             | Self::Copy { .. }
             | Self::Delete { .. }
             | Self::DeleteTrash { .. }
@@ -586,13 +774,17 @@ impl Operation {
             | Self::Extract { .. }
             | Self::Move { .. }
             | Self::PermanentlyDelete { .. }
-            | Self::Restore { .. } => true,
+            | Self::Restore { .. }
+            | Self::RestoreTo { .. }
+            | Self::SecureDelete { .. } => true,
+            #[cfg(unix)]
+            Self::SetPermissions { change, .. } => change.recursive,
             Self::NewFile { .. }
             | Self::NewFolder { .. }
             | Self::RemoveFromRecents { .. }
             | Self::Rename { .. }
-            | Self::SetExecutableAndLaunch { .. }
-            | Self::SetPermissions { .. } => false,
+            | Self::Rotate { .. }
+            | Self::SetExecutableAndLaunch { .. } => false,
         }
     }
 
@@ -601,6 +793,7 @@ impl Operation {
             Self::Compress { .. } => Some(self.completed_text()),
             Self::Delete { .. } => Some(self.completed_text()),
             Self::Extract { .. } => Some(self.completed_text()),
+            Self::Rotate { .. } => Some(self.completed_text()),
             //TODO: more toasts
             _ => None,
         }
@@ -616,6 +809,26 @@ impl Operation {
 
         //TODO: IF ERROR, RETURN AN Operation THAT CAN UNDO THE CURRENT STATE
         let paths: Result<OperationSelection, OperationError> = match self {
+            Self::Checksum { paths } => {
+                let msg_tx = msg_tx.clone();
+                let controller_clone = controller.clone();
+                compio::runtime::spawn_blocking(move || -> Result<(), OperationError> {
+                    let controller = controller_clone;
+                    crate::checksum::hash_paths(&paths, &controller, |path, result| {
+                        futures::executor::block_on(async {
+                            let _ = msg_tx
+                                .lock()
+                                .await
+                                .send(Message::ChecksumResult(path, result))
+                                .await;
+                        });
+                    })
+                })
+                .await
+                .map_err(wrap_compio_spawn_error)?
+                .map_err(|e| OperationError::from_err(e, &controller))?;
+                Ok(OperationSelection::default())
+            }
             Self::Compress {
                 paths,
                 to,
@@ -636,6 +849,7 @@ impl Operation {
                         let op_sel = OperationSelection {
                             ignored: paths.clone(),
                             selected: vec![to.clone()],
+                            ..Default::default()
                         };
 
                         let mut paths = paths;
@@ -791,8 +1005,22 @@ impl Operation {
                 .await
                 .map_err(wrap_compio_spawn_error)?
             }
-            Self::Copy { paths, to } => {
-                copy_or_move(paths, to, Method::Copy, msg_tx, controller).await
+            Self::Copy {
+                paths,
+                to,
+                preserve,
+                verify,
+            } => {
+                copy_or_move(
+                    paths,
+                    to,
+                    Method::Copy,
+                    preserve,
+                    verify,
+                    msg_tx,
+                    controller,
+                )
+                .await
             }
             Self::Delete { paths } => {
                 let total = paths.len();
@@ -806,10 +1034,10 @@ impl Operation {
 
                     controller.set_progress((i as f32) / (total as f32));
 
-                    let _items_opt = compio::runtime::spawn_blocking(|| trash::delete(path))
+                    compio::runtime::spawn_blocking(move || crate::trash_folder::delete(&path))
                         .await
-                        .map_err(wrap_compio_spawn_error)?;
-                    //TODO: items_opt allows for easy restore
+                        .map_err(wrap_compio_spawn_error)?
+                        .map_err(|err| OperationError::from_err(err, &controller))?;
                 }
                 Ok(OperationSelection::default())
             }
@@ -935,11 +1163,15 @@ impl Operation {
                 paths,
                 to,
                 cross_device_copy,
+                preserve,
+                verify,
             } => {
                 copy_or_move(
                     paths,
                     to,
                     Method::Move { cross_device_copy },
+                    preserve,
+                    verify,
                     msg_tx,
                     controller,
                 )
@@ -959,6 +1191,7 @@ impl Operation {
                     Result::<_, OperationError>::Ok(OperationSelection {
                         ignored: Vec::new(),
                         selected: vec![path],
+                        ..Default::default()
                     })
                 })
             }
@@ -978,6 +1211,7 @@ impl Operation {
                     Result::<_, OperationError>::Ok(OperationSelection {
                         ignored: Vec::new(),
                         selected: vec![path],
+                        ..Default::default()
                     })
                 })
             }
@@ -1038,6 +1272,7 @@ impl Operation {
                     Result::<_, OperationError>::Ok(OperationSelection {
                         ignored: vec![from],
                         selected: vec![to],
+                        ..Default::default()
                     })
                 })
             }
@@ -1070,7 +1305,96 @@ impl Operation {
                 Ok(OperationSelection {
                     ignored: Vec::new(),
                     selected: paths,
+                    ..Default::default()
+                })
+            }
+            #[cfg(not(all(
+                unix,
+                not(target_os = "macos"),
+                not(target_os = "ios"),
+                not(target_os = "android")
+            )))]
+            Self::RestoreTo { .. } => Err(OperationError::from_msg(
+                "Restoring from trash to another folder is not supported on this platform",
+            )),
+            #[cfg(all(
+                unix,
+                not(target_os = "macos"),
+                not(target_os = "ios"),
+                not(target_os = "android")
+            ))]
+            Self::RestoreTo {
+                items,
+                to,
+                preserve,
+            } => {
+                // Trashed items are moved out of the trash folder under their original names
+                let mut infos = Vec::with_capacity(items.len());
+                let mut from_to_pairs = Vec::with_capacity(items.len());
+                for item in &items {
+                    let info = PathBuf::from(&item.id);
+                    if let Some(from) = crate::trash_folder::item_file(&info) {
+                        from_to_pairs.push((from, to.join(&item.name)));
+                        infos.push(info);
+                    }
+                }
+                let op_sel = copy_or_move_pairs(
+                    from_to_pairs.clone(),
+                    Method::Move {
+                        cross_device_copy: false,
+                    },
+                    preserve,
+                    false,
+                    msg_tx,
+                    controller,
+                )
+                .await?;
+                // Items that were skipped are still in the trash and keep their info
+                for (info, (from, _)) in infos.iter().zip(&from_to_pairs) {
+                    if !from.exists() {
+                        if let Err(err) = fs::remove_file(info) {
+                            log::warn!("failed to remove {}: {}", info.display(), err);
+                        }
+                    }
+                }
+                Ok(op_sel)
+            }
+            Self::Rotate { paths, rotation } => {
+                controller
+                    .check()
+                    .await
+                    .map_err(|s| OperationError::from_state(s, &controller))?;
+
+                let controller_clone = controller.clone();
+                compio::runtime::spawn_blocking(move || -> Result<(), OperationError> {
+                    let controller = controller_clone;
+                    let total = paths.len();
+                    for (i, path) in paths.iter().enumerate() {
+                        controller.set_progress((i as f32) / (total as f32));
+                        photo::rotate_jpeg(path, rotation).map_err(|e| {
+                            OperationError::from_err(
+                                format!("{}: {}", path.display(), e),
+                                &controller,
+                            )
+                        })?;
+                    }
+                    Ok(())
+                })
+                .await
+                .map_err(wrap_compio_spawn_error)?
+                .map_err(|e| OperationError::from_err(e, &controller))?;
+                Ok(OperationSelection::default())
+            }
+            Self::SecureDelete { paths, passes } => {
+                let controller_clone = controller.clone();
+                compio::runtime::spawn_blocking(move || -> Result<(), OperationError> {
+                    let controller = controller_clone;
+                    crate::shred::shred_paths(&paths, passes, &controller)
                 })
+                .await
+                .map_err(wrap_compio_spawn_error)?
+                .map_err(|e| OperationError::from_err(e, &controller))?;
+                Ok(OperationSelection::default())
             }
             Self::SetExecutableAndLaunch { path } => {
                 controller
@@ -1107,7 +1431,8 @@ impl Operation {
                 .map_err(|e| OperationError::from_err(e, &controller))?;
                 Ok(OperationSelection::default())
             }
-            Self::SetPermissions { path, mode } => {
+            #[cfg(unix)]
+            Self::SetPermissions { paths, change } => {
                 controller
                     .check()
                     .await
@@ -1116,16 +1441,7 @@ impl Operation {
                 let controller_clone = controller.clone();
                 compio::runtime::spawn_blocking(move || -> Result<(), OperationError> {
                     let controller = controller_clone;
-                    //TODO: what to do on non-Unix systems?
-                    #[cfg(unix)]
-                    {
-                        use std::os::unix::fs::PermissionsExt;
-                        let perms = fs::Permissions::from_mode(mode);
-                        fs::set_permissions(&path, perms)
-                            .map_err(|e| OperationError::from_err(e, &controller))?;
-                    }
-
-                    Ok(())
+                    set_permissions(&paths, &change, &controller)
                 })
                 .await
                 .map_err(wrap_compio_spawn_error)?
@@ -1184,20 +1500,28 @@ mod tests {
     pub async fn operation_copy(
         paths: Vec<PathBuf>,
         to: PathBuf,
+    ) -> Result<OperationSelection, OperationError> {
+        operation_perform(Operation::Copy {
+            paths,
+            to,
+            preserve: false,
+            verify: false,
+        })
+        .await
+    }
+
+    /// Perform an operation, cancelling any replace requests
+    pub async fn operation_perform(
+        operation: Operation,
     ) -> Result<OperationSelection, OperationError> {
         let id = fastrand::u64(0..u64::MAX);
         let (tx, mut rx) = mpsc::channel(1);
-        let paths_clone = paths.clone();
-        let to_clone = to.clone();
 
         // Wrap this into its own future so that it may be polled concurerntly with the message handler.
         let handle_copy = async move {
-            Operation::Copy {
-                paths: paths_clone,
-                to: to_clone,
-            }
-            .perform(&sync::Mutex::new(tx).into(), Controller::default())
-            .await
+            operation
+                .perform(&sync::Mutex::new(tx).into(), Controller::default())
+                .await
         };
 
         // Concurrently handling messages will prevent the mpsc channel from blocking when full.
@@ -1388,6 +1712,86 @@ mod tests {
         assert!(file_path.exists(), "Original file should still exist");
         assert!(expected.exists(), "File should have been copied");
 
+        Ok(())
+    }
+    #[test(compio::test)]
+    async fn copy_preserves_metadata() -> io::Result<()> {
+        use std::{
+            os::unix::fs::PermissionsExt,
+            time::{Duration, SystemTime},
+        };
+
+        let fs = empty_fs()?;
+        let path = fs.path();
+
+        let file_path = path.join("ferris");
+        fs::write(&file_path, b"crab")?;
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        File::options()
+            .write(true)
+            .open(&file_path)?
+            .set_modified(modified)?;
+        fs::set_permissions(&file_path, fs::Permissions::from_mode(0o640))?;
+        let dir_path = path.join("cosmic");
+        fs::create_dir(&dir_path)?;
+
+        let op_sel = operation_perform(Operation::Copy {
+            paths: vec![file_path.clone()],
+            to: dir_path.clone(),
+            preserve: true,
+            verify: false,
+        })
+        .await
+        .expect("Copy operation should have succeeded");
+        assert!(op_sel.warnings.is_empty(), "{:?}", op_sel.warnings);
+
+        let metadata = fs::metadata(dir_path.join("ferris"))?;
+        assert_eq!(metadata.modified()?, modified);
+        assert_eq!(metadata.permissions().mode() & 0o7777, 0o640);
+
+        Ok(())
+    }
+
+    #[test(compio::test)]
+    async fn copy_dir_with_verify() -> io::Result<()> {
+        let fs = simple_fs(NUM_FILES, NUM_HIDDEN, NUM_DIRS, NUM_NESTED, NAME_LEN)?;
+        let path = fs.path();
+
+        let first_dir = filter_dirs(path)?
+            .next()
+            .expect("Should have at least one directory");
+        let to = path.join("verified");
+        fs::create_dir(&to)?;
+
+        debug!(
+            "Copying {} to {} with verify",
+            first_dir.display(),
+            to.display()
+        );
+        let op_sel = operation_perform(Operation::Copy {
+            paths: vec![first_dir.clone()],
+            to: to.clone(),
+            preserve: false,
+            verify: true,
+        })
+        .await
+        .expect("Verified copy operation should have succeeded");
+        assert!(
+            op_sel.verify_failed.is_empty(),
+            "{:?}",
+            op_sel.verify_failed
+        );
+
+        let copied_dir = to.join(first_dir.file_name().expect("Directory has a name"));
+        for file in filter_files(&first_dir)? {
+            let copied = copied_dir.join(file.file_name().expect("File has a name"));
+            assert_eq!(
+                fs::read(&file)?,
+                fs::read(&copied)?,
+                "Copied file should match the original"
+            );
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,118 @@
+use std::{
+    ffi::CString,
+    fs, io,
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{MetadataExt, PermissionsExt},
+    },
+    path::Path,
+};
+
+use crate::properties;
+
+/// Copy ownership, mode bits, extended attributes (including ACLs) and timestamps of `from` to
+/// `to`. Anything that could not be preserved is added to `warnings`.
+///
+/// Ownership is only preserved when running as root, as other users cannot give files away.
+pub fn copy_metadata(from: &Path, to: &Path, warnings: &mut Vec<String>) {
+    let metadata = match fs::symlink_metadata(from) {
+        Ok(ok) => ok,
+        Err(err) => {
+            warnings.push(format!(
+                "failed to read metadata of {}: {}",
+                from.display(),
+                err
+            ));
+            return;
+        }
+    };
+    let is_symlink = metadata.file_type().is_symlink();
+    let privileged = unsafe { libc::geteuid() } == 0;
+
+    if privileged {
+        let result = if is_symlink {
+            std::os::unix::fs::lchown(to, Some(metadata.uid()), Some(metadata.gid()))
+        } else {
+            std::os::unix::fs::chown(to, Some(metadata.uid()), Some(metadata.gid()))
+        };
+        if let Err(err) = result {
+            warnings.push(format!(
+                "failed to preserve ownership of {}: {}",
+                to.display(),
+                err
+            ));
+        }
+    }
+
+    // Symlinks have no mode or extended attributes of their own on Linux
+    if !is_symlink {
+        // Set after changing ownership, which clears the setuid and setgid bits
+        if let Err(err) =
+            fs::set_permissions(to, fs::Permissions::from_mode(metadata.mode() & 0o7777))
+        {
+            warnings.push(format!(
+                "failed to preserve permissions of {}: {}",
+                to.display(),
+                err
+            ));
+        }
+
+        // ACLs are stored as system.posix_acl_* attributes, and are set after the mode as
+        // they would be reset by it
+        if let Err(err) = copy_xattrs(from, to, privileged) {
+            warnings.push(format!(
+                "failed to preserve extended attributes of {}: {}",
+                to.display(),
+                err
+            ));
+        }
+    }
+
+    if let Err(err) = set_times(to, &metadata, is_symlink) {
+        warnings.push(format!(
+            "failed to preserve timestamps of {}: {}",
+            to.display(),
+            err
+        ));
+    }
+}
+
+fn copy_xattrs(from: &Path, to: &Path, privileged: bool) -> io::Result<()> {
+    for name in properties::xattr_list(from)? {
+        // Only root may write these namespaces
+        if !privileged && (name.starts_with("trusted.") || name.starts_with("security.")) {
+            continue;
+        }
+        if let Some(value) = properties::xattr_get(from, &name)? {
+            properties::xattr_set(to, &name, &value)
+                .map_err(|err| io::Error::new(err.kind(), format!("{name}: {err}")))?;
+        }
+    }
+    Ok(())
+}
+
+fn set_times(path: &Path, metadata: &fs::Metadata, is_symlink: bool) -> io::Result<()> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    let times = [
+        libc::timespec {
+            tv_sec: metadata.atime() as _,
+            tv_nsec: metadata.atime_nsec() as _,
+        },
+        libc::timespec {
+            tv_sec: metadata.mtime() as _,
+            tv_nsec: metadata.mtime_nsec() as _,
+        },
+    ];
+    let flags = if is_symlink {
+        libc::AT_SYMLINK_NOFOLLOW
+    } else {
+        0
+    };
+    let ret = unsafe { libc::utimensat(libc::AT_FDCWD, c_path.as_ptr(), times.as_ptr(), flags) };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
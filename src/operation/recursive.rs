@@ -4,7 +4,14 @@ use compio::io::{AsyncReadAt, AsyncWriteAt};
 use std::future::Future;
 use std::pin::Pin;
 use std::time::Instant;
-use std::{cell::Cell, error::Error, fs, ops::ControlFlow, path::PathBuf, rc::Rc};
+use std::{
+    cell::Cell,
+    error::Error,
+    fs,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 use walkdir::WalkDir;
 
 use crate::operation::OperationError;
@@ -23,6 +30,10 @@ pub struct Context {
     on_replace: Pin<Box<dyn OnReplace>>,
     pub(crate) op_sel: OperationSelection,
     replace_result_opt: Option<ReplaceResult>,
+    verify: bool,
+    preserve: bool,
+    /// Directories to preserve metadata of once their contents are written
+    preserve_dirs: Vec<(PathBuf, PathBuf)>,
 }
 
 pub trait OnProgress: Fn(&Op, &Progress) + 'static {}
@@ -47,6 +58,9 @@ impl Context {
             on_replace: Box::pin(|_op| Box::pin(async { ReplaceResult::Cancel })),
             op_sel: OperationSelection::default(),
             replace_result_opt: None,
+            verify: false,
+            preserve: false,
+            preserve_dirs: Vec::new(),
         }
     }
 
@@ -177,8 +191,11 @@ impl Context {
                     &self.controller,
                 )
             })? {
-                // The from path is ignored in the operation selection if it is a top level item
-                if self.op_sel.ignored.contains(&op.from) {
+                // The from path is ignored in the operation selection if it is a top level item,
+                // and its copy was not removed after failing verification
+                if self.op_sel.ignored.contains(&op.from)
+                    && !self.op_sel.verify_failed.contains(&op.from)
+                {
                     // So add the to path to the selection
                     self.op_sel.selected.push(op.to.clone());
                 }
@@ -188,6 +205,15 @@ impl Context {
             }
         }
 
+        // Deepest directories first, as setting a parent's times could be undone by a child
+        #[cfg(unix)]
+        for (from, to) in self.preserve_dirs.drain(..).rev() {
+            super::preserve::copy_metadata(&from, &to, &mut self.op_sel.warnings);
+        }
+        for warning in &self.op_sel.warnings {
+            log::warn!("{warning}");
+        }
+
         Ok(true)
     }
 
@@ -201,6 +227,65 @@ impl Context {
         self
     }
 
+    /// Preserve ownership, permissions, extended attributes and timestamps of copies
+    pub fn preserve(mut self, preserve: bool) -> Self {
+        self.preserve = preserve;
+        self
+    }
+
+    /// Re-read copied files and compare checksums before considering them done
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Read a file back from storage and return its checksum
+    async fn checksum_file(&mut self, path: &Path) -> Result<blake3::Hash, Box<dyn Error>> {
+        let file = compio::fs::File::open(path).await?;
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::fd::AsRawFd;
+            // Drop cached pages so the data is read from the device and not from memory
+            let ret =
+                unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED) };
+            if ret != 0 {
+                log::warn!(
+                    "failed to drop cache for {}: {}",
+                    path.display(),
+                    std::io::Error::from_raw_os_error(ret)
+                );
+            }
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        let mut buf_in = std::mem::take(&mut self.buf);
+        let mut pos = 0;
+        loop {
+            let BufResult(result, buf_out) = file.read_at(buf_in, pos).await;
+            let count = match result {
+                Ok(0) => {
+                    self.buf = buf_out;
+                    break;
+                }
+                Ok(count) => count,
+                Err(why) => {
+                    self.buf = buf_out;
+                    return Err(why.into());
+                }
+            };
+            hasher.update(&buf_out[..count]);
+            pos += count as u64;
+
+            if let Err(state) = self.controller.check().await {
+                self.buf = buf_out;
+                return Err(OperationError::from_state(state, &self.controller).into());
+            }
+
+            buf_in = buf_out;
+        }
+        Ok(hasher.finalize())
+    }
+
     async fn replace(&mut self, op: &Op) -> Result<ControlFlow<bool, PathBuf>, Box<dyn Error>> {
         let replace_result = match self.replace_result_opt {
             Some(result) => result,
@@ -334,57 +419,105 @@ impl Op {
                     );
                 }
 
-                // Prevent spamming the progress callbacks.
-                let mut last_progress_update = Instant::now();
-                // io_uring/IOCP requires transferring ownership of the buffer to the kernel.
-                let mut buf_in = std::mem::take(&mut ctx.buf);
-                // Track where the current read/write position is at.
-                let mut pos = 0;
-
-                loop {
-                    let BufResult(result, buf_out) = from_file.read_at(buf_in, pos).await;
+                // Checksum of the source data, compared to the destination when verifying
+                let mut hasher_opt = ctx.verify.then(blake3::Hasher::new);
+
+                // Same filesystem copies can be done by the kernel without reading the data
+                #[cfg(target_os = "linux")]
+                let fast_copied = fast_copy(ctx, self, &from_file, &to_file, &mut progress).await?;
+                #[cfg(not(target_os = "linux"))]
+                let fast_copied = false;
+
+                if !fast_copied {
+                    // Prevent spamming the progress callbacks.
+                    let mut last_progress_update = Instant::now();
+                    // io_uring/IOCP requires transferring ownership of the buffer to the kernel.
+                    let mut buf_in = std::mem::take(&mut ctx.buf);
+                    // Track where the current read/write position is at.
+                    let mut pos = 0;
+
+                    loop {
+                        let BufResult(result, buf_out) = from_file.read_at(buf_in, pos).await;
+
+                        let count = match result {
+                            Ok(0) => {
+                                ctx.buf = buf_out;
+                                break;
+                            }
+                            Ok(count) => count,
+                            Err(why) => {
+                                ctx.buf = buf_out;
+                                return Err(why.into());
+                            }
+                        };
 
-                    let count = match result {
-                        Ok(0) => {
-                            ctx.buf = buf_out;
-                            break;
+                        if let Some(hasher) = &mut hasher_opt {
+                            hasher.update(&buf_out[..count]);
                         }
-                        Ok(count) => count,
-                        Err(why) => {
+
+                        let BufResult(result, buf_out_slice) =
+                            to_file.write_at(buf_out.slice(..count), pos).await;
+                        let buf_out = buf_out_slice.into_inner();
+
+                        if let Err(why) = result {
                             ctx.buf = buf_out;
                             return Err(why.into());
                         }
-                    };
 
-                    let BufResult(result, buf_out_slice) =
-                        to_file.write_at(buf_out.slice(..count), pos).await;
-                    let buf_out = buf_out_slice.into_inner();
+                        progress.current_bytes += count as u64;
+                        pos += count as u64;
+
+                        // Avoid spamming progress messages too early.
+                        let current = Instant::now();
+                        if current.duration_since(last_progress_update).as_millis() > 49 {
+                            last_progress_update = current;
+                            (ctx.on_progress)(self, &progress);
+
+                            // Also check if the progress was cancelled.
+                            if let Err(state) = ctx.controller.check().await {
+                                ctx.buf = buf_out;
+                                return Err(
+                                    OperationError::from_state(state, &ctx.controller).into()
+                                );
+                            }
+                        }
 
-                    if let Err(why) = result {
-                        ctx.buf = buf_out;
-                        return Err(why.into());
+                        buf_in = buf_out;
                     }
+                }
 
-                    progress.current_bytes += count as u64;
-                    pos += count as u64;
-
-                    // Avoid spamming progress messages too early.
-                    let current = Instant::now();
-                    if current.duration_since(last_progress_update).as_millis() > 49 {
-                        last_progress_update = current;
-                        (ctx.on_progress)(self, &progress);
+                to_file.sync_all().await?;
+                drop(to_file);
 
-                        // Also check if the progress was cancelled.
-                        if let Err(state) = ctx.controller.check().await {
-                            ctx.buf = buf_out;
-                            return Err(OperationError::from_state(state, &ctx.controller).into());
+                if let Some(hasher) = hasher_opt {
+                    let expected = if fast_copied {
+                        // The data was never read, so read the source too
+                        ctx.checksum_file(&self.from).await?
+                    } else {
+                        hasher.finalize()
+                    };
+                    let actual = ctx.checksum_file(&self.to).await?;
+                    if actual != expected {
+                        log::warn!(
+                            "verification of {} failed, expected {} but found {}",
+                            self.to.display(),
+                            expected.to_hex(),
+                            actual.to_hex()
+                        );
+                        // Keep the source, and do not leave a corrupt copy behind
+                        self.skipped.cleanup.set(true);
+                        if let Err(err) = compio::fs::remove_file(&self.to).await {
+                            log::warn!("failed to remove {}: {}", self.to.display(), err);
                         }
+                        ctx.op_sel.verify_failed.push(self.from.clone());
+                        return Ok(true);
                     }
-
-                    buf_in = buf_out;
                 }
 
-                to_file.sync_all().await?;
+                #[cfg(unix)]
+                if ctx.preserve {
+                    super::preserve::copy_metadata(&self.from, &self.to, &mut ctx.op_sel.warnings);
+                }
             }
             OpKind::Move { cross_device_copy } => {
                 // Remove `to` if overwriting and it is an existing file
@@ -429,11 +562,23 @@ impl Op {
             }
             OpKind::Mkdir => {
                 compio::fs::create_dir_all(&self.to).await?;
+                if ctx.preserve {
+                    ctx.preserve_dirs.push((self.from.clone(), self.to.clone()));
+                }
             }
             OpKind::Remove => {
                 compio::fs::remove_file(&self.from).await?;
             }
             OpKind::Rmdir => {
+                // Directories still holding sources that failed verification are kept
+                if ctx
+                    .op_sel
+                    .verify_failed
+                    .iter()
+                    .any(|path| path.starts_with(&self.from))
+                {
+                    return Ok(true);
+                }
                 compio::fs::remove_dir(&self.from).await?;
             }
             OpKind::Symlink { ref target } => {
@@ -451,6 +596,13 @@ impl Op {
                 #[cfg(unix)]
                 {
                     std::os::unix::fs::symlink(target, &self.to)?;
+                    if ctx.preserve {
+                        super::preserve::copy_metadata(
+                            &self.from,
+                            &self.to,
+                            &mut ctx.op_sel.warnings,
+                        );
+                    }
                 }
                 #[cfg(windows)]
                 {
@@ -465,3 +617,95 @@ impl Op {
         Ok(true)
     }
 }
+
+/// Copy file contents with a reflink or `copy_file_range`, returning false if neither
+/// works between these files so the buffered copy must be used instead
+#[cfg(target_os = "linux")]
+async fn fast_copy(
+    ctx: &Context,
+    op: &Op,
+    from_file: &compio::fs::File,
+    to_file: &compio::fs::File,
+    progress: &mut Progress,
+) -> Result<bool, Box<dyn Error>> {
+    use std::os::fd::AsRawFd;
+
+    // Larger chunks than the buffered copy, as the data does not pass through userspace
+    const CHUNK_SIZE: usize = 64 * 1024 * 1024;
+
+    let from_fd = from_file.as_raw_fd();
+    let to_fd = to_file.as_raw_fd();
+    let total_bytes = progress.total_bytes.unwrap_or(0);
+
+    // Copy on write filesystems like Btrfs and XFS can share extents with a reflink
+    if unsafe { libc::ioctl(to_fd, libc::FICLONE, from_fd) } == 0 {
+        log::debug!("reflinked {} to {}", op.from.display(), op.to.display());
+        progress.current_bytes = total_bytes;
+        (ctx.on_progress)(op, progress);
+        return Ok(true);
+    }
+
+    let mut last_progress_update = Instant::now();
+    let mut pos = 0u64;
+    loop {
+        let result = compio::runtime::spawn_blocking(move || {
+            let mut off_in = pos as libc::loff_t;
+            let mut off_out = pos as libc::loff_t;
+            let ret = unsafe {
+                libc::copy_file_range(from_fd, &mut off_in, to_fd, &mut off_out, CHUNK_SIZE, 0)
+            };
+            if ret < 0 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(ret as u64)
+            }
+        })
+        .await
+        .map_err(|_| "copy_file_range task failed")?;
+
+        let count = match result {
+            // Some filesystems like procfs report no data, so fall back if nothing was copied
+            Ok(0) if pos == 0 && total_bytes > 0 => return Ok(false),
+            Ok(0) => break,
+            Ok(count) => count,
+            Err(err)
+                if pos == 0
+                    && matches!(
+                        err.raw_os_error(),
+                        Some(
+                            libc::EXDEV
+                                | libc::ENOSYS
+                                | libc::EOPNOTSUPP
+                                | libc::EINVAL
+                                | libc::EBADF
+                                | libc::EPERM
+                        )
+                    ) =>
+            {
+                log::debug!(
+                    "copy_file_range unsupported from {} to {}: {}",
+                    op.from.display(),
+                    op.to.display(),
+                    err
+                );
+                return Ok(false);
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        pos += count;
+        progress.current_bytes = pos;
+
+        let current = Instant::now();
+        if current.duration_since(last_progress_update).as_millis() > 49 {
+            last_progress_update = current;
+            (ctx.on_progress)(op, progress);
+        }
+
+        if let Err(state) = ctx.controller.check().await {
+            return Err(OperationError::from_state(state, &ctx.controller).into());
+        }
+    }
+    (ctx.on_progress)(op, progress);
+    Ok(true)
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use hayro::{InterpreterSettings, Pdf, RenderSettings};
+use image::DynamicImage;
+use std::{fs, path::Path, sync::Arc};
+
+/// A rendered page of a PDF document
+pub struct PdfPage {
+    pub image: DynamicImage,
+    pub page: usize,
+    pub page_count: usize,
+}
+
+/// Render a page of a PDF document so that it fits within `max_size` pixels.
+/// Pages past the end of the document are clamped to the last page.
+pub fn render_page(path: &Path, page: usize, max_size: u32) -> Result<PdfPage, String> {
+    let data =
+        fs::read(path).map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+    let pdf = Pdf::new(Arc::new(data))
+        .map_err(|err| format!("failed to load PDF {}: {:?}", path.display(), err))?;
+    let pages = pdf.pages();
+    let page_count = pages.len();
+    if page_count == 0 {
+        return Err(format!("PDF {} has no pages", path.display()));
+    }
+    let page = page.min(page_count - 1);
+    let pdf_page = &pages[page];
+
+    let (width, height) = pdf_page.render_dimensions();
+    let scale = max_size as f32 / width.max(height).max(1.0);
+    let render_settings = RenderSettings {
+        x_scale: scale,
+        y_scale: scale,
+        ..Default::default()
+    };
+    let pixmap = hayro::render(pdf_page, &InterpreterSettings::default(), &render_settings);
+    let image = image::load_from_memory_with_format(&pixmap.take_png(), image::ImageFormat::Png)
+        .map_err(|err| {
+            format!(
+                "failed to decode rendered page of {}: {}",
+                path.display(),
+                err
+            )
+        })?;
+
+    Ok(PdfPage {
+        image,
+        page,
+        page_count,
+    })
+}
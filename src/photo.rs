@@ -0,0 +1,721 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use exif::{Exif, Field, In, Tag, Value};
+use image::{DynamicImage, ImageDecoder, ImageReader, ImageResult, metadata::Orientation};
+use mime_guess::{Mime, mime};
+use regex::bytes::Regex;
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Seek, Write},
+    os::unix::fs::FileExt,
+    path::Path,
+    sync::LazyLock,
+    time::SystemTime,
+};
+
+/// Only this many bytes are searched for an XMP packet
+const MAX_XMP_SEARCH: u64 = 4 * 1024 * 1024;
+
+/// Camera information of a photo, read from EXIF with XMP as a fallback. Fields that could not be
+/// determined are left empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhotoInfo {
+    pub camera: Option<String>,
+    pub lens: Option<String>,
+    /// Exposure time, aperture, ISO and focal length
+    pub exposure: Option<String>,
+    /// Latitude and longitude in degrees
+    pub gps: Option<(f64, f64)>,
+    pub date_taken: Option<SystemTime>,
+}
+
+impl PhotoInfo {
+    pub fn read(path: &Path) -> Self {
+        let mut info = match read_exif(path) {
+            Ok(exif) => Self::from_exif(&exif),
+            Err(err) => {
+                log::debug!("failed to read EXIF of {}: {}", path.display(), err);
+                Self::default()
+            }
+        };
+        if info.camera.is_none() || info.date_taken.is_none() {
+            match read_xmp(path) {
+                Ok(Some(xmp)) => info.merge_xmp(&xmp),
+                Ok(None) => {}
+                Err(err) => {
+                    log::debug!("failed to read XMP of {}: {}", path.display(), err);
+                }
+            }
+        }
+        info
+    }
+
+    fn from_exif(exif: &Exif) -> Self {
+        let field = |tag| exif.get_field(tag, In::PRIMARY);
+        let text = |tag| field(tag).and_then(ascii);
+
+        let mut exposure = Vec::new();
+        if let Some(time) = field(Tag::ExposureTime).and_then(rational) {
+            exposure.push(format_exposure_time(time));
+        }
+        if let Some(f_number) = field(Tag::FNumber).and_then(rational) {
+            exposure.push(format!("f/{}", trim_float(f_number, 1)));
+        }
+        if let Some(iso) = field(Tag::PhotographicSensitivity).and_then(|f| f.value.get_uint(0)) {
+            exposure.push(format!("ISO {iso}"));
+        }
+        if let Some(focal_length) = field(Tag::FocalLength).and_then(rational) {
+            exposure.push(format!("{} mm", trim_float(focal_length, 1)));
+        }
+
+        let coordinate = |tag, ref_tag, negative: &str| {
+            let Value::Rational(parts) = &field(tag)?.value else {
+                return None;
+            };
+            let degrees = parts
+                .iter()
+                .zip([1.0, 60.0, 3600.0])
+                .map(|(part, divisor)| part.to_f64() / divisor)
+                .sum::<f64>();
+            Some(if text(ref_tag).as_deref() == Some(negative) {
+                -degrees
+            } else {
+                degrees
+            })
+        };
+        let gps = coordinate(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S").zip(coordinate(
+            Tag::GPSLongitude,
+            Tag::GPSLongitudeRef,
+            "W",
+        ));
+
+        let date_taken = [Tag::DateTimeOriginal, Tag::DateTimeDigitized, Tag::DateTime]
+            .into_iter()
+            .find_map(|tag| match &field(tag)?.value {
+                Value::Ascii(values) => {
+                    let date_time = exif::DateTime::from_ascii(values.first()?).ok()?;
+                    NaiveDate::from_ymd_opt(
+                        date_time.year.into(),
+                        date_time.month.into(),
+                        date_time.day.into(),
+                    )?
+                    .and_hms_opt(
+                        date_time.hour.into(),
+                        date_time.minute.into(),
+                        date_time.second.into(),
+                    )
+                }
+                _ => None,
+            })
+            .and_then(local_time);
+
+        Self {
+            camera: camera_name(text(Tag::Make), text(Tag::Model)),
+            lens: text(Tag::LensModel),
+            exposure: (!exposure.is_empty()).then(|| exposure.join(", ")),
+            gps,
+            date_taken,
+        }
+    }
+
+    /// Fill in missing fields from an XMP packet
+    fn merge_xmp(&mut self, xmp: &[u8]) {
+        if self.camera.is_none() {
+            self.camera = camera_name(
+                xmp_property(xmp, "tiff:Make"),
+                xmp_property(xmp, "tiff:Model"),
+            );
+        }
+        if self.lens.is_none() {
+            self.lens =
+                xmp_property(xmp, "exifEX:LensModel").or_else(|| xmp_property(xmp, "aux:Lens"));
+        }
+        if self.gps.is_none() {
+            self.gps = xmp_property(xmp, "exif:GPSLatitude")
+                .and_then(|value| xmp_coordinate(&value))
+                .zip(
+                    xmp_property(xmp, "exif:GPSLongitude").and_then(|value| xmp_coordinate(&value)),
+                );
+        }
+        if self.date_taken.is_none() {
+            self.date_taken = [
+                "exif:DateTimeOriginal",
+                "photoshop:DateCreated",
+                "xmp:CreateDate",
+            ]
+            .into_iter()
+            .find_map(|name| xmp_date(&xmp_property(xmp, name)?))
+            .and_then(local_time);
+        }
+    }
+}
+
+/// Returns true for image formats that may contain EXIF or XMP metadata, including camera RAW
+/// formats based on TIFF
+pub fn is_photo(mime: &Mime) -> bool {
+    mime.type_() == mime::IMAGE
+        && matches!(
+            mime.subtype().as_str(),
+            "jpeg"
+                | "tiff"
+                | "heif"
+                | "heic"
+                | "avif"
+                | "webp"
+                | "png"
+                | "dng"
+                | "x-adobe-dng"
+                | "x-canon-cr2"
+                | "x-nikon-nef"
+                | "x-nikon-nrw"
+                | "x-sony-arw"
+                | "x-sony-sr2"
+                | "x-pentax-pef"
+                | "x-olympus-orf"
+                | "x-panasonic-rw2"
+                | "x-panasonic-raw2"
+                | "x-samsung-srw"
+        )
+}
+
+/// Decode an image, rotating and flipping it as specified by its EXIF orientation
+pub fn decode_oriented<R: BufRead + Seek>(reader: ImageReader<R>) -> ImageResult<DynamicImage> {
+    let mut decoder = reader.into_decoder()?;
+    // Unreadable metadata should not prevent showing the image
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut image = DynamicImage::from_decoder(decoder)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Quarter turn of a photo
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Rotation {
+    Clockwise,
+    CounterClockwise,
+}
+
+impl Rotation {
+    pub const fn inverse(self) -> Self {
+        match self {
+            Self::Clockwise => Self::CounterClockwise,
+            Self::CounterClockwise => Self::Clockwise,
+        }
+    }
+}
+
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+const ORIENTATION_TAG: u16 = 0x0112;
+/// TIFF field type of a 16-bit unsigned integer
+const SHORT_TYPE: u16 = 3;
+
+/// Rotate a JPEG without recompressing it, by changing its EXIF orientation
+pub fn rotate_jpeg(path: &Path, rotation: Rotation) -> io::Result<()> {
+    let original = fs::read(path)?;
+    let mut data = original.clone();
+    rotate_orientation(&mut data, rotation)?;
+
+    if data.len() == original.len() {
+        // Only the orientation value changed, it is written in place so that hard links,
+        // ownership, extended attributes and ACLs are kept
+        let changed = |(a, b): (&u8, &u8)| a != b;
+        let Some(start) = original.iter().zip(&data).position(changed) else {
+            return Ok(());
+        };
+        let end = data.len()
+            - original
+                .iter()
+                .rev()
+                .zip(data.iter().rev())
+                .position(changed)
+                .unwrap_or(0);
+        let file = File::options().write(true).open(path)?;
+        return file.write_all_at(&data[start..end], start as u64);
+    }
+
+    // EXIF data was added, which moves the image data. The file is replaced in one step so
+    // that a failed write cannot damage the photo.
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(parent)?;
+    file.write_all(&data)?;
+    let mut warnings = Vec::new();
+    crate::operation::preserve::copy_metadata(path, file.path(), &mut warnings);
+    for warning in warnings {
+        log::warn!("{warning}");
+    }
+    // The timestamps were copied too, but the photo did change
+    file.as_file().set_modified(SystemTime::now())?;
+    file.persist(path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Update the orientation in JPEG data, adding EXIF data if there is none
+fn rotate_orientation(data: &mut Vec<u8>, rotation: Rotation) -> io::Result<()> {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return Err(invalid_data("not a JPEG file"));
+    }
+
+    // New EXIF data goes directly after the start of image, or after a JFIF segment which has to
+    // come first
+    let mut insert_at = 2;
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return Err(invalid_data("invalid JPEG marker"));
+        }
+        let marker = data[pos + 1];
+        if marker == 0xFF {
+            // Fill byte
+            pos += 1;
+            continue;
+        }
+        // Metadata always comes before the start of scan
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        let len = usize::from(u16::from_be_bytes([data[pos + 2], data[pos + 3]]));
+        let end = pos + 2 + len;
+        if len < 2 || end > data.len() {
+            return Err(invalid_data("truncated JPEG segment"));
+        }
+        if marker == 0xE1 && data[pos + 4..end].starts_with(EXIF_HEADER) {
+            return rotate_exif(data, pos, rotation);
+        }
+        if marker == 0xE0 && pos == 2 {
+            insert_at = end;
+        }
+        pos = end;
+    }
+
+    let tiff = new_tiff(rotated(1, rotation));
+    let len = 2 + EXIF_HEADER.len() + tiff.len();
+    let mut segment = vec![0xFF, 0xE1];
+    segment.extend_from_slice(&(len as u16).to_be_bytes());
+    segment.extend_from_slice(EXIF_HEADER);
+    segment.extend_from_slice(&tiff);
+    data.splice(insert_at..insert_at, segment);
+    Ok(())
+}
+
+/// Update the orientation in the EXIF segment starting at `segment`
+fn rotate_exif(data: &mut Vec<u8>, segment: usize, rotation: Rotation) -> io::Result<()> {
+    let len = usize::from(u16::from_be_bytes([data[segment + 2], data[segment + 3]]));
+    let tiff_start = segment + 4 + EXIF_HEADER.len();
+    let tiff_end = segment + 2 + len;
+    let tiff = Tiff::new(&data[tiff_start..tiff_end])?;
+
+    let ifd = tiff.u32(4)? as usize;
+    let count = usize::from(tiff.u16(ifd)?);
+    let mut entries = Vec::with_capacity(count + 1);
+    for i in 0..count {
+        let entry = ifd + 2 + i * 12;
+        if tiff.u16(entry)? == ORIENTATION_TAG {
+            // Orientation is a single short, stored in the entry itself
+            if tiff.u16(entry + 2)? != SHORT_TYPE || tiff.u32(entry + 4)? != 1 {
+                return Err(invalid_data("unsupported EXIF orientation"));
+            }
+            let orientation = rotated(tiff.u16(entry + 8)?, rotation);
+            let bytes = tiff.u16_bytes(orientation);
+            data[tiff_start + entry + 8..][..2].copy_from_slice(&bytes);
+            return Ok(());
+        }
+        entries.push(tiff.bytes(entry, 12)?.to_vec());
+    }
+    let next_ifd = tiff.bytes(ifd + 2 + count * 12, 4)?.to_vec();
+
+    // Write a copy of the first IFD with an orientation entry to the end of the segment. Offsets
+    // are relative to the TIFF header, so none of the existing data has to move.
+    let mut entry = Vec::with_capacity(12);
+    entry.extend_from_slice(&tiff.u16_bytes(ORIENTATION_TAG));
+    entry.extend_from_slice(&tiff.u16_bytes(SHORT_TYPE));
+    entry.extend_from_slice(&tiff.u32_bytes(1));
+    entry.extend_from_slice(&tiff.u16_bytes(rotated(1, rotation)));
+    entry.extend_from_slice(&[0, 0]);
+    // Entries are sorted by tag
+    let index = entries
+        .iter()
+        .position(|entry| tiff.u16_of(&entry[..2]) > ORIENTATION_TAG)
+        .unwrap_or(entries.len());
+    entries.insert(index, entry);
+
+    // IFDs start on a word boundary
+    let padding = tiff.len() % 2;
+    let new_ifd = tiff.len() + padding;
+    let mut appended = vec![0; padding];
+    appended.extend_from_slice(&tiff.u16_bytes(entries.len() as u16));
+    for entry in &entries {
+        appended.extend_from_slice(entry);
+    }
+    appended.extend_from_slice(&next_ifd);
+
+    let new_len =
+        u16::try_from(len + appended.len()).map_err(|_| invalid_data("EXIF data is too large"))?;
+    let new_ifd_bytes = tiff.u32_bytes(new_ifd as u32);
+    data[tiff_start + 4..][..4].copy_from_slice(&new_ifd_bytes);
+    data[segment + 2..][..2].copy_from_slice(&new_len.to_be_bytes());
+    data.splice(tiff_end..tiff_end, appended);
+    Ok(())
+}
+
+/// Minimal big endian TIFF structure holding only an orientation
+fn new_tiff(orientation: u16) -> Vec<u8> {
+    let mut tiff = Vec::with_capacity(26);
+    tiff.extend_from_slice(b"MM");
+    tiff.extend_from_slice(&42u16.to_be_bytes());
+    // First IFD follows the header
+    tiff.extend_from_slice(&8u32.to_be_bytes());
+    tiff.extend_from_slice(&1u16.to_be_bytes());
+    tiff.extend_from_slice(&ORIENTATION_TAG.to_be_bytes());
+    tiff.extend_from_slice(&SHORT_TYPE.to_be_bytes());
+    tiff.extend_from_slice(&1u32.to_be_bytes());
+    tiff.extend_from_slice(&orientation.to_be_bytes());
+    tiff.extend_from_slice(&[0, 0]);
+    // No next IFD
+    tiff.extend_from_slice(&0u32.to_be_bytes());
+    tiff
+}
+
+/// EXIF orientation after turning the displayed image a quarter turn
+fn rotated(orientation: u16, rotation: Rotation) -> u16 {
+    // Orientations in clockwise order, without and with mirroring
+    const CYCLES: [[u16; 4]; 2] = [[1, 6, 3, 8], [2, 7, 4, 5]];
+    let step = match rotation {
+        Rotation::Clockwise => 1,
+        Rotation::CounterClockwise => 3,
+    };
+    CYCLES
+        .iter()
+        .find_map(|cycle| {
+            let index = cycle.iter().position(|&value| value == orientation)?;
+            Some(cycle[(index + step) % 4])
+        })
+        // Invalid orientations are displayed upright
+        .unwrap_or_else(|| rotated(1, rotation))
+}
+
+/// TIFF data in either byte order
+struct Tiff<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a [u8]) -> io::Result<Self> {
+        let big_endian = match data.get(..2) {
+            Some(b"MM") => true,
+            Some(b"II") => false,
+            _ => return Err(invalid_data("invalid TIFF header")),
+        };
+        Ok(Self { data, big_endian })
+    }
+
+    const fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> io::Result<&'a [u8]> {
+        self.data
+            .get(offset..offset + len)
+            .ok_or_else(|| invalid_data("truncated EXIF data"))
+    }
+
+    fn u16(&self, offset: usize) -> io::Result<u16> {
+        Ok(self.u16_of(self.bytes(offset, 2)?))
+    }
+
+    fn u32(&self, offset: usize) -> io::Result<u32> {
+        let bytes = self.bytes(offset, 4)?.try_into().unwrap();
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    fn u16_of(&self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        }
+    }
+
+    const fn u16_bytes(&self, value: u16) -> [u8; 2] {
+        if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    }
+
+    const fn u32_bytes(&self, value: u32) -> [u8; 4] {
+        if self.big_endian {
+            value.to_be_bytes()
+        } else {
+            value.to_le_bytes()
+        }
+    }
+}
+
+fn read_exif(path: &Path) -> Result<Exif, exif::Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    exif::Reader::new().read_from_container(&mut reader)
+}
+
+fn read_xmp(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    static XMP: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?s)<x:xmpmeta.*?</x:xmpmeta>").unwrap());
+    let mut data = Vec::new();
+    File::open(path)?
+        .take(MAX_XMP_SEARCH)
+        .read_to_end(&mut data)?;
+    Ok(XMP.find(&data).map(|found| found.as_bytes().to_vec()))
+}
+
+/// Read a simple XMP property, written either as an attribute or as an element
+fn xmp_property(xmp: &[u8], name: &str) -> Option<String> {
+    let name = regex::escape(name);
+    let regex = Regex::new(&format!(
+        r#"(?s){name}="([^"]*)"|<{name}>\s*(?:<rdf:\w+>\s*<rdf:li[^>]*>)?([^<]*)<"#
+    ))
+    .ok()?;
+    let captures = regex.captures(xmp)?;
+    let value = captures.get(1).or_else(|| captures.get(2))?;
+    let value = String::from_utf8_lossy(value.as_bytes()).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Parse an XMP GPS coordinate like `48,51.5N` or `48,51,30N`
+fn xmp_coordinate(value: &str) -> Option<f64> {
+    let direction = value.chars().last()?;
+    let degrees = value[..value.len() - direction.len_utf8()]
+        .split(',')
+        .zip([1.0, 60.0, 3600.0])
+        .map(|(part, divisor)| part.trim().parse::<f64>().map(|part| part / divisor))
+        .sum::<Result<f64, _>>()
+        .ok()?;
+    match direction.to_ascii_uppercase() {
+        'N' | 'E' => Some(degrees),
+        'S' | 'W' => Some(-degrees),
+        _ => None,
+    }
+}
+
+/// Parse an XMP date, ignoring any time zone as EXIF dates are also local
+fn xmp_date(value: &str) -> Option<NaiveDateTime> {
+    let value = value.get(..19).unwrap_or(value);
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(&format!("{value}:00"), "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })
+}
+
+fn local_time(date_time: NaiveDateTime) -> Option<SystemTime> {
+    Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+fn ascii(field: &Field) -> Option<String> {
+    match &field.value {
+        Value::Ascii(values) => {
+            let value = String::from_utf8_lossy(values.first()?);
+            let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+            (!value.is_empty()).then(|| value.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn rational(field: &Field) -> Option<f64> {
+    match &field.value {
+        Value::Rational(values) => values
+            .first()
+            .filter(|value| value.denom != 0)
+            .map(|value| value.to_f64()),
+        _ => None,
+    }
+}
+
+fn camera_name(make: Option<String>, model: Option<String>) -> Option<String> {
+    match (make, model) {
+        // Many cameras already include the make in the model
+        (Some(make), Some(model)) if model.to_lowercase().starts_with(&make.to_lowercase()) => {
+            Some(model)
+        }
+        (Some(make), Some(model)) => Some(format!("{make} {model}")),
+        (make, model) => model.or(make),
+    }
+}
+
+fn format_exposure_time(seconds: f64) -> String {
+    if seconds > 0.0 && seconds < 1.0 {
+        format!("1/{} s", (1.0 / seconds).round())
+    } else {
+        format!("{} s", trim_float(seconds, 1))
+    }
+}
+
+/// Format a number without trailing zeros
+fn trim_float(value: f64, decimals: usize) -> String {
+    let formatted = format!("{value:.decimals$}");
+    if formatted.contains('.') {
+        formatted
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    } else {
+        formatted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XMP: &[u8] = br#"<x:xmpmeta xmlns:x="adobe:ns:meta/"><rdf:RDF>
+<rdf:Description tiff:Make="Canon" tiff:Model="Canon EOS R6" aux:Lens="RF24-105mm F4 L IS USM"
+ exif:GPSLatitude="48,51.5N" exif:GPSLongitude="2,17.6W">
+<exif:DateTimeOriginal>2024-05-01T12:34:56+02:00</exif:DateTimeOriginal>
+</rdf:Description></rdf:RDF></x:xmpmeta>"#;
+
+    #[test]
+    fn xmp_properties() {
+        assert_eq!(xmp_property(XMP, "tiff:Make").as_deref(), Some("Canon"));
+        assert_eq!(
+            xmp_property(XMP, "exif:DateTimeOriginal").as_deref(),
+            Some("2024-05-01T12:34:56+02:00")
+        );
+        assert_eq!(xmp_property(XMP, "tiff:Orientation"), None);
+
+        let mut info = PhotoInfo::default();
+        info.merge_xmp(XMP);
+        assert_eq!(info.camera.as_deref(), Some("Canon EOS R6"));
+        assert_eq!(info.lens.as_deref(), Some("RF24-105mm F4 L IS USM"));
+        let (lat, lon) = info.gps.unwrap();
+        assert!((lat - 48.858_333).abs() < 1e-5);
+        assert!((lon + 2.293_333).abs() < 1e-5);
+        assert_eq!(
+            info.date_taken,
+            local_time(xmp_date("2024-05-01T12:34:56").unwrap())
+        );
+    }
+
+    #[test]
+    fn xmp_dates_and_coordinates() {
+        let date = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        assert_eq!(xmp_date("2024-05-01T12:34"), date.and_hms_opt(12, 34, 0));
+        assert_eq!(xmp_date("2024-05-01"), date.and_hms_opt(0, 0, 0));
+        assert_eq!(xmp_coordinate("10,30,0S"), Some(-10.5));
+        assert_eq!(xmp_coordinate("10,30X"), None);
+    }
+
+    fn jpeg() -> Vec<u8> {
+        let mut data = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new(&mut data)
+            .encode(&[128; 8 * 4 * 3], 8, 4, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        data
+    }
+
+    fn orientation(data: &[u8]) -> Option<u32> {
+        let exif = exif::Reader::new()
+            .read_from_container(&mut io::Cursor::new(data))
+            .ok()?;
+        exif.get_field(Tag::Orientation, In::PRIMARY)?
+            .value
+            .get_uint(0)
+    }
+
+    #[test]
+    fn orientation_cycles() {
+        assert_eq!(rotated(1, Rotation::Clockwise), 6);
+        assert_eq!(rotated(8, Rotation::Clockwise), 1);
+        assert_eq!(rotated(1, Rotation::CounterClockwise), 8);
+        assert_eq!(rotated(2, Rotation::Clockwise), 7);
+        assert_eq!(rotated(5, Rotation::CounterClockwise), 4);
+        assert_eq!(rotated(0, Rotation::Clockwise), 6);
+    }
+
+    #[test]
+    fn jpeg_rotation() {
+        let mut data = jpeg();
+        assert_eq!(orientation(&data), None);
+        rotate_orientation(&mut data, Rotation::Clockwise).unwrap();
+        assert_eq!(orientation(&data), Some(6));
+        rotate_orientation(&mut data, Rotation::Clockwise).unwrap();
+        assert_eq!(orientation(&data), Some(3));
+        rotate_orientation(&mut data, Rotation::CounterClockwise).unwrap();
+        assert_eq!(orientation(&data), Some(6));
+
+        let reader = ImageReader::new(io::Cursor::new(&data))
+            .with_guessed_format()
+            .unwrap();
+        let image = decode_oriented(reader).unwrap();
+        assert_eq!((image.width(), image.height()), (4, 8));
+    }
+
+    #[test]
+    fn jpeg_rotation_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("photo.jpg");
+        fs::write(&path, jpeg()).unwrap();
+        // Adding EXIF data replaces the file
+        rotate_jpeg(&path, Rotation::Clockwise).unwrap();
+
+        // Changing the orientation again keeps hard links
+        let link = dir.path().join("link.jpg");
+        fs::hard_link(&path, &link).unwrap();
+        rotate_jpeg(&path, Rotation::Clockwise).unwrap();
+        assert_eq!(orientation(&fs::read(&link).unwrap()), Some(3));
+    }
+
+    #[test]
+    fn exif_without_orientation() {
+        // Little endian EXIF data with only a camera make
+        let mut tiff = b"II*\0\x08\0\0\0\x01\0".to_vec();
+        tiff.extend_from_slice(&[0x0F, 0x01, 2, 0, 4, 0, 0, 0, b'A', b'b', b'c', 0]);
+        tiff.extend_from_slice(&[0; 4]);
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&((2 + EXIF_HEADER.len() + tiff.len()) as u16).to_be_bytes());
+        segment.extend_from_slice(EXIF_HEADER);
+        segment.extend_from_slice(&tiff);
+        let data = jpeg();
+        let mut data = [&data[..2], &segment, &data[2..]].concat();
+
+        rotate_orientation(&mut data, Rotation::CounterClockwise).unwrap();
+        assert_eq!(orientation(&data), Some(8));
+        let exif = exif::Reader::new()
+            .read_from_container(&mut io::Cursor::new(&data))
+            .unwrap();
+        let make = exif.get_field(Tag::Make, In::PRIMARY).unwrap();
+        assert_eq!(ascii(make).as_deref(), Some("Abc"));
+    }
+
+    #[test]
+    fn exposure_formatting() {
+        assert_eq!(format_exposure_time(0.004), "1/250 s");
+        assert_eq!(format_exposure_time(2.0), "2 s");
+        assert_eq!(trim_float(2.8, 1), "2.8");
+        assert_eq!(trim_float(35.0, 1), "35");
+        assert_eq!(
+            camera_name(Some("NIKON CORPORATION".into()), Some("NIKON D750".into())).as_deref(),
+            Some("NIKON CORPORATION NIKON D750")
+        );
+        assert_eq!(
+            camera_name(Some("Canon".into()), Some("Canon EOS R6".into())).as_deref(),
+            Some("Canon EOS R6")
+        );
+    }
+}
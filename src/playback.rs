@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use gstreamer::{self as gst, prelude::*};
+use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
+use std::{
+    fmt,
+    path::Path,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+
+/// Video frame in RGBA, without padding between rows
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Message from the pipeline that needs handling
+#[derive(Debug)]
+pub enum Event {
+    EndOfStream,
+    Error(String),
+}
+
+/// Initialize GStreamer once, keeping hardware decoders from being picked so that playback
+/// decodes in software and works without a GPU
+fn init() -> Result<(), String> {
+    static INIT: OnceLock<Result<(), String>> = OnceLock::new();
+    INIT.get_or_init(|| {
+        gst::init().map_err(|err| err.to_string())?;
+        for factory in gst::ElementFactory::factories_with_type(
+            gst::ElementFactoryType::DECODER | gst::ElementFactoryType::HARDWARE,
+            gst::Rank::NONE,
+        ) {
+            factory.set_rank(gst::Rank::NONE);
+        }
+        Ok(())
+    })
+    .clone()
+}
+
+/// Plays an audio or video file, keeping the latest video frame for display
+pub struct Player {
+    playbin: gst::Element,
+    frame: Arc<Mutex<Option<Frame>>>,
+}
+
+impl Player {
+    /// Create a paused player for a local file
+    pub fn new(path: &Path, muted: bool) -> Result<Self, String> {
+        init()?;
+        let uri = url::Url::from_file_path(path)
+            .map_err(|()| format!("failed to convert {} to URI", path.display()))?;
+
+        let frame = Arc::new(Mutex::new(None));
+        let sink = gst_app::AppSink::builder()
+            .caps(
+                &gst_video::VideoCapsBuilder::new()
+                    .format(gst_video::VideoFormat::Rgba)
+                    .build(),
+            )
+            // Frames that are not displayed in time are dropped rather than queued
+            .max_buffers(1)
+            .drop(true)
+            .build();
+        let preroll_frame = frame.clone();
+        let sample_frame = frame.clone();
+        sink.set_callbacks(
+            gst_app::AppSinkCallbacks::builder()
+                .new_preroll(move |sink| store_sample(sink.pull_preroll(), &preroll_frame))
+                .new_sample(move |sink| store_sample(sink.pull_sample(), &sample_frame))
+                .build(),
+        );
+
+        let playbin = gst::ElementFactory::make("playbin")
+            .property("uri", uri.as_str())
+            .property("video-sink", &sink)
+            .property("mute", muted)
+            .build()
+            .map_err(|err| err.to_string())?;
+        playbin
+            .set_state(gst::State::Paused)
+            .map_err(|err| err.to_string())?;
+        Ok(Self { playbin, frame })
+    }
+
+    pub fn set_playing(&self, playing: bool) {
+        let state = if playing {
+            gst::State::Playing
+        } else {
+            gst::State::Paused
+        };
+        if let Err(err) = self.playbin.set_state(state) {
+            log::warn!("failed to set playback state to {state:?}: {err}");
+        }
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.playbin.set_property("mute", muted);
+    }
+
+    pub fn seek(&self, position: Duration) {
+        let position = gst::ClockTime::from_nseconds(position.as_nanos() as u64);
+        if let Err(err) = self
+            .playbin
+            .seek_simple(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT, position)
+        {
+            log::warn!("failed to seek to {position}: {err}");
+        }
+    }
+
+    pub fn position(&self) -> Option<Duration> {
+        self.playbin
+            .query_position::<gst::ClockTime>()
+            .map(|time| Duration::from_nanos(time.nseconds()))
+    }
+
+    pub fn duration(&self) -> Option<Duration> {
+        self.playbin
+            .query_duration::<gst::ClockTime>()
+            .map(|time| Duration::from_nanos(time.nseconds()))
+    }
+
+    /// Latest video frame, if there is one that was not taken yet
+    pub fn take_frame(&self) -> Option<Frame> {
+        self.frame.lock().unwrap().take()
+    }
+
+    /// End of stream or error since the last call, other messages are discarded
+    pub fn poll(&self) -> Option<Event> {
+        let bus = self.playbin.bus()?;
+        let message = bus.pop_filtered(&[gst::MessageType::Eos, gst::MessageType::Error])?;
+        match message.view() {
+            gst::MessageView::Eos(_) => Some(Event::EndOfStream),
+            gst::MessageView::Error(err) => Some(Event::Error(err.error().to_string())),
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        if let Err(err) = self.playbin.set_state(gst::State::Null) {
+            log::warn!("failed to stop playback: {err}");
+        }
+    }
+}
+
+impl fmt::Debug for Player {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Player")
+            .field("playbin", &self.playbin)
+            .finish_non_exhaustive()
+    }
+}
+
+fn store_sample(
+    sample: Result<gst::Sample, gst::glib::BoolError>,
+    frame: &Mutex<Option<Frame>>,
+) -> Result<gst::FlowSuccess, gst::FlowError> {
+    let sample = sample.map_err(|_| gst::FlowError::Eos)?;
+    let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+    let caps = sample.caps().ok_or(gst::FlowError::Error)?;
+    let info = gst_video::VideoInfo::from_caps(caps).map_err(|_| gst::FlowError::Error)?;
+    let video_frame = gst_video::VideoFrameRef::from_buffer_ref_readable(buffer, &info)
+        .map_err(|_| gst::FlowError::Error)?;
+    let data = video_frame
+        .plane_data(0)
+        .map_err(|_| gst::FlowError::Error)?;
+    let width = video_frame.width();
+    let height = video_frame.height();
+    let stride =
+        usize::try_from(video_frame.plane_stride()[0]).map_err(|_| gst::FlowError::Error)?;
+    let pixels = packed_rows(data, stride, width as usize * 4, height as usize)
+        .ok_or(gst::FlowError::Error)?;
+    *frame.lock().unwrap() = Some(Frame {
+        width,
+        height,
+        pixels,
+    });
+    Ok(gst::FlowSuccess::Ok)
+}
+
+/// Copy rows out of a plane where each row may be followed by padding
+fn packed_rows(data: &[u8], stride: usize, row_len: usize, rows: usize) -> Option<Vec<u8>> {
+    if stride < row_len {
+        return None;
+    }
+    let mut pixels = Vec::with_capacity(row_len * rows);
+    for row in 0..rows {
+        let start = row * stride;
+        pixels.extend_from_slice(data.get(start..start + row_len)?);
+    }
+    Some(pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padded_rows() {
+        let data = [1, 2, 0, 3, 4, 0, 5, 6];
+        assert_eq!(packed_rows(&data, 3, 2, 3), Some(vec![1, 2, 3, 4, 5, 6]));
+        assert_eq!(packed_rows(&data, 2, 2, 4), Some(data.to_vec()));
+        assert_eq!(packed_rows(&data, 3, 2, 4), None);
+        assert_eq!(packed_rows(&data, 1, 2, 1), None);
+    }
+}
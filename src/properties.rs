@@ -0,0 +1,1021 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::{
+    Element, cosmic_theme,
+    iced::{Alignment, Length},
+    theme,
+    widget::{self, icon},
+};
+use std::{
+    borrow::Cow,
+    ffi::CString,
+    fs, io,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+    path::{Path, PathBuf},
+};
+use uzers::{get_group_by_gid, get_group_by_name, get_user_by_name, get_user_by_uid};
+
+use crate::{
+    fl,
+    tab::{
+        MODE_NAMES, MODE_SHIFT_GROUP, MODE_SHIFT_OTHER, MODE_SHIFT_USER, get_mode_part,
+        set_mode_part,
+    },
+};
+
+const ACL_XATTR_ACCESS: &str = "system.posix_acl_access";
+const ACL_EA_VERSION: u32 = 0x0002;
+const ACL_UNDEFINED_ID: u32 = u32::MAX;
+
+const MODE_SETUID: u32 = 0o4000;
+const MODE_SETGID: u32 = 0o2000;
+const MODE_STICKY: u32 = 0o1000;
+
+/// Tag of a POSIX ACL entry, ordered the same way the kernel expects entries
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AclTag {
+    UserObj,
+    User(u32),
+    GroupObj,
+    Group(u32),
+    Mask,
+    Other,
+}
+
+impl AclTag {
+    const fn from_raw(tag: u16, id: u32) -> Option<Self> {
+        match tag {
+            0x01 => Some(Self::UserObj),
+            0x02 => Some(Self::User(id)),
+            0x04 => Some(Self::GroupObj),
+            0x08 => Some(Self::Group(id)),
+            0x10 => Some(Self::Mask),
+            0x20 => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    const fn to_raw(self) -> (u16, u32) {
+        match self {
+            Self::UserObj => (0x01, ACL_UNDEFINED_ID),
+            Self::User(id) => (0x02, id),
+            Self::GroupObj => (0x04, ACL_UNDEFINED_ID),
+            Self::Group(id) => (0x08, id),
+            Self::Mask => (0x10, ACL_UNDEFINED_ID),
+            Self::Other => (0x20, ACL_UNDEFINED_ID),
+        }
+    }
+
+    /// Named entries are the ones not represented by the mode bits
+    pub const fn is_named(self) -> bool {
+        matches!(self, Self::User(_) | Self::Group(_))
+    }
+
+    fn name(self) -> String {
+        match self {
+            Self::User(uid) => get_user_by_uid(uid).map_or_else(
+                || uid.to_string(),
+                |user| user.name().to_string_lossy().into_owned(),
+            ),
+            Self::Group(gid) => get_group_by_gid(gid).map_or_else(
+                || gid.to_string(),
+                |group| group.name().to_string_lossy().into_owned(),
+            ),
+            _ => String::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AclEntry {
+    pub tag: AclTag,
+    /// Permission bits, in the same format as one class of the mode
+    pub perm: u32,
+}
+
+impl AclEntry {
+    /// Parse a `user:name` or `group:name` qualifier, as accepted by setfacl
+    pub fn parse_qualifier(input: &str) -> Option<AclTag> {
+        let (kind, name) = input.trim().split_once(':')?;
+        match kind {
+            "u" | "user" => get_user_by_name(name).map(|user| AclTag::User(user.uid())),
+            "g" | "group" => get_group_by_name(name).map(|group| AclTag::Group(group.gid())),
+            _ => None,
+        }
+    }
+}
+
+/// Decode the value of a `system.posix_acl_*` extended attribute
+pub fn acl_from_bytes(bytes: &[u8]) -> Option<Vec<AclEntry>> {
+    let (header, mut rest) = bytes.split_at_checked(4)?;
+    if u32::from_le_bytes(header.try_into().ok()?) != ACL_EA_VERSION {
+        return None;
+    }
+    let mut entries = Vec::with_capacity(rest.len() / 8);
+    while !rest.is_empty() {
+        let (entry, next) = rest.split_at_checked(8)?;
+        let tag = u16::from_le_bytes([entry[0], entry[1]]);
+        let perm = u16::from_le_bytes([entry[2], entry[3]]);
+        let id = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+        entries.push(AclEntry {
+            tag: AclTag::from_raw(tag, id)?,
+            perm: u32::from(perm) & 0o7,
+        });
+        rest = next;
+    }
+    Some(entries)
+}
+
+/// Encode a `system.posix_acl_*` extended attribute value, sorting entries as required
+pub fn acl_to_bytes(entries: &[AclEntry]) -> Vec<u8> {
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|entry| entry.tag);
+    let mut bytes = Vec::with_capacity(4 + entries.len() * 8);
+    bytes.extend_from_slice(&ACL_EA_VERSION.to_le_bytes());
+    for entry in entries {
+        let (tag, id) = entry.tag.to_raw();
+        bytes.extend_from_slice(&tag.to_le_bytes());
+        bytes.extend_from_slice(&(entry.perm as u16 & 0o7).to_le_bytes());
+        bytes.extend_from_slice(&id.to_le_bytes());
+    }
+    bytes
+}
+
+/// Read the access ACL of a path, returns `None` if it only has the base entries
+pub fn get_acl(path: &Path) -> io::Result<Option<Vec<AclEntry>>> {
+    let Some(bytes) = xattr_get(path, ACL_XATTR_ACCESS)? else {
+        return Ok(None);
+    };
+    acl_from_bytes(&bytes)
+        .map(Some)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid POSIX ACL"))
+}
+
+/// Write the access ACL of a path, using `mode` for the base entries.
+/// Having no named entries removes the extended ACL.
+pub fn set_acl(path: &Path, mode: u32, named: &[AclEntry]) -> io::Result<()> {
+    if named.is_empty() {
+        return xattr_remove(path, ACL_XATTR_ACCESS);
+    }
+
+    let group_perm = get_mode_part(mode, MODE_SHIFT_GROUP);
+    let mut entries = Vec::with_capacity(named.len() + 4);
+    entries.push(AclEntry {
+        tag: AclTag::UserObj,
+        perm: get_mode_part(mode, MODE_SHIFT_USER),
+    });
+    entries.push(AclEntry {
+        tag: AclTag::GroupObj,
+        perm: group_perm,
+    });
+    // The mask must cover every entry in the group class
+    let mut mask = group_perm;
+    for entry in named.iter().filter(|entry| entry.tag.is_named()) {
+        mask |= entry.perm;
+        entries.push(*entry);
+    }
+    entries.push(AclEntry {
+        tag: AclTag::Mask,
+        perm: mask,
+    });
+    entries.push(AclEntry {
+        tag: AclTag::Other,
+        perm: get_mode_part(mode, MODE_SHIFT_OTHER),
+    });
+    xattr_set(path, ACL_XATTR_ACCESS, &acl_to_bytes(&entries))
+}
+
+/// Mode of a path with the group class taken from the ACL, instead of the ACL mask
+pub fn effective_mode(path: &Path, metadata: &fs::Metadata) -> u32 {
+    let mode = metadata.mode() & 0o7777;
+    match get_acl(path) {
+        Ok(Some(entries)) => entries
+            .iter()
+            .find(|entry| entry.tag == AclTag::GroupObj)
+            .map_or(mode, |entry| {
+                set_mode_part(mode, MODE_SHIFT_GROUP, entry.perm)
+            }),
+        _ => mode,
+    }
+}
+
+fn path_cstring(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+fn name_cstring(name: &str) -> io::Result<CString> {
+    CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+/// List the names of the extended attributes of a path
+pub fn xattr_list(path: &Path) -> io::Result<Vec<String>> {
+    let c_path = path_cstring(path)?;
+    loop {
+        let size = unsafe { libc::listxattr(c_path.as_ptr(), std::ptr::null_mut(), 0) };
+        if size < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mut buf = vec![0u8; size as usize];
+        let size = unsafe { libc::listxattr(c_path.as_ptr(), buf.as_mut_ptr().cast(), buf.len()) };
+        if size < 0 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::ERANGE) {
+                // Attributes were added in between calls, try again
+                continue;
+            }
+            return Err(err);
+        }
+        buf.truncate(size as usize);
+        return Ok(buf
+            .split(|&b| b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect());
+    }
+}
+
+/// Read an extended attribute, returns `None` if it does not exist
+pub fn xattr_get(path: &Path, name: &str) -> io::Result<Option<Vec<u8>>> {
+    let c_path = path_cstring(path)?;
+    let c_name = name_cstring(name)?;
+    loop {
+        let size =
+            unsafe { libc::getxattr(c_path.as_ptr(), c_name.as_ptr(), std::ptr::null_mut(), 0) };
+        if size < 0 {
+            let err = io::Error::last_os_error();
+            return match err.raw_os_error() {
+                Some(libc::ENODATA | libc::ENOTSUP) => Ok(None),
+                _ => Err(err),
+            };
+        }
+        let mut buf = vec![0u8; size as usize];
+        let size = unsafe {
+            libc::getxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                buf.as_mut_ptr().cast(),
+                buf.len(),
+            )
+        };
+        if size < 0 {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::ERANGE) => continue,
+                Some(libc::ENODATA) => return Ok(None),
+                _ => return Err(err),
+            }
+        }
+        buf.truncate(size as usize);
+        return Ok(Some(buf));
+    }
+}
+
+/// Create or replace an extended attribute
+pub fn xattr_set(path: &Path, name: &str, value: &[u8]) -> io::Result<()> {
+    let c_path = path_cstring(path)?;
+    let c_name = name_cstring(name)?;
+    let res = unsafe {
+        libc::setxattr(
+            c_path.as_ptr(),
+            c_name.as_ptr(),
+            value.as_ptr().cast(),
+            value.len(),
+            0,
+        )
+    };
+    if res < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Remove an extended attribute, succeeding if it does not exist
+pub fn xattr_remove(path: &Path, name: &str) -> io::Result<()> {
+    let c_path = path_cstring(path)?;
+    let c_name = name_cstring(name)?;
+    let res = unsafe { libc::removexattr(c_path.as_ptr(), c_name.as_ptr()) };
+    if res < 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ENODATA) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Changes applied by [`crate::operation::Operation::SetPermissions`].
+/// Only what was changed is recorded, so each path keeps everything else, like `chmod -R g+w`.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PermissionsChange {
+    /// Mode bits to set, including setuid, setgid and sticky
+    pub mode_set: u32,
+    /// Mode bits to clear
+    pub mode_clear: u32,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    /// Named ACL entries that were added or edited
+    pub acl_set: Vec<AclEntry>,
+    /// Named ACL entries that were removed
+    pub acl_remove: Vec<AclTag>,
+    /// Extended attributes to set, or remove if the value is `None`.
+    /// These only apply to the selected paths, never to their contents.
+    pub xattrs: Vec<(String, Option<Vec<u8>>)>,
+    /// Also apply to the contents of directories
+    pub recursive: bool,
+}
+
+impl PermissionsChange {
+    /// Set every mode bit to the ones of `mode`
+    pub const fn mode(mode: u32) -> Self {
+        Self {
+            mode_set: mode & 0o7777,
+            mode_clear: !mode & 0o7777,
+            uid: None,
+            gid: None,
+            acl_set: Vec::new(),
+            acl_remove: Vec::new(),
+            xattrs: Vec::new(),
+            recursive: false,
+        }
+    }
+
+    /// The resulting mode, if every mode bit is changed
+    pub const fn full_mode(&self) -> Option<u32> {
+        if self.mode_set | self.mode_clear == 0o7777 {
+            Some(self.mode_set)
+        } else {
+            None
+        }
+    }
+
+    pub const fn has_mode(&self) -> bool {
+        self.mode_set != 0 || self.mode_clear != 0
+    }
+
+    pub const fn has_acl(&self) -> bool {
+        !self.acl_set.is_empty() || !self.acl_remove.is_empty()
+    }
+
+    /// Apply the mode change to the mode of a path. Contents of selected directories only get
+    /// execute bits if they are directories or already executable, like chmod's `X`, and only
+    /// directories get setgid and sticky. Setuid is never set on them.
+    pub const fn apply_mode(&self, mode: u32, selected: bool, is_dir: bool) -> u32 {
+        let mut set = self.mode_set;
+        if !selected {
+            if !is_dir && mode & 0o111 == 0 {
+                set &= !0o111;
+            }
+            if !is_dir {
+                set &= !(MODE_SETGID | MODE_STICKY);
+            }
+            set &= !MODE_SETUID;
+        }
+        (mode & !self.mode_clear) | set
+    }
+
+    /// Apply the ACL change to the named entries of a path
+    pub fn apply_acl(&self, named: &mut Vec<AclEntry>) {
+        named.retain(|entry| {
+            !self.acl_remove.contains(&entry.tag)
+                && !self.acl_set.iter().any(|set| set.tag == entry.tag)
+        });
+        named.extend_from_slice(&self.acl_set);
+        named.sort_by_key(|entry| entry.tag);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.has_mode()
+            && self.uid.is_none()
+            && self.gid.is_none()
+            && !self.has_acl()
+            && self.xattrs.is_empty()
+    }
+
+    pub fn is_mode_only(&self) -> bool {
+        self.has_mode()
+            && self.uid.is_none()
+            && self.gid.is_none()
+            && !self.has_acl()
+            && self.xattrs.is_empty()
+            && !self.recursive
+    }
+}
+
+/// Read-only details of a single path
+#[derive(Clone, Debug)]
+pub struct PropertiesInfo {
+    pub inode: u64,
+    pub device: (u64, u64),
+    pub links: u64,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct PermissionsState {
+    mode: u32,
+    owner: String,
+    group: String,
+    acl: Vec<AclEntry>,
+    /// Values are kept as bytes, as they are not always text
+    xattrs: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    AclAdd,
+    AclInput(String),
+    AclPerm(usize, u32),
+    AclRemove(usize),
+    Group(String),
+    Mode(u32),
+    Owner(String),
+    Recursive(bool),
+    XattrAdd,
+    XattrInputName(String),
+    XattrInputValue(String),
+    XattrRemove(usize),
+    XattrValue(usize, String),
+}
+
+/// State of the properties dialog
+#[derive(Clone, Debug)]
+pub struct Properties {
+    pub paths: Box<[PathBuf]>,
+    info_opt: Option<PropertiesInfo>,
+    has_dir: bool,
+    original: PermissionsState,
+    state: PermissionsState,
+    recursive: bool,
+    acl_input: String,
+    xattr_input: (String, String),
+}
+
+impl Properties {
+    /// Load properties, the first path is used for the initial values
+    pub fn new(paths: Box<[PathBuf]>) -> io::Result<Self> {
+        let Some(path) = paths.first() else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no paths"));
+        };
+        let metadata = fs::metadata(path)?;
+
+        let info_opt = (paths.len() == 1).then(|| PropertiesInfo {
+            inode: metadata.ino(),
+            device: dev_major_minor(metadata.dev()),
+            links: metadata.nlink(),
+        });
+
+        let owner = get_user_by_uid(metadata.uid()).map_or_else(
+            || metadata.uid().to_string(),
+            |user| user.name().to_string_lossy().into_owned(),
+        );
+        let group = get_group_by_gid(metadata.gid()).map_or_else(
+            || metadata.gid().to_string(),
+            |group| group.name().to_string_lossy().into_owned(),
+        );
+
+        let acl = match get_acl(path) {
+            Ok(entries) => entries
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|entry| entry.tag.is_named())
+                .collect(),
+            Err(err) => {
+                log::warn!("failed to read ACL of {}: {}", path.display(), err);
+                Vec::new()
+            }
+        };
+
+        let mut xattrs = Vec::new();
+        match xattr_list(path) {
+            Ok(names) => {
+                for name in names {
+                    // ACLs are shown separately
+                    if name.starts_with("system.posix_acl_") {
+                        continue;
+                    }
+                    match xattr_get(path, &name) {
+                        Ok(Some(value)) => {
+                            xattrs.push((name, value));
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            log::warn!(
+                                "failed to read extended attribute {} of {}: {}",
+                                name,
+                                path.display(),
+                                err
+                            );
+                        }
+                    }
+                }
+            }
+            Err(err) => {
+                log::warn!(
+                    "failed to list extended attributes of {}: {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+
+        let state = PermissionsState {
+            mode: effective_mode(path, &metadata),
+            owner,
+            group,
+            acl,
+            xattrs,
+        };
+
+        Ok(Self {
+            has_dir: paths.iter().any(|path| path.is_dir()),
+            paths,
+            info_opt,
+            original: state.clone(),
+            state,
+            recursive: false,
+            acl_input: String::new(),
+            xattr_input: (String::new(), String::new()),
+        })
+    }
+
+    /// Compute the changes to apply, or a message describing why they are invalid
+    pub fn change(&self) -> Result<PermissionsChange, String> {
+        let mut change = PermissionsChange {
+            recursive: self.recursive,
+            ..Default::default()
+        };
+
+        // Only the bits that were toggled are changed on each path
+        change.mode_set = self.state.mode & !self.original.mode;
+        change.mode_clear = self.original.mode & !self.state.mode;
+
+        if self.state.owner != self.original.owner {
+            let owner = self.state.owner.trim();
+            let user = get_user_by_name(owner)
+                .ok_or_else(|| fl!("properties-unknown-user", name = owner))?;
+            change.uid = Some(user.uid());
+        }
+
+        if self.state.group != self.original.group {
+            let group = self.state.group.trim();
+            let group = get_group_by_name(group)
+                .ok_or_else(|| fl!("properties-unknown-group", name = group))?;
+            change.gid = Some(group.gid());
+        }
+
+        for entry in &self.original.acl {
+            if !self.state.acl.iter().any(|state| state.tag == entry.tag) {
+                change.acl_remove.push(entry.tag);
+            }
+        }
+        for entry in &self.state.acl {
+            if !self.original.acl.contains(entry) {
+                change.acl_set.push(*entry);
+            }
+        }
+
+        for (name, _) in &self.original.xattrs {
+            if !self.state.xattrs.iter().any(|(x, _)| x == name) {
+                change.xattrs.push((name.clone(), None));
+            }
+        }
+        // Only values that were added or edited are written
+        for (name, value) in &self.state.xattrs {
+            if !self
+                .original
+                .xattrs
+                .iter()
+                .any(|(x, original)| x == name && original == value)
+            {
+                change.xattrs.push((name.clone(), Some(value.clone())));
+            }
+        }
+
+        Ok(change)
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::AclAdd => {
+                if let Some(tag) = AclEntry::parse_qualifier(&self.acl_input) {
+                    if !self.state.acl.iter().any(|entry| entry.tag == tag) {
+                        self.state.acl.push(AclEntry { tag, perm: 0o4 });
+                        self.state.acl.sort_by_key(|entry| entry.tag);
+                    }
+                    self.acl_input.clear();
+                }
+            }
+            Message::AclInput(input) => {
+                self.acl_input = input;
+            }
+            Message::AclPerm(index, perm) => {
+                if let Some(entry) = self.state.acl.get_mut(index) {
+                    entry.perm = perm & 0o7;
+                }
+            }
+            Message::AclRemove(index) => {
+                if index < self.state.acl.len() {
+                    self.state.acl.remove(index);
+                }
+            }
+            Message::Group(group) => {
+                self.state.group = group;
+            }
+            Message::Mode(mode) => {
+                self.state.mode = mode & 0o7777;
+            }
+            Message::Owner(owner) => {
+                self.state.owner = owner;
+            }
+            Message::Recursive(recursive) => {
+                self.recursive = recursive;
+            }
+            Message::XattrAdd => {
+                let (name, value) = std::mem::take(&mut self.xattr_input);
+                let name = name.trim().to_string();
+                if !name.is_empty() {
+                    let value = value.into_bytes();
+                    match self.state.xattrs.iter_mut().find(|(x, _)| *x == name) {
+                        Some(existing) => existing.1 = value,
+                        None => self.state.xattrs.push((name, value)),
+                    }
+                }
+            }
+            Message::XattrInputName(name) => {
+                self.xattr_input.0 = name;
+            }
+            Message::XattrInputValue(value) => {
+                self.xattr_input.1 = value;
+            }
+            Message::XattrRemove(index) => {
+                if index < self.state.xattrs.len() {
+                    self.state.xattrs.remove(index);
+                }
+            }
+            Message::XattrValue(index, value) => {
+                // Values that are not text cannot be edited
+                if let Some(xattr) = self.state.xattrs.get_mut(index) {
+                    if std::str::from_utf8(&xattr.1).is_ok() {
+                        xattr.1 = value.into_bytes();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn title(&self) -> String {
+        if let [path] = &*self.paths {
+            fl!(
+                "properties-title",
+                name = path
+                    .file_name()
+                    .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy())
+            )
+        } else {
+            fl!("properties-title-items", items = self.paths.len())
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let cosmic_theme::Spacing {
+            space_xxs, space_m, ..
+        } = theme::active().cosmic().spacing;
+
+        let mode = self.state.mode;
+        let mut column = widget::column::with_capacity(5).spacing(space_m);
+
+        if let Some(info) = &self.info_opt {
+            column = column.push(
+                widget::settings::section()
+                    .title(fl!("properties-details"))
+                    .add(
+                        widget::settings::item::builder(fl!("properties-inode"))
+                            .control(widget::text::body(info.inode.to_string())),
+                    )
+                    .add(
+                        widget::settings::item::builder(fl!("properties-device")).control(
+                            widget::text::body(format!("{}:{}", info.device.0, info.device.1)),
+                        ),
+                    )
+                    .add(
+                        widget::settings::item::builder(fl!("properties-links"))
+                            .control(widget::text::body(info.links.to_string())),
+                    ),
+            );
+        }
+
+        column = column.push(
+            widget::settings::section()
+                .title(fl!("properties-ownership"))
+                .add(
+                    widget::settings::item::builder(fl!("owner")).control(
+                        widget::text_input("", self.state.owner.as_str())
+                            .on_input(Message::Owner)
+                            .width(Length::Fixed(200.0)),
+                    ),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("group")).control(
+                        widget::text_input("", self.state.group.as_str())
+                            .on_input(Message::Group)
+                            .width(Length::Fixed(200.0)),
+                    ),
+                ),
+        );
+
+        let mode_item = |label: String, shift: u32| {
+            widget::settings::item::builder(label).control(widget::dropdown(
+                Cow::Borrowed(MODE_NAMES.as_slice()),
+                Some(get_mode_part(mode, shift) as usize),
+                move |selected| Message::Mode(set_mode_part(mode, shift, selected as u32)),
+            ))
+        };
+        let special_item = |label: String, bit: u32| {
+            widget::settings::item::builder(label).toggler(mode & bit != 0, move |enabled| {
+                Message::Mode(if enabled { mode | bit } else { mode & !bit })
+            })
+        };
+        column = column.push(
+            widget::settings::section()
+                .title(fl!("properties-permissions"))
+                .add(mode_item(fl!("owner"), MODE_SHIFT_USER))
+                .add(mode_item(fl!("group"), MODE_SHIFT_GROUP))
+                .add(mode_item(fl!("other"), MODE_SHIFT_OTHER))
+                .add(special_item(fl!("properties-setuid"), MODE_SETUID))
+                .add(special_item(fl!("properties-setgid"), MODE_SETGID))
+                .add(special_item(fl!("properties-sticky"), MODE_STICKY)),
+        );
+
+        let mut acl_section = widget::settings::section().title(fl!("properties-acl"));
+        for (index, entry) in self.state.acl.iter().enumerate() {
+            let label = match entry.tag {
+                AclTag::User(_) => fl!("properties-acl-user", name = entry.tag.name()),
+                _ => fl!("properties-acl-group", name = entry.tag.name()),
+            };
+            acl_section = acl_section.add(
+                widget::settings::item::builder(label).control(
+                    widget::row::with_capacity(2)
+                        .align_y(Alignment::Center)
+                        .spacing(space_xxs)
+                        .push(widget::dropdown(
+                            Cow::Borrowed(MODE_NAMES.as_slice()),
+                            Some(entry.perm as usize),
+                            move |selected| Message::AclPerm(index, selected as u32),
+                        ))
+                        .push(
+                            widget::button::icon(icon::from_name("edit-delete-symbolic"))
+                                .on_press(Message::AclRemove(index)),
+                        ),
+                ),
+            );
+        }
+        let acl_add_maybe = AclEntry::parse_qualifier(&self.acl_input).map(|_| Message::AclAdd);
+        acl_section = acl_section.add(
+            widget::row::with_capacity(2)
+                .align_y(Alignment::Center)
+                .spacing(space_xxs)
+                .push(
+                    widget::text_input(fl!("properties-acl-placeholder"), self.acl_input.as_str())
+                        .on_input(Message::AclInput)
+                        .on_submit_maybe(acl_add_maybe.clone().map(|msg| move |_| msg.clone())),
+                )
+                .push(
+                    widget::button::icon(icon::from_name("list-add-symbolic"))
+                        .on_press_maybe(acl_add_maybe),
+                ),
+        );
+        column = column.push(acl_section);
+
+        let mut xattr_section = widget::settings::section().title(fl!("properties-xattrs"));
+        for (index, (name, value)) in self.state.xattrs.iter().enumerate() {
+            // Values that are not text are shown as hexadecimal and cannot be edited
+            let (item, value_input) = match std::str::from_utf8(value) {
+                Ok(text) => (
+                    widget::settings::item::builder(name.clone()),
+                    widget::text_input("", text)
+                        .on_input(move |value| Message::XattrValue(index, value)),
+                ),
+                Err(_) => (
+                    widget::settings::item::builder(name.clone())
+                        .description(fl!("properties-xattr-binary")),
+                    widget::text_input("", hex(value)),
+                ),
+            };
+            xattr_section = xattr_section.add(
+                item.control(
+                    widget::row::with_capacity(2)
+                        .align_y(Alignment::Center)
+                        .spacing(space_xxs)
+                        .push(value_input.width(Length::Fixed(200.0)))
+                        .push(
+                            widget::button::icon(icon::from_name("edit-delete-symbolic"))
+                                .on_press(Message::XattrRemove(index)),
+                        ),
+                ),
+            );
+        }
+        let xattr_add_maybe = (!self.xattr_input.0.trim().is_empty()).then_some(Message::XattrAdd);
+        xattr_section = xattr_section.add(
+            widget::row::with_capacity(3)
+                .align_y(Alignment::Center)
+                .spacing(space_xxs)
+                .push(
+                    widget::text_input(fl!("properties-xattr-name"), self.xattr_input.0.as_str())
+                        .on_input(Message::XattrInputName),
+                )
+                .push(
+                    widget::text_input(fl!("properties-xattr-value"), self.xattr_input.1.as_str())
+                        .on_input(Message::XattrInputValue)
+                        .on_submit_maybe(xattr_add_maybe.clone().map(|msg| move |_| msg.clone())),
+                )
+                .push(
+                    widget::button::icon(icon::from_name("list-add-symbolic"))
+                        .on_press_maybe(xattr_add_maybe),
+                ),
+        );
+        column = column.push(xattr_section);
+
+        if self.has_dir {
+            column = column.push(
+                widget::settings::section().add(
+                    widget::settings::item::builder(fl!("properties-recursive"))
+                        .toggler(self.recursive, Message::Recursive),
+                ),
+            );
+        }
+
+        widget::container(widget::scrollable(column))
+            .max_height(480.0)
+            .into()
+    }
+}
+
+/// Split a device number into major and minor numbers, using the glibc encoding
+const fn dev_major_minor(dev: u64) -> (u64, u64) {
+    let major = ((dev >> 32) & 0xffff_f000) | ((dev >> 8) & 0x0000_0fff);
+    let minor = ((dev >> 12) & 0xffff_ff00) | (dev & 0x0000_00ff);
+    (major, minor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AclEntry, AclTag, MODE_SETGID, Message, PermissionsState, Properties, acl_from_bytes,
+        acl_to_bytes, dev_major_minor,
+    };
+
+    fn properties(original: PermissionsState) -> Properties {
+        Properties {
+            paths: Box::new([]),
+            info_opt: None,
+            has_dir: true,
+            original: original.clone(),
+            state: original,
+            recursive: true,
+            acl_input: String::new(),
+            xattr_input: (String::new(), String::new()),
+        }
+    }
+
+    #[test]
+    fn acl_roundtrip() {
+        let entries = [
+            AclEntry {
+                tag: AclTag::Other,
+                perm: 0o4,
+            },
+            AclEntry {
+                tag: AclTag::User(1000),
+                perm: 0o6,
+            },
+            AclEntry {
+                tag: AclTag::UserObj,
+                perm: 0o7,
+            },
+            AclEntry {
+                tag: AclTag::Mask,
+                perm: 0o6,
+            },
+            AclEntry {
+                tag: AclTag::GroupObj,
+                perm: 0o5,
+            },
+        ];
+        let bytes = acl_to_bytes(&entries);
+        assert_eq!(bytes.len(), 4 + entries.len() * 8);
+
+        let decoded = acl_from_bytes(&bytes).expect("failed to decode ACL");
+        let tags: Vec<_> = decoded.iter().map(|entry| entry.tag).collect();
+        assert_eq!(
+            tags,
+            [
+                AclTag::UserObj,
+                AclTag::User(1000),
+                AclTag::GroupObj,
+                AclTag::Mask,
+                AclTag::Other
+            ]
+        );
+        assert_eq!(decoded[1].perm, 0o6);
+    }
+
+    #[test]
+    fn acl_invalid() {
+        assert!(acl_from_bytes(&[]).is_none());
+        // Wrong version
+        assert!(acl_from_bytes(&[1, 0, 0, 0]).is_none());
+        // Truncated entry
+        assert!(acl_from_bytes(&[2, 0, 0, 0, 1, 0]).is_none());
+    }
+
+    #[test]
+    fn xattr_binary_values_are_kept() {
+        let original = PermissionsState {
+            xattrs: vec![
+                ("user.blob".to_string(), vec![0xff, 0x00, 0xfe]),
+                ("user.note".to_string(), b"draft".to_vec()),
+            ],
+            ..Default::default()
+        };
+        let mut properties = properties(original);
+
+        properties.update(Message::XattrValue(0, "text".to_string()));
+        properties.update(Message::XattrValue(1, "final".to_string()));
+        let change = properties.change().unwrap();
+        assert_eq!(
+            change.xattrs,
+            [("user.note".to_string(), Some(b"final".to_vec()))]
+        );
+    }
+
+    #[test]
+    fn only_changed_permissions_apply() {
+        let mut properties = properties(PermissionsState {
+            mode: 0o755,
+            acl: vec![
+                AclEntry {
+                    tag: AclTag::User(1000),
+                    perm: 0o4,
+                },
+                AclEntry {
+                    tag: AclTag::Group(100),
+                    perm: 0o6,
+                },
+            ],
+            ..Default::default()
+        });
+        properties.update(Message::Mode(0o775 | MODE_SETGID));
+        properties.update(Message::AclPerm(0, 0o6));
+        properties.update(Message::AclRemove(1));
+        let change = properties.change().unwrap();
+        assert_eq!(change.mode_set, 0o020 | MODE_SETGID);
+        assert_eq!(change.mode_clear, 0);
+
+        // Private contents only gain group write, setgid is only set on folders
+        assert_eq!(change.apply_mode(0o600, false, false), 0o620);
+        assert_eq!(change.apply_mode(0o700, false, true), 0o720 | MODE_SETGID);
+        assert_eq!(change.apply_mode(0o644, true, false), 0o664 | MODE_SETGID);
+
+        // Entries of the contents that were not edited are kept
+        let mut named = vec![
+            AclEntry {
+                tag: AclTag::User(1000),
+                perm: 0o4,
+            },
+            AclEntry {
+                tag: AclTag::User(1001),
+                perm: 0o7,
+            },
+            AclEntry {
+                tag: AclTag::Group(100),
+                perm: 0o6,
+            },
+        ];
+        change.apply_acl(&mut named);
+        assert_eq!(
+            named,
+            [
+                AclEntry {
+                    tag: AclTag::User(1000),
+                    perm: 0o6,
+                },
+                AclEntry {
+                    tag: AclTag::User(1001),
+                    perm: 0o7,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn device_numbers() {
+        // makedev(8, 1)
+        assert_eq!(dev_major_minor(0x0801), (8, 1));
+        // makedev(259, 65536)
+        assert_eq!(dev_major_minor(0x1001_0300), (259, 65536));
+    }
+}
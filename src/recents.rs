@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use chrono::{DateTime, Local, NaiveDate, TimeDelta, Utc};
+use mime_guess::{Mime, mime};
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use crate::{
+    desktop_stack::{Stack, kind_stack},
+    fl,
+};
+
+/// Number of recent files that can be shown, selected in the settings
+pub const RECENTS_LIMITS: [usize; 5] = [50, 100, 250, 500, 1000];
+
+/// Labels of the limits
+pub static LIMIT_NAMES: LazyLock<Vec<String>> =
+    LazyLock::new(|| RECENTS_LIMITS.iter().map(ToString::to_string).collect());
+
+/// Labels of the type filter, the first one shows all types
+pub static KIND_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let mut names = vec![fl!("all-types")];
+    names.extend(RecentKind::ALL.iter().map(|kind| kind.name()));
+    names
+});
+
+/// Labels of the ages that recent files can be cleared by
+pub static CLEAR_NAMES: LazyLock<Vec<String>> =
+    LazyLock::new(|| ClearOlderThan::ALL.iter().map(|age| age.name()).collect());
+
+/// When and by which applications a file from `recently-used.xbel` was used
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Recent {
+    /// Last time the file was modified or visited
+    pub used: DateTime<Utc>,
+    /// Names of the applications that registered the file
+    pub apps: Vec<String>,
+}
+
+/// Heading that recent files are listed under
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum RecentGroup {
+    Today,
+    Yesterday,
+    ThisWeek,
+    Older,
+}
+
+impl RecentGroup {
+    pub fn new(used: DateTime<Utc>, today: NaiveDate) -> Self {
+        let date = used.with_timezone(&Local).date_naive();
+        let days = today.signed_duration_since(date).num_days();
+        if days <= 0 {
+            Self::Today
+        } else if days == 1 {
+            Self::Yesterday
+        } else if days < 7 {
+            Self::ThisWeek
+        } else {
+            Self::Older
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Today => fl!("today"),
+            Self::Yesterday => fl!("stack-yesterday"),
+            Self::ThisWeek => fl!("stack-this-week"),
+            Self::Older => fl!("stack-older"),
+        }
+    }
+}
+
+/// Type of file that recent files can be filtered by
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RecentKind {
+    Documents,
+    Images,
+    Audio,
+    Video,
+    Archives,
+}
+
+impl RecentKind {
+    pub const ALL: [Self; 5] = [
+        Self::Documents,
+        Self::Images,
+        Self::Audio,
+        Self::Video,
+        Self::Archives,
+    ];
+
+    pub fn new(mime: &Mime) -> Option<Self> {
+        if mime.type_() == mime::AUDIO {
+            return Some(Self::Audio);
+        }
+        if mime.type_() == mime::VIDEO {
+            return Some(Self::Video);
+        }
+        match kind_stack(mime)? {
+            Stack::Images => Some(Self::Images),
+            Stack::Documents => Some(Self::Documents),
+            Stack::Archives => Some(Self::Archives),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Documents => fl!("stack-documents"),
+            Self::Images => fl!("stack-images"),
+            Self::Audio => fl!("recents-audio"),
+            Self::Video => fl!("recents-video"),
+            Self::Archives => fl!("stack-archives"),
+        }
+    }
+}
+
+/// Recent files that are shown
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RecentsFilter {
+    /// Only show files used by this application
+    pub app_opt: Option<String>,
+    /// Only show files of this type
+    pub kind_opt: Option<RecentKind>,
+}
+
+impl RecentsFilter {
+    pub fn matches(&self, mime: &Mime, recent: &Recent) -> bool {
+        if let Some(app) = &self.app_opt {
+            if !recent.apps.contains(app) {
+                return false;
+            }
+        }
+        if let Some(kind) = self.kind_opt {
+            if RecentKind::new(mime) != Some(kind) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Age of recent files to remove from the list
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ClearOlderThan {
+    Day,
+    #[default]
+    Week,
+    Month,
+    Year,
+}
+
+impl ClearOlderThan {
+    pub const ALL: [Self; 4] = [Self::Day, Self::Week, Self::Month, Self::Year];
+
+    pub fn name(self) -> String {
+        match self {
+            Self::Day => fl!("clear-older-than-day"),
+            Self::Week => fl!("clear-older-than-week"),
+            Self::Month => fl!("clear-older-than-month"),
+            Self::Year => fl!("clear-older-than-year"),
+        }
+    }
+
+    /// Files last used before this time are cleared
+    pub fn cutoff(self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let days = match self {
+            Self::Day => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::Year => 365,
+        };
+        now - TimeDelta::days(days)
+    }
+}
+
+/// Use of a file to add to `recently-used.xbel`
+#[derive(Clone, Debug)]
+pub struct RecentUse {
+    pub path: PathBuf,
+    pub mime: Mime,
+    /// Name of the application that used the file
+    pub app_name: String,
+    /// Command that starts the application
+    pub exec: String,
+}
+
+impl RecentUse {
+    pub fn new(path: &Path, mime: &Mime, app_name: &str, exec: &str) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            mime: mime.clone(),
+            app_name: app_name.to_string(),
+            exec: exec.to_string(),
+        }
+    }
+}
+
+/// Add files to `recently-used.xbel`. This writes the file, so it should not run on the UI
+/// thread.
+pub fn add_recents(uses: &[RecentUse]) {
+    for recent_use in uses {
+        add_recent(
+            &recent_use.path,
+            &recent_use.mime,
+            &recent_use.app_name,
+            &recent_use.exec,
+        );
+    }
+}
+
+/// Add a file to `recently-used.xbel`, as used by the application named `app_name` that is
+/// started with `exec`
+fn add_recent(path: &Path, mime: &Mime, app_name: &str, exec: &str) {
+    if let Err(err) = recently_used_xbel::update_recently_used(
+        &path.to_path_buf(),
+        app_name.to_string(),
+        exec.to_string(),
+        Some(mime.to_string()),
+    ) {
+        log::warn!(
+            "failed to add {} to recent files: {:?}",
+            path.display(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, TimeZone};
+
+    fn recent(apps: &[&str]) -> Recent {
+        Recent {
+            used: Utc::now(),
+            apps: apps.iter().map(|app| app.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn groups_by_day() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 20).unwrap();
+        let group = |day| {
+            let date = NaiveDate::from_ymd_opt(2024, 5, day).unwrap();
+            let time = Local
+                .from_local_datetime(&date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap()))
+                .unwrap();
+            RecentGroup::new(time.with_timezone(&Utc), today)
+        };
+        assert_eq!(group(20), RecentGroup::Today);
+        assert_eq!(group(19), RecentGroup::Yesterday);
+        assert_eq!(group(14), RecentGroup::ThisWeek);
+        assert_eq!(group(1), RecentGroup::Older);
+    }
+
+    #[test]
+    fn filters_by_app_and_kind() {
+        let png: Mime = "image/png".parse().unwrap();
+        let ogg: Mime = "audio/ogg".parse().unwrap();
+        let viewer = recent(&["Image Viewer"]);
+
+        assert!(RecentsFilter::default().matches(&ogg, &viewer));
+
+        let by_app = RecentsFilter {
+            app_opt: Some("Image Viewer".to_string()),
+            kind_opt: None,
+        };
+        assert!(by_app.matches(&png, &viewer));
+        assert!(!by_app.matches(&png, &recent(&["Text Editor"])));
+
+        let by_kind = RecentsFilter {
+            app_opt: None,
+            kind_opt: Some(RecentKind::Images),
+        };
+        assert!(by_kind.matches(&png, &viewer));
+        assert!(!by_kind.matches(&ogg, &viewer));
+    }
+}
@@ -0,0 +1,332 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    fs,
+    net::{IpAddr, ToSocketAddrs},
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use crate::{FxOrderMap, config::RemoteThumbnails};
+
+/// Network or GVFS mount that thumbnails can be enabled for
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemoteMount {
+    pub mount_point: PathBuf,
+    /// Identifies the mount in the thumbnail policy, for example `//nas/media` or
+    /// `smb-share:server=nas,share=media`
+    pub source: String,
+    pub host: Option<String>,
+    /// The host resolves to loopback, private or link-local addresses only
+    pub local_network: bool,
+}
+
+/// List mounted network filesystems and GVFS mounts. This may resolve host names, so it should
+/// be called from a blocking task.
+pub fn remote_mounts() -> Vec<RemoteMount> {
+    let mut mounts = Vec::new();
+
+    #[cfg(target_os = "linux")]
+    match procfs::process::Process::myself().and_then(|process| process.mountinfo()) {
+        Ok(mount_infos) => {
+            for mount_info in mount_infos.iter() {
+                if crate::tab::fs_type_kind(&mount_info.fs_type) != crate::tab::FsKind::Remote {
+                    continue;
+                }
+                let Some(source) = mount_info.mount_source.clone() else {
+                    continue;
+                };
+                let host = mount_host(&mount_info.fs_type, &source);
+                mounts.push(RemoteMount {
+                    mount_point: mount_info.mount_point.clone(),
+                    source,
+                    host,
+                    local_network: false,
+                });
+            }
+        }
+        Err(err) => {
+            log::warn!("failed to get mount info: {err}");
+        }
+    }
+
+    // GVFS exposes each of its mounts as a directory below its FUSE mount point
+    if let Some(gvfs_dir) = dirs::runtime_dir().map(|dir| dir.join("gvfs")) {
+        if let Ok(entries) = fs::read_dir(&gvfs_dir) {
+            for entry in entries.flatten() {
+                let Ok(source) = entry.file_name().into_string() else {
+                    continue;
+                };
+                if !is_gvfs_network(&source) {
+                    continue;
+                }
+                let host = gvfs_host(&source);
+                mounts.push(RemoteMount {
+                    mount_point: entry.path(),
+                    source,
+                    host,
+                    local_network: false,
+                });
+            }
+        }
+    }
+
+    for mount in &mut mounts {
+        mount.local_network = mount.host.as_deref().is_some_and(is_local_network);
+    }
+    mounts.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+    mounts
+}
+
+/// Host name from the source of a kernel mount, for filesystem types where the source names one
+fn mount_host(fs_type: &str, source: &str) -> Option<String> {
+    let host = match fs_type {
+        // //host/share
+        "cifs" | "smb" | "smb2" | "smbfs" => source.strip_prefix("//")?.split('/').next()?,
+        // https://host/path
+        "davfs" | "davfs2" | "fuse.davfs2" => {
+            let (_scheme, rest) = source.split_once("://")?;
+            let authority = rest.split('/').next()?;
+            strip_port(authority.rsplit('@').next()?)
+        }
+        // [user@]host:/path
+        "nfs" | "nfs4" | "fuse.sshfs" => {
+            let (authority, _path) = source.rsplit_once(':')?;
+            authority.rsplit('@').next()?
+        }
+        _ => return None,
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+fn strip_port(authority: &str) -> &str {
+    if authority.starts_with('[') {
+        authority
+            .split_once(']')
+            .map_or(authority, |(host, _port)| host)
+    } else {
+        authority
+            .split_once(':')
+            .map_or(authority, |(host, _port)| host)
+    }
+}
+
+/// GVFS backends that talk to a server, rather than to a device or an archive
+fn is_gvfs_network(name: &str) -> bool {
+    let backend = name
+        .split_once(':')
+        .map_or(name, |(backend, _params)| backend);
+    matches!(
+        backend,
+        "afp-volume"
+            | "dav"
+            | "davs"
+            | "ftp"
+            | "ftps"
+            | "google-drive"
+            | "nfs"
+            | "onedrive"
+            | "sftp"
+            | "smb-share"
+    )
+}
+
+/// Host name from a GVFS mount directory name, like `sftp:host=example.com,user=me`
+fn gvfs_host(name: &str) -> Option<String> {
+    let (_backend, params) = name.split_once(':')?;
+    params.split(',').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        matches!(key, "host" | "server")
+            .then(|| value.trim_start_matches('[').trim_end_matches(']'))
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    })
+}
+
+fn is_local_network(host: &str) -> bool {
+    if let Ok(addr) = host.parse::<IpAddr>() {
+        return is_local_addr(addr);
+    }
+    // mDNS and single label names are only meaningful on the local network
+    if host.ends_with(".local") || !host.contains('.') {
+        return true;
+    }
+    match (host, 0).to_socket_addrs() {
+        Ok(addrs) => {
+            let mut found = false;
+            for addr in addrs {
+                if !is_local_addr(addr.ip()) {
+                    return false;
+                }
+                found = true;
+            }
+            found
+        }
+        Err(err) => {
+            log::info!("failed to resolve {host}: {err}");
+            false
+        }
+    }
+}
+
+fn is_local_addr(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(addr) => addr.is_loopback() || addr.is_private() || addr.is_link_local(),
+        IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+            Some(addr) => is_local_addr(IpAddr::V4(addr)),
+            None => addr.is_loopback() || addr.is_unique_local() || addr.is_unicast_link_local(),
+        },
+    }
+}
+
+/// Which remote mounts may be thumbnailed, shared with each tab
+#[derive(Clone, Debug, Default)]
+pub struct RemoteThumbnailPolicy {
+    allowed: Arc<[PathBuf]>,
+}
+
+impl RemoteThumbnailPolicy {
+    pub fn new(mounts: &[RemoteMount], policies: &FxOrderMap<String, RemoteThumbnails>) -> Self {
+        let allowed = mounts
+            .iter()
+            .filter(
+                |mount| match policies.get(&mount.source).copied().unwrap_or_default() {
+                    RemoteThumbnails::Never => false,
+                    RemoteThumbnails::LocalNetwork => mount.local_network,
+                    RemoteThumbnails::Always => true,
+                },
+            )
+            .map(|mount| mount.mount_point.clone())
+            .collect();
+        Self { allowed }
+    }
+
+    /// Files on remote mounts that are not known are never thumbnailed
+    pub fn allows(&self, path: &Path) -> bool {
+        self.allowed
+            .iter()
+            .any(|mount_point| path.starts_with(mount_point))
+    }
+}
+
+/// Estimated throughput of remote thumbnailing in bytes per second, zero if unknown
+static THROUGHPUT: AtomicU64 = AtomicU64::new(0);
+
+/// Reads shorter than this are dominated by latency or cache hits and say little about bandwidth
+const MIN_SAMPLE: Duration = Duration::from_millis(100);
+
+/// Update the throughput estimate after thumbnailing a remote file
+pub fn record_transfer(bytes: u64, elapsed: Duration) {
+    if elapsed < MIN_SAMPLE || bytes == 0 {
+        return;
+    }
+    let sample = (bytes as f64 / elapsed.as_secs_f64()) as u64;
+    // Exponentially weighted so a few slow files do not throttle a fast link for long
+    _ = THROUGHPUT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |old| {
+        Some(if old == 0 {
+            sample
+        } else {
+            (old * 3 + sample) / 4
+        })
+    });
+}
+
+/// Number of concurrent remote thumbnail jobs for the measured bandwidth
+pub fn remote_jobs(max_jobs: usize) -> usize {
+    const MB: u64 = 1000 * 1000;
+    match THROUGHPUT.load(Ordering::Relaxed) {
+        0 => max_jobs,
+        bps if bps < MB => 1,
+        bps if bps < 10 * MB => max_jobs.div_ceil(2),
+        _ => max_jobs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mount_hosts() {
+        assert_eq!(mount_host("cifs", "//nas/media").as_deref(), Some("nas"));
+        assert_eq!(
+            mount_host("nfs4", "server:/export").as_deref(),
+            Some("server")
+        );
+        assert_eq!(
+            mount_host("fuse.sshfs", "me@example.com:/home/me").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            mount_host("davfs", "https://dav.example.com:8443/files").as_deref(),
+            Some("dav.example.com")
+        );
+        assert_eq!(
+            mount_host("nfs", "[fd00::1]:/export").as_deref(),
+            Some("fd00::1")
+        );
+        assert_eq!(mount_host("fuse.rclone", "gdrive:"), None);
+        assert!(is_gvfs_network("smb-share:server=nas.local,share=media"));
+        assert!(!is_gvfs_network("mtp:host=Phone"));
+        assert_eq!(
+            gvfs_host("smb-share:server=nas.local,share=media").as_deref(),
+            Some("nas.local")
+        );
+        assert_eq!(
+            gvfs_host("sftp:host=example.com,user=me").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(gvfs_host("trash:"), None);
+    }
+
+    #[test]
+    fn local_network() {
+        assert!(is_local_network("192.168.1.10"));
+        assert!(is_local_network("fe80::1"));
+        assert!(is_local_network("fd12:3456::1"));
+        assert!(is_local_network("::ffff:10.0.0.2"));
+        assert!(is_local_network("nas.local"));
+        assert!(is_local_network("nas"));
+        assert!(!is_local_network("8.8.8.8"));
+        assert!(!is_local_network("2001:4860:4860::8888"));
+    }
+
+    #[test]
+    fn policy() {
+        let mounts = [
+            RemoteMount {
+                mount_point: PathBuf::from("/mnt/nas"),
+                source: "//nas/media".to_string(),
+                host: Some("nas".to_string()),
+                local_network: true,
+            },
+            RemoteMount {
+                mount_point: PathBuf::from("/mnt/cloud"),
+                source: "me@example.com:/".to_string(),
+                host: Some("example.com".to_string()),
+                local_network: false,
+            },
+        ];
+        let mut policies = FxOrderMap::default();
+        policies.insert("//nas/media".to_string(), RemoteThumbnails::LocalNetwork);
+        policies.insert(
+            "me@example.com:/".to_string(),
+            RemoteThumbnails::LocalNetwork,
+        );
+        let policy = RemoteThumbnailPolicy::new(&mounts, &policies);
+        assert!(policy.allows(Path::new("/mnt/nas/photo.jpg")));
+        assert!(!policy.allows(Path::new("/mnt/cloud/photo.jpg")));
+        assert!(!policy.allows(Path::new("/mnt/other/photo.jpg")));
+
+        policies.insert("me@example.com:/".to_string(), RemoteThumbnails::Always);
+        policies.insert("//nas/media".to_string(), RemoteThumbnails::Never);
+        let policy = RemoteThumbnailPolicy::new(&mounts, &policies);
+        assert!(!policy.allows(Path::new("/mnt/nas/photo.jpg")));
+        assert!(policy.allows(Path::new("/mnt/cloud/photo.jpg")));
+    }
+}
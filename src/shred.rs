@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use walkdir::WalkDir;
+
+use crate::{
+    fl,
+    operation::{Controller, OperationError},
+};
+
+/// Number of times file contents can be overwritten, selected in the settings
+pub const SHRED_PASSES: [u32; 3] = [1, 3, 7];
+
+/// Labels of the numbers of passes
+pub static PASSES_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    SHRED_PASSES
+        .iter()
+        .map(|passes| fl!("secure-delete-passes-count", passes = passes))
+        .collect()
+});
+
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Pseudo random bytes, which are enough to hide what was stored before
+struct Noise(u64);
+
+impl Noise {
+    fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        // Xorshift must not start from zero
+        Self(nanos | 1)
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(8) {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            chunk.copy_from_slice(&self.0.to_le_bytes()[..chunk.len()]);
+        }
+    }
+}
+
+/// Overwrite the contents of the files in `paths` and inside the folders in `paths` `passes`
+/// times with random data, then delete them. Symbolic links and special files are deleted
+/// without being overwritten. Progress and cancellation are handled through `controller`.
+///
+/// Overwriting in place does not reach old copies of the data kept by SSD wear leveling or
+/// copy-on-write filesystems.
+pub fn shred_paths(
+    paths: &[PathBuf],
+    passes: u32,
+    controller: &Controller,
+) -> Result<(), OperationError> {
+    // Contents are listed before their folders, so folders are empty when deleted
+    let mut entries = Vec::new();
+    for path in paths {
+        for entry_res in WalkDir::new(path)
+            .contents_first(true)
+            .follow_root_links(false)
+        {
+            let entry = entry_res.map_err(|err| OperationError::from_err(err, controller))?;
+            let metadata = entry
+                .metadata()
+                .map_err(|err| OperationError::from_err(err, controller))?;
+            let len = if metadata.is_file() {
+                metadata.len()
+            } else {
+                0
+            };
+            entries.push((entry.into_path(), metadata.is_dir(), len));
+        }
+    }
+
+    let total = entries
+        .iter()
+        .map(|(_, _, len)| len * u64::from(passes))
+        .sum::<u64>()
+        .max(1);
+    let mut done = 0;
+    let mut noise = Noise::new();
+    let mut buf = vec![0; BUFFER_SIZE];
+    for (path, is_dir, len) in entries {
+        futures::executor::block_on(async {
+            controller
+                .check()
+                .await
+                .map_err(|s| OperationError::from_state(s, controller))
+        })?;
+
+        if is_dir {
+            fs::remove_dir(&path).map_err(|err| shred_error(&path, err, controller))?;
+            continue;
+        }
+
+        if len > 0 {
+            overwrite(&path, len, passes, &mut noise, &mut buf, |written| {
+                futures::executor::block_on(async {
+                    controller
+                        .check()
+                        .await
+                        .map_err(|s| OperationError::from_state(s, controller))
+                })?;
+                done += written;
+                controller.set_progress(done as f32 / total as f32);
+                Ok(())
+            })
+            .map_err(|err| match err {
+                ShredError::Io(err) => shred_error(&path, err, controller),
+                ShredError::Operation(err) => err,
+            })?;
+        }
+        fs::remove_file(&path).map_err(|err| shred_error(&path, err, controller))?;
+    }
+    Ok(())
+}
+
+/// Overwrite a file of length `len`, calling `on_progress` with the number of bytes written
+/// after each buffer, then truncate it so its size is not left behind either
+fn overwrite(
+    path: &Path,
+    len: u64,
+    passes: u32,
+    noise: &mut Noise,
+    buf: &mut [u8],
+    mut on_progress: impl FnMut(u64) -> Result<(), OperationError>,
+) -> Result<(), ShredError> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    for _ in 0..passes {
+        file.seek(SeekFrom::Start(0))?;
+        let mut remaining = len;
+        while remaining > 0 {
+            let count = remaining.min(buf.len() as u64) as usize;
+            noise.fill(&mut buf[..count]);
+            file.write_all(&buf[..count])?;
+            remaining -= count as u64;
+            on_progress(count as u64).map_err(ShredError::Operation)?;
+        }
+        // Each pass has to reach the disk, or only the last one may be written
+        file.sync_all()?;
+    }
+    file.set_len(0)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+fn shred_error(path: &Path, err: io::Error, controller: &Controller) -> OperationError {
+    OperationError::from_err(
+        format!("failed to securely delete {}: {}", path.display(), err),
+        controller,
+    )
+}
+
+enum ShredError {
+    Io(io::Error),
+    Operation(OperationError),
+}
+
+impl From<io::Error> for ShredError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_fills_partial_chunks() {
+        let mut noise = Noise::new();
+        let mut buf = [0; 13];
+        noise.fill(&mut buf);
+        assert!(buf.iter().any(|&byte| byte != 0));
+    }
+
+    #[test]
+    fn shreds_folders_recursively() {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("folder");
+        fs::create_dir_all(folder.join("nested")).unwrap();
+        fs::write(folder.join("a.txt"), b"secret").unwrap();
+        fs::write(
+            folder.join("nested").join("b.txt"),
+            vec![7; BUFFER_SIZE + 1],
+        )
+        .unwrap();
+        fs::write(folder.join("empty"), b"").unwrap();
+        let file = dir.path().join("c.txt");
+        fs::write(&file, b"secret").unwrap();
+
+        shred_paths(&[folder.clone(), file.clone()], 2, &Controller::default()).unwrap();
+        assert!(!folder.exists());
+        assert!(!file.exists());
+        assert!(dir.path().exists());
+    }
+}
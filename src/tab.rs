@@ -55,7 +55,7 @@ use std::{
     borrow::Cow,
     cell::{Cell, RefCell},
     cmp::{Ordering, Reverse},
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     error::Error,
     fmt::{self, Display},
     fs::{self, File, Metadata},
@@ -76,19 +76,32 @@ use crate::{
     FxOrderMap,
     app::{Action, PreviewItem, PreviewKind},
     clipboard::{ClipboardCopy, ClipboardKind, ClipboardPaste},
-    config::{DesktopConfig, ICON_SCALE_MAX, ICON_SIZE_GRID, IconSizes, TabConfig, ThumbCfg},
+    config::{
+        DesktopConfig, DesktopLayout, ICON_SCALE_MAX, ICON_SIZE_GRID, IconSizes, StackBy,
+        TabConfig, ThumbCfg,
+    },
+    desktop_layout,
+    desktop_stack::{self, Stack},
     dialog::DialogKind,
     fl,
+    folder_preview::FolderPreview,
+    free_layout::FreeLayout,
+    image_viewer::{ImageViewer, ImageZoom},
     large_image::{
         LargeImageManager, decode_large_image, exceeds_memory_limit, should_use_dedicated_worker,
         should_use_tiling,
     },
     localize::{LANGUAGE_SORTER, LOCALE},
+    media::{self, MediaInfo},
     menu, mime_app,
     mime_icon::{mime_for_path, mime_icon},
     mounter::MOUNTERS,
     mouse_area,
     operation::{Controller, OperationError},
+    photo::{self, PhotoInfo, Rotation},
+    recents::{self, ClearOlderThan, Recent, RecentGroup, RecentKind, RecentsFilter},
+    remote::{self, RemoteThumbnailPolicy},
+    text_preview::{self, TextPreview},
     thumbnail_cacher::{CachedThumbnail, ThumbnailCacher, ThumbnailSize},
     thumbnailer::thumbnailer,
 };
@@ -96,11 +109,18 @@ use uzers::{get_group_by_gid, get_user_by_uid};
 
 pub const DOUBLE_CLICK_DURATION: Duration = Duration::from_millis(500);
 pub const HOVER_DURATION: Duration = Duration::from_millis(1600);
+const SLIDESHOW_INTERVAL: Duration = Duration::from_secs(5);
+/// How often frames and the position of playing media are updated
+#[cfg(feature = "playback")]
+const PLAYBACK_INTERVAL: Duration = Duration::from_millis(33);
 //TODO: best limit for search items
 const MAX_SEARCH_LATENCY: Duration = Duration::from_millis(20);
 const MAX_SEARCH_RESULTS: usize = 200;
 //TODO: configurable thumbnail size?
 const THUMBNAIL_SIZE: u32 = (ICON_SIZE_GRID as u32) * (ICON_SCALE_MAX as u32);
+//TODO: allow resizing?
+const MEDIA_TEXT_WIDTH: f32 = 150.0;
+const MEDIA_DURATION_WIDTH: f32 = 80.0;
 
 // Thumbnail generation semaphore - limits parallel thumbnail workers
 // Uses 4 workers for balanced throughput and memory usage
@@ -109,15 +129,26 @@ pub static THUMB_SEMAPHORE: LazyLock<tokio::sync::Semaphore> =
 
 pub(crate) static SORT_OPTION_FALLBACK: LazyLock<FxHashMap<String, (HeadingOptions, bool)>> =
     LazyLock::new(|| {
-        FxHashMap::from_iter(dirs::download_dir().into_iter().map(|dir| {
+        let mut fallback = FxHashMap::from_iter(dirs::download_dir().into_iter().map(|dir| {
             (
                 Location::Path(dir).normalize().to_string(),
                 (HeadingOptions::Modified, false),
             )
-        }))
+        }));
+        // Recent files are shown from the most recently used
+        fallback.insert(
+            Location::Recents.to_string(),
+            (HeadingOptions::Modified, false),
+        );
+        // Trashed items are shown from the most recently deleted
+        fallback.insert(
+            Location::Trash.to_string(),
+            (HeadingOptions::TrashedOn, false),
+        );
+        fallback
     });
 
-static MODE_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+pub(crate) static MODE_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
     vec![
         // Mode 0
         fl!("none"),
@@ -379,7 +410,7 @@ pub fn trash_icon_symbolic(icon_size: u16) -> widget::icon::Handle {
 }
 
 //TODO: translate, add more levels?
-fn format_size(size: u64) -> String {
+pub(crate) fn format_size(size: u64) -> String {
     const KB: u64 = 1000;
     const MB: u64 = 1000 * KB;
     const GB: u64 = 1000 * MB;
@@ -398,15 +429,37 @@ fn format_size(size: u64) -> String {
     }
 }
 
-const MODE_SHIFT_USER: u32 = 6;
-const MODE_SHIFT_GROUP: u32 = 3;
-const MODE_SHIFT_OTHER: u32 = 0;
+/// Compare optional values, sorting missing values last in either direction
+fn compare_missing_last<T>(
+    a: Option<T>,
+    b: Option<T>,
+    ascending: bool,
+    compare: impl FnOnce(T, T) -> Ordering,
+) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = compare(a, b);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+pub(crate) const MODE_SHIFT_USER: u32 = 6;
+pub(crate) const MODE_SHIFT_GROUP: u32 = 3;
+pub(crate) const MODE_SHIFT_OTHER: u32 = 0;
 
-const fn get_mode_part(mode: u32, shift: u32) -> u32 {
+pub(crate) const fn get_mode_part(mode: u32, shift: u32) -> u32 {
     (mode >> shift) & 0o7
 }
 
-fn set_mode_part(mode: u32, shift: u32, bits: u32) -> u32 {
+pub(crate) fn set_mode_part(mode: u32, shift: u32, bits: u32) -> u32 {
     assert!(bits <= 0o7);
     (mode & !(0o7 << shift)) | (bits << shift)
 }
@@ -530,6 +583,38 @@ pub enum FsKind {
     Gvfs,
 }
 
+/// Classify a filesystem type as found in /proc/self/mountinfo
+#[cfg(target_os = "linux")]
+pub(crate) fn fs_type_kind(fs_type: &str) -> FsKind {
+    // Network and distributed filesystem types
+    // Based on common remote filesystem types found in /proc/mounts
+    match fs_type {
+        // SMB/CIFS variants
+        "cifs" | "smb" | "smb2" | "smbfs" => FsKind::Remote,
+
+        // NFS variants
+        "nfs" | "nfs4" => FsKind::Remote,
+
+        // FUSE-based remote filesystems
+        "fuse.rclone" | "fuse.sshfs" | "fuse.davfs2" | "fuse.ceph" | "fuse.glusterfs"
+        | "fuse.s3fs" | "fuse.goofys" | "fuse.gcsfuse" | "fuse.afp" | "fuse.afpfs" => {
+            FsKind::Remote
+        }
+
+        // Other network protocols
+        "afs" | "coda" | "ncpfs" | "davfs" | "davfs2" | "shfs" => FsKind::Remote,
+
+        // Cluster/distributed filesystems
+        "ceph" | "glusterfs" | "lustre" | "gfs" | "gfs2" | "ocfs2" => FsKind::Remote,
+
+        // GVFS (GNOME Virtual File System)
+        "fuse.gvfsd-fuse" => FsKind::Gvfs,
+
+        // Everything else is local
+        _ => FsKind::Local,
+    }
+}
+
 #[cfg(target_os = "linux")]
 pub fn fs_kind(metadata: &Metadata) -> FsKind {
     //TODO: method to reload remote filesystems dynamically
@@ -546,37 +631,7 @@ pub fn fs_kind(metadata: &Metadata) -> FsKind {
                         let major = major_str.parse::<libc::c_uint>().ok()?;
                         let minor = minor_str.parse::<libc::c_uint>().ok()?;
                         let dev = libc::makedev(major, minor);
-                        // Network and distributed filesystem types
-                        // Based on common remote filesystem types found in /proc/mounts
-                        let kind = match mount_info.fs_type.as_str() {
-                            // SMB/CIFS variants
-                            "cifs" | "smb" | "smb2" | "smbfs" => FsKind::Remote,
-
-                            // NFS variants
-                            "nfs" | "nfs4" => FsKind::Remote,
-
-                            // FUSE-based remote filesystems
-                            "fuse.rclone" | "fuse.sshfs" | "fuse.davfs2" | "fuse.ceph"
-                            | "fuse.glusterfs" | "fuse.s3fs" | "fuse.goofys" | "fuse.gcsfuse"
-                            | "fuse.afp" | "fuse.afpfs" => FsKind::Remote,
-
-                            // Other network protocols
-                            "afs" | "coda" | "ncpfs" | "davfs" | "davfs2" | "shfs" => {
-                                FsKind::Remote
-                            }
-
-                            // Cluster/distributed filesystems
-                            "ceph" | "glusterfs" | "lustre" | "gfs" | "gfs2" | "ocfs2" => {
-                                FsKind::Remote
-                            }
-
-                            // GVFS (GNOME Virtual File System)
-                            "fuse.gvfsd-fuse" => FsKind::Gvfs,
-
-                            // Everything else is local
-                            _ => FsKind::Local,
-                        };
-                        Some((dev, kind))
+                        Some((dev, fs_type_kind(&mount_info.fs_type)))
                     }));
                 }
                 Err(err) => {
@@ -744,17 +799,22 @@ pub fn item_from_gvfs_info(path: PathBuf, file_info: gio::FileInfo, sizes: IconS
         icon_handle_grid,
         icon_handle_list,
         icon_handle_list_condensed,
-        thumbnail_opt: if remote {
-            Some(ItemThumbnail::NotImage)
-        } else {
-            None
-        },
+        thumbnail_opt: None,
         button_id: widget::Id::unique(),
         pos_opt: Cell::new(None),
         rect_opt: Cell::new(None),
         selected: false,
         highlighted: false,
         overlaps_drag_rect: false,
+        pdf_preview_opt: None,
+        text_preview_opt: None,
+        #[cfg(feature = "playback")]
+        playback_opt: None,
+        media_opt: None,
+        photo_opt: None,
+        recent_opt: None,
+        stack_opt: None,
+        remote,
         dir_size,
         cut: false,
     }
@@ -877,13 +937,22 @@ pub fn item_from_entry(
         icon_handle_grid,
         icon_handle_list,
         icon_handle_list_condensed,
-        thumbnail_opt: remote.then_some(ItemThumbnail::NotImage),
+        thumbnail_opt: None,
         button_id: widget::Id::unique(),
         pos_opt: Cell::new(None),
         rect_opt: Cell::new(None),
         selected: false,
         highlighted: false,
         overlaps_drag_rect: false,
+        pdf_preview_opt: None,
+        text_preview_opt: None,
+        #[cfg(feature = "playback")]
+        playback_opt: None,
+        media_opt: None,
+        photo_opt: None,
+        recent_opt: None,
+        stack_opt: None,
+        remote,
         dir_size,
         cut: false,
     }
@@ -1118,7 +1187,7 @@ pub fn scan_trash(_sizes: IconSizes) -> Vec<Item> {
     )
 ))]
 pub fn scan_trash(sizes: IconSizes) -> Vec<Item> {
-    let entries = match trash::os_limited::list() {
+    let entries = match crate::trash_folder::list() {
         Ok(entry) => entry,
         Err(err) => {
             log::warn!("failed to read trash items: {err}");
@@ -1176,6 +1245,15 @@ pub fn scan_trash(sizes: IconSizes) -> Vec<Item> {
                 selected: false,
                 highlighted: false,
                 overlaps_drag_rect: false,
+                pdf_preview_opt: None,
+                text_preview_opt: None,
+                #[cfg(feature = "playback")]
+                playback_opt: None,
+                media_opt: None,
+                photo_opt: None,
+                recent_opt: None,
+                stack_opt: None,
+                remote: false,
                 dir_size: DirSize::NotDirectory,
                 cut: false,
             })
@@ -1215,6 +1293,12 @@ pub fn scan_recents(sizes: IconSizes) -> Vec<Item> {
             let path = uri_to_path(bookmark.href)?;
             let last_edit = bookmark.modified.parse::<chrono::DateTime<Utc>>().ok()?;
             let last_visit = bookmark.visited.parse::<chrono::DateTime<Utc>>().ok()?;
+            let apps = bookmark
+                .info
+                .iter()
+                .flat_map(|info| &info.metadata.applications.applications)
+                .map(|app| app.name.clone())
+                .collect();
 
             if path.exists() {
                 let file_name = path.file_name()?;
@@ -1232,8 +1316,12 @@ pub fn scan_recents(sizes: IconSizes) -> Vec<Item> {
                     }
                 };
 
-                let item = item_from_entry(path, name, metadata, sizes);
-                Some((item, last_edit.min(last_visit)))
+                let mut item = item_from_entry(path, name, metadata, sizes);
+                item.recent_opt = Some(Recent {
+                    used: last_edit.max(last_visit),
+                    apps,
+                });
+                Some(item)
             } else {
                 log::warn!("recent file path not exist: {}", path.display());
                 None
@@ -1241,9 +1329,9 @@ pub fn scan_recents(sizes: IconSizes) -> Vec<Item> {
         })
         .collect();
 
-    recents.sort_by_key(|recent| Reverse(recent.1));
-
-    recents.into_iter().take(50).map(|(item, _)| item).collect()
+    // Filters and the limit from the tab config are applied by the tab
+    recents.sort_by_key(|item| Reverse(item.recent_opt.as_ref().map(|recent| recent.used)));
+    recents
 }
 
 pub fn scan_network(uri: &str, sizes: IconSizes) -> Vec<Item> {
@@ -1259,10 +1347,8 @@ pub fn scan_network(uri: &str, sizes: IconSizes) -> Vec<Item> {
     Vec::new()
 }
 
-//TODO: organize desktop items based on display
 pub fn scan_desktop(
     tab_path: &PathBuf,
-    _display: &str,
     desktop_config: DesktopConfig,
     mut sizes: IconSizes,
 ) -> Vec<Item> {
@@ -1271,7 +1357,19 @@ pub fn scan_desktop(
     let mut items = Vec::new();
 
     if desktop_config.show_content {
-        items.extend(scan_path(tab_path, sizes));
+        let mut content = scan_path(tab_path, sizes);
+        if desktop_config.stack_by != StackBy::Off {
+            for item in &mut content {
+                let stack_opt = match (item.path_opt(), &item.metadata) {
+                    (Some(path), ItemMetadata::Path { metadata, .. }) => {
+                        desktop_stack::stack_for(path, metadata, desktop_config.stack_by)
+                    }
+                    _ => None,
+                };
+                item.stack_opt = stack_opt;
+            }
+        }
+        items.extend(content);
     }
 
     if desktop_config.show_mounted_drives {
@@ -1345,6 +1443,15 @@ pub fn scan_desktop(
             selected: false,
             highlighted: false,
             overlaps_drag_rect: false,
+            pdf_preview_opt: None,
+            text_preview_opt: None,
+            #[cfg(feature = "playback")]
+            playback_opt: None,
+            media_opt: None,
+            photo_opt: None,
+            recent_opt: None,
+            stack_opt: None,
+            remote: false,
             dir_size: DirSize::NotDirectory,
             cut: false,
         });
@@ -1490,8 +1597,8 @@ impl Location {
 
     pub fn scan(&self, sizes: IconSizes) -> (Option<Item>, Vec<Item>) {
         let items = match self {
-            Self::Desktop(path, display, desktop_config) => {
-                scan_desktop(path, display, *desktop_config, sizes)
+            Self::Desktop(path, _display, desktop_config) => {
+                scan_desktop(path, *desktop_config, sizes)
             }
             Self::Path(path) => scan_path(path, sizes),
             Self::Search(..) => {
@@ -1575,9 +1682,14 @@ pub enum Command {
     OpenInNewWindow(PathBuf),
     OpenTrash,
     Preview(PreviewKind),
+    RemoveFromRecents(Vec<PathBuf>),
+    Rotate(Vec<PathBuf>, Rotation),
+    SetDesktopLayout(String, DesktopLayout),
     SetOpenWith(Mime, String),
+    #[cfg(unix)]
     SetPermissions(PathBuf, u32),
     SetSort(String, HeadingOptions, bool),
+    UpdateWatcher,
     WindowDrag,
     WindowToggleMaximize,
 }
@@ -1597,6 +1709,9 @@ pub enum Message {
     LocationMenuAction(LocationMenuAction),
     Drag(Option<Rectangle>),
     DragEnd,
+    DesktopAlignToGrid,
+    DesktopAutoArrange,
+    DesktopStack(Stack),
     EditLocation(Option<EditLocation>),
     EditLocationComplete(usize),
     EditLocationEnable,
@@ -1608,7 +1723,11 @@ pub enum Message {
     Gallery(bool),
     GalleryPrevious,
     GalleryNext,
+    GalleryRotate(Rotation),
+    GallerySlideshow(bool),
+    GallerySlideshowNext,
     GalleryToggle,
+    GalleryZoom(ImageZoom),
     GoNext,
     GoPrevious,
     ItemDown,
@@ -1617,7 +1736,17 @@ pub enum Message {
     ItemUp,
     Location(Location),
     LocationUp,
+    MediaInfo(PathBuf, MediaInfo),
     Open(Option<PathBuf>),
+    PdfPage(PathBuf, usize),
+    PdfPageRendered(PathBuf, Result<PdfPreview, String>),
+    Playback(PathBuf, PlaybackMessage),
+    PlaybackTick,
+    PhotoInfo(PathBuf, PhotoInfo),
+    RecentsApp(usize),
+    RecentsClear,
+    RecentsClearAge(usize),
+    RecentsKind(usize),
     Reload,
     RightClick(Option<Point>, Option<usize>),
     MiddleClick(usize),
@@ -1630,15 +1759,19 @@ pub enum Message {
     SelectFirst,
     SelectLast,
     SetOpenWith(Mime, String),
+    #[cfg(unix)]
     SetPermissions(PathBuf, u32),
     SetSort(HeadingOptions, bool),
     TabComplete(PathBuf, Vec<(String, PathBuf)>),
+    TextPreview(PathBuf, TextPreviewState),
+    TextPreviewLines(PathBuf, usize),
     Thumbnail(PathBuf, ItemThumbnail),
     ToggleSort(HeadingOptions),
     Drop(Option<(Location, ClipboardPaste)>),
     DndHover(Location),
     DndEnter(Location),
     DndLeave(Location),
+    DndMotion(Point),
     WindowDrag,
     WindowToggleMaximize,
     ZoomIn,
@@ -1742,7 +1875,6 @@ pub enum ItemThumbnail {
     NotImage,
     Image(widget::image::Handle, Option<(u32, u32)>),
     Svg(widget::svg::Handle),
-    Text(widget::text_editor::Content),
 }
 
 impl Clone for ItemThumbnail {
@@ -1751,15 +1883,65 @@ impl Clone for ItemThumbnail {
             Self::NotImage => Self::NotImage,
             Self::Image(handle, size_opt) => Self::Image(handle.clone(), *size_opt),
             Self::Svg(handle) => Self::Svg(handle.clone()),
-            // Content cannot be cloned simply
-            Self::Text(content) => {
-                Self::Text(widget::text_editor::Content::with_text(&content.text()))
-            }
         }
     }
 }
 
 impl ItemThumbnail {
+    /// Thumbnail from an image rendered by a built-in thumbnailer, saved to the cache if possible
+    fn from_rendered(
+        path: &Path,
+        dyn_img: DynamicImage,
+        thumbnail_cacher: Result<&ThumbnailCacher, &String>,
+    ) -> Option<Self> {
+        let (img_width, img_height) = (dyn_img.width(), dyn_img.height());
+        if let Ok(cacher) = thumbnail_cacher {
+            match cacher.update_with_image(dyn_img) {
+                Ok(thumb_path) => Some(Self::Image(
+                    widget::image::Handle::from_path(thumb_path),
+                    Some((img_width, img_height)),
+                )),
+                Err(err) => {
+                    log::warn!("cacher failed to save {}: {}", path.display(), err);
+                    None
+                }
+            }
+        } else {
+            let thumbnail = dyn_img.into_rgba8();
+            Some(Self::Image(
+                widget::image::Handle::from_rgba(
+                    thumbnail.width(),
+                    thumbnail.height(),
+                    thumbnail.into_raw(),
+                ),
+                Some((img_width, img_height)),
+            ))
+        }
+    }
+
+    /// Preview of a folder's contents for the grid view
+    fn folder(path: &Path, thumbnail_size: u32, max_size_mb: u64) -> Self {
+        let from_file = |file: PathBuf| {
+            if file.extension().is_some_and(|ext| ext == "svg") {
+                Self::Svg(widget::svg::Handle::from_path(file))
+            } else {
+                Self::Image(widget::image::Handle::from_path(file), None)
+            }
+        };
+        match FolderPreview::new(path, thumbnail_size, max_size_mb * 1000 * 1000) {
+            Some(FolderPreview::Icon(name)) => widget::icon::from_name(name)
+                .size(thumbnail_size as u16)
+                .path()
+                .map_or(Self::NotImage, from_file),
+            Some(FolderPreview::Svg(file)) => from_file(file),
+            Some(FolderPreview::Image(image)) => Self::Image(
+                widget::image::Handle::from_rgba(image.width(), image.height(), image.into_raw()),
+                None,
+            ),
+            None => Self::NotImage,
+        }
+    }
+
     pub fn new(
         path: &Path,
         metadata: ItemMetadata,
@@ -1898,7 +2080,7 @@ impl ItemThumbnail {
                             let max_ram = max_mem * 1000 * 1000 / jobs as u64;
                             limits.max_alloc = Some(max_ram);
                             reader.limits(limits);
-                            match reader.decode() {
+                            match photo::decode_oriented(reader) {
                                 Ok(reader) => Some(reader),
                                 Err(err) => {
                                     log::warn!("failed to decode {}: {}", path.display(), err);
@@ -1946,6 +2128,47 @@ impl ItemThumbnail {
             }
         }
 
+        // Then try built-in PDF thumbnailer
+        #[cfg(feature = "pdf")]
+        if mime == mime::APPLICATION_PDF && check_size("pdf", max_size_mb * 1000 * 1000) {
+            tried_supported_file = true;
+            match crate::pdf::render_page(path, 0, thumbnail_size) {
+                Ok(pdf_page) => {
+                    if let Some(thumbnail) =
+                        Self::from_rendered(path, pdf_page.image, thumbnail_cacher.as_ref())
+                    {
+                        return thumbnail;
+                    }
+                }
+                Err(err) => {
+                    log::warn!("failed to render PDF thumbnail: {}", err);
+                }
+            }
+        }
+
+        // Then try cover art embedded in audio files
+        if mime.type_() == mime::AUDIO {
+            match media::cover_art(path) {
+                Ok(Some(cover)) => {
+                    tried_supported_file = true;
+                    let cover = if cover.width().max(cover.height()) > thumbnail_size {
+                        cover.thumbnail(thumbnail_size, thumbnail_size)
+                    } else {
+                        cover
+                    };
+                    if let Some(thumbnail) =
+                        Self::from_rendered(path, cover, thumbnail_cacher.as_ref())
+                    {
+                        return thumbnail;
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    log::debug!("failed to load cover art: {}", err);
+                }
+            }
+        }
+
         // Try external thumbnailers.
         let thumbnail_dir = thumbnail_cacher
             .as_ref()
@@ -1981,17 +2204,6 @@ impl ItemThumbnail {
                     log::warn!("failed to read {}: {}", path.display(), err);
                 }
             }
-        } else if mime.type_() == mime::TEXT && check_size("text", 8 * 1000 * 1000) {
-            /*TODO: fix performance issues, widget::text_editor::Content::with_text forces all text to shape, which blocks rendering
-            match fs::read_to_string(&path) {
-                Ok(data) => {
-                    return ItemThumbnail::Text(widget::text_editor::Content::with_text(&data));
-                }
-                Err(err) => {
-                    log::warn!("failed to read {}: {}", path.display(), err);
-                }
-            }
-            */
         }
 
         // If we weren't able to create a thumbnail, but we should have
@@ -2124,6 +2336,101 @@ pub struct Item {
     pub cut: bool,
     pub overlaps_drag_rect: bool,
     pub dir_size: DirSize,
+    pub pdf_preview_opt: Option<PdfPreview>,
+    /// Lines of a text file, loaded when it is shown in the preview pane or the gallery
+    pub text_preview_opt: Option<TextPreviewState>,
+    /// Audio or video being played in the preview, the gallery or on hover
+    #[cfg(feature = "playback")]
+    pub playback_opt: Option<Playback>,
+    /// Audio or video information, loaded in the background for media files
+    pub media_opt: Option<MediaInfo>,
+    /// Camera information, loaded in the background for photos
+    pub photo_opt: Option<PhotoInfo>,
+    /// Pile the item is grouped into on the desktop
+    pub stack_opt: Option<Stack>,
+    /// When and by which applications the item was used, for items in Recents
+    pub recent_opt: Option<Recent>,
+    /// On a network filesystem or remote GVFS mount
+    pub remote: bool,
+}
+
+/// Page of a PDF document shown in the preview pane
+#[derive(Clone, Debug)]
+pub struct PdfPreview {
+    pub page: usize,
+    pub page_count: usize,
+    pub handle: widget::image::Handle,
+}
+
+#[derive(Clone, Debug)]
+pub enum TextPreviewState {
+    Loaded(Box<TextPreview>),
+    /// Binary data or a file that could not be read, the icon is shown instead
+    Unavailable,
+}
+
+impl TextPreviewState {
+    fn load(path: &Path, first_line: usize) -> Self {
+        match TextPreview::load(path, first_line) {
+            Ok(Some(preview)) => Self::Loaded(Box::new(preview)),
+            Ok(None) => {
+                log::debug!("not previewing binary file {}", path.display());
+                Self::Unavailable
+            }
+            Err(err) => {
+                log::warn!("failed to read {}: {}", path.display(), err);
+                Self::Unavailable
+            }
+        }
+    }
+}
+
+/// Playback of an audio or video file
+#[cfg(feature = "playback")]
+#[derive(Clone, Debug)]
+pub struct Playback {
+    player: Arc<crate::playback::Player>,
+    frame_opt: Option<widget::image::Handle>,
+    playing: bool,
+    position: Duration,
+    duration_opt: Option<Duration>,
+    /// Muted playback started by hovering over the item in the grid view
+    hover: bool,
+    /// Waiting for the frame at a new position while paused
+    waiting: bool,
+}
+
+#[cfg(feature = "playback")]
+impl Playback {
+    fn start(path: &Path, hover: bool) -> Option<Self> {
+        match crate::playback::Player::new(path, hover) {
+            Ok(player) => {
+                player.set_playing(true);
+                Some(Self {
+                    player: Arc::new(player),
+                    frame_opt: None,
+                    playing: true,
+                    position: Duration::ZERO,
+                    duration_opt: None,
+                    hover,
+                    waiting: false,
+                })
+            }
+            Err(err) => {
+                log::warn!("failed to play {}: {}", path.display(), err);
+                None
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlaybackMessage {
+    Play,
+    Pause,
+    Seek(Duration),
+    /// Pointer entered or left an item in the grid view
+    Hover(bool),
 }
 
 impl Item {
@@ -2136,8 +2443,143 @@ impl Item {
         self.location_opt.as_ref()?.path_opt()
     }
 
+    /// Original folder of a trashed item
+    fn original_parent_opt(&self) -> Option<&Path> {
+        match &self.metadata {
+            ItemMetadata::Trash { entry, .. } => Some(&entry.original_parent),
+            _ => None,
+        }
+    }
+
+    /// Folder shown under the name in search results and the trash
+    fn parent_caption(&self) -> String {
+        match self.original_parent_opt() {
+            Some(parent) => parent.display().to_string(),
+            None => match self.path_opt() {
+                Some(path) => path.display().to_string(),
+                None => String::new(),
+            },
+        }
+    }
+
+    /// Tag shown in a media column
+    fn media_text(&self, heading: HeadingOptions) -> Option<&str> {
+        let media = self.media_opt.as_ref()?;
+        match heading {
+            HeadingOptions::Artist => media.artist.as_deref(),
+            HeadingOptions::Album => media.album.as_deref(),
+            HeadingOptions::Title => media.title.as_deref(),
+            _ => None,
+        }
+    }
+
     pub fn can_gallery(&self) -> bool {
-        self.mime.type_() == mime::IMAGE || self.mime.type_() == mime::TEXT
+        self.mime.type_() == mime::IMAGE
+            || text_preview::is_text(&self.mime)
+            || (cfg!(feature = "playback") && media::is_playable(&self.mime))
+    }
+
+    /// Icon in the grid view, replaced by the video while it plays on hover
+    fn grid_icon(&self, size: u16) -> Element<'_, Message> {
+        #[cfg(feature = "playback")]
+        if let Some(handle) = self
+            .playback_opt
+            .as_ref()
+            .filter(|playback| playback.hover)
+            .and_then(|playback| playback.frame_opt.as_ref())
+        {
+            return widget::image(handle.clone())
+                .content_fit(ContentFit::Contain)
+                .width(Length::Fixed(size.into()))
+                .height(Length::Fixed(size.into()))
+                .into();
+        }
+        widget::icon::icon(self.icon_handle_grid.clone())
+            .content_fit(ContentFit::Contain)
+            .size(size)
+            .width(Length::Shrink)
+            .into()
+    }
+
+    /// Current video frame, or the preview for audio, above the playback controls
+    #[cfg(feature = "playback")]
+    fn playback_view(&self) -> Option<Element<'_, Message>> {
+        if !media::is_playable(&self.mime) {
+            return None;
+        }
+        let path = self.path_opt()?;
+        let picture: Element<'_, Message> = match self
+            .playback_opt
+            .as_ref()
+            .and_then(|playback| playback.frame_opt.as_ref())
+        {
+            Some(handle) => widget::image(handle.clone())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .content_fit(ContentFit::Contain)
+                .into(),
+            None => self.preview(),
+        };
+        Some(
+            widget::column::with_children([
+                widget::container(picture).center(Length::Fill).into(),
+                self.playback_controls(path),
+            ])
+            .spacing(theme::spacing().space_xs)
+            .into(),
+        )
+    }
+
+    #[cfg(feature = "playback")]
+    fn playback_controls(&self, path: &Path) -> Element<'_, Message> {
+        let spacing = theme::spacing();
+        // Hover playback is muted and looping, so it is not shown as playing
+        let playback_opt = self
+            .playback_opt
+            .as_ref()
+            .filter(|playback| !playback.hover);
+        let playing = playback_opt.is_some_and(|playback| playback.playing);
+        let position = playback_opt.map_or(Duration::ZERO, |playback| playback.position);
+        let duration_opt = playback_opt
+            .and_then(|playback| playback.duration_opt)
+            .or_else(|| self.media_opt.as_ref()?.duration);
+
+        let mut row = widget::row::with_capacity(3)
+            .align_y(Alignment::Center)
+            .spacing(spacing.space_xxs);
+        row = row.push(if playing {
+            widget::button::icon(widget::icon::from_name("media-playback-pause-symbolic")).on_press(
+                Message::Playback(path.to_path_buf(), PlaybackMessage::Pause),
+            )
+        } else {
+            widget::button::icon(widget::icon::from_name("media-playback-start-symbolic"))
+                .on_press(Message::Playback(path.to_path_buf(), PlaybackMessage::Play))
+        });
+        // Seeking needs a player, so the slider is only shown once playback started
+        if let (Some(_), Some(duration)) = (playback_opt, duration_opt) {
+            let seek_path = path.to_path_buf();
+            let end = duration.as_secs_f64();
+            row = row.push(
+                widget::slider(0.0..=end, position.as_secs_f64().min(end), move |secs| {
+                    Message::Playback(
+                        seek_path.clone(),
+                        PlaybackMessage::Seek(Duration::from_secs_f64(secs)),
+                    )
+                })
+                .step(0.1),
+            );
+        } else {
+            row = row.push(widget::horizontal_space());
+        }
+        row = row.push(widget::text::body(match duration_opt {
+            Some(duration) => format!(
+                "{} / {}",
+                media::format_duration(position),
+                media::format_duration(duration)
+            ),
+            None => media::format_duration(position),
+        }));
+        row.into()
     }
 
     fn preview(&self) -> Element<'_, Message> {
@@ -2147,6 +2589,24 @@ impl Item {
             .content_fit(ContentFit::Contain)
             .size(IconSizes::default().grid())
             .into();
+        if let Some(pdf_preview) = &self.pdf_preview_opt {
+            return widget::image(pdf_preview.handle.clone()).into();
+        }
+        #[cfg(feature = "playback")]
+        if let Some(handle) = self
+            .playback_opt
+            .as_ref()
+            .and_then(|playback| playback.frame_opt.as_ref())
+        {
+            return widget::image(handle.clone()).into();
+        }
+        if let Some(TextPreviewState::Loaded(preview)) = &self.text_preview_opt {
+            return text_preview_editor(preview)
+                .width(THUMBNAIL_SIZE as f32)
+                .height(Length::Fixed(THUMBNAIL_SIZE as f32))
+                .padding(spacing.space_xxs)
+                .into();
+        }
         match self
             .thumbnail_opt
             .as_ref()
@@ -2159,17 +2619,51 @@ impl Item {
                 widget::image(handle.clone()).into()
             }
             ItemThumbnail::Svg(handle) => widget::svg(handle.clone()).into(),
-            ItemThumbnail::Text(content) => widget::text_editor(content)
-                .class(cosmic::theme::iced::TextEditor::Custom(Box::new(
-                    text_editor_class,
-                )))
-                .width(THUMBNAIL_SIZE as f32)
-                .height(Length::Fixed(THUMBNAIL_SIZE as f32))
-                .padding(spacing.space_xxs)
-                .into(),
         }
     }
 
+    /// Buttons to move through a text file that does not fit in one preview
+    fn text_preview_controls(&self) -> Option<Element<'_, Message>> {
+        let Some(TextPreviewState::Loaded(preview)) = &self.text_preview_opt else {
+            return None;
+        };
+        if preview.first_line == 0 && !preview.more {
+            return None;
+        }
+        let path = self.path_opt()?;
+        let mut previous = widget::button::icon(widget::icon::from_name("go-previous-symbolic"));
+        if preview.first_line > 0 {
+            previous = previous.on_press(Message::TextPreviewLines(
+                path.clone(),
+                preview.first_line.saturating_sub(text_preview::MAX_LINES),
+            ));
+        }
+        let mut next = widget::button::icon(widget::icon::from_name("go-next-symbolic"));
+        if preview.more {
+            next = next.on_press(Message::TextPreviewLines(
+                path.clone(),
+                preview.first_line + preview.lines,
+            ));
+        }
+        Some(
+            widget::row::with_children([
+                widget::horizontal_space().into(),
+                previous.into(),
+                widget::text::body(fl!(
+                    "text-lines",
+                    first = preview.first_line + 1,
+                    last = preview.first_line + preview.lines
+                ))
+                .into(),
+                next.into(),
+                widget::horizontal_space().into(),
+            ])
+            .align_y(Alignment::Center)
+            .spacing(theme::spacing().space_xxxs)
+            .into(),
+        )
+    }
+
     pub fn preview_actions(&self) -> Element<'_, Message> {
         let mut row = widget::row::with_capacity(3)
             .align_y(Alignment::Center)
@@ -2212,6 +2706,51 @@ impl Item {
                 .max_height(THUMBNAIL_SIZE as f32),
         );
 
+        #[cfg(feature = "pdf")]
+        if self.mime == mime::APPLICATION_PDF {
+            if let Some(path) = self.path_opt() {
+                let (page, page_count_opt) = self
+                    .pdf_preview_opt
+                    .as_ref()
+                    .map_or((0, None), |pdf| (pdf.page, Some(pdf.page_count)));
+                let mut previous =
+                    widget::button::icon(widget::icon::from_name("go-previous-symbolic"));
+                if page > 0 {
+                    previous = previous.on_press(Message::PdfPage(path.clone(), page - 1));
+                }
+                let mut next = widget::button::icon(widget::icon::from_name("go-next-symbolic"));
+                if page_count_opt.is_none_or(|page_count| page + 1 < page_count) {
+                    next = next.on_press(Message::PdfPage(path.clone(), page + 1));
+                }
+                let label = match page_count_opt {
+                    Some(page_count) => fl!("pdf-page", page = page + 1, pages = page_count),
+                    None => fl!("pdf-page-unknown", page = page + 1),
+                };
+                column = column.push(
+                    widget::row::with_children([
+                        widget::horizontal_space().into(),
+                        previous.into(),
+                        widget::text::body(label).into(),
+                        next.into(),
+                        widget::horizontal_space().into(),
+                    ])
+                    .align_y(Alignment::Center)
+                    .spacing(space_xxxs),
+                );
+            }
+        }
+
+        if let Some(controls) = self.text_preview_controls() {
+            column = column.push(controls);
+        }
+
+        #[cfg(feature = "playback")]
+        if media::is_playable(&self.mime) {
+            if let Some(path) = self.path_opt() {
+                column = column.push(self.playback_controls(path));
+            }
+        }
+
         let mut details = widget::column().spacing(space_xxxs);
         details = details.push(widget::text::heading(self.name.clone()));
         details = details.push(widget::text::body(fl!(
@@ -2385,6 +2924,85 @@ impl Item {
                 details = details.push(widget::text::body(format!("{width}x{height}")));
             }
         }
+        if let Some(media) = &self.media_opt {
+            if let Some(title) = &media.title {
+                details = details.push(widget::text::body(fl!(
+                    "media-title",
+                    title = title.as_str()
+                )));
+            }
+            if let Some(artist) = &media.artist {
+                details = details.push(widget::text::body(fl!(
+                    "media-artist",
+                    artist = artist.as_str()
+                )));
+            }
+            if let Some(album) = &media.album {
+                details = details.push(widget::text::body(fl!(
+                    "media-album",
+                    album = album.as_str()
+                )));
+            }
+            if let Some(duration) = media.duration {
+                details = details.push(widget::text::body(fl!(
+                    "media-duration",
+                    duration = media::format_duration(duration)
+                )));
+            }
+            if let Some(codec) = &media.codec {
+                details = details.push(widget::text::body(fl!(
+                    "media-codec",
+                    codec = codec.as_str()
+                )));
+            }
+            if let Some(bitrate) = media.bitrate {
+                details = details.push(widget::text::body(fl!("media-bitrate", bitrate = bitrate)));
+            }
+            if let Some((width, height)) = media.resolution {
+                details = details.push(widget::text::body(format!("{width}x{height}")));
+            }
+        }
+        if let Some(photo) = &self.photo_opt {
+            if let Some(camera) = &photo.camera {
+                details = details.push(widget::text::body(fl!(
+                    "photo-camera",
+                    camera = camera.as_str()
+                )));
+            }
+            if let Some(lens) = &photo.lens {
+                details = details.push(widget::text::body(fl!("photo-lens", lens = lens.as_str())));
+            }
+            if let Some(exposure) = &photo.exposure {
+                details = details.push(widget::text::body(fl!(
+                    "photo-exposure",
+                    exposure = exposure.as_str()
+                )));
+            }
+            if let Some(time) = photo.date_taken {
+                details = details.push(widget::text::body(fl!(
+                    "photo-date-taken",
+                    date = format_time(
+                        time,
+                        &date_time_formatter(military_time),
+                        &time_formatter(military_time)
+                    )
+                    .to_string()
+                )));
+            }
+            if let Some((latitude, longitude)) = photo.gps {
+                details = details.push(widget::text::body(fl!(
+                    "photo-location",
+                    latitude = format!("{latitude:.6}"),
+                    longitude = format!("{longitude:.6}")
+                )));
+            }
+        }
+        if let Some(TextPreviewState::Loaded(preview)) = &self.text_preview_opt {
+            details = details.push(widget::text::body(fl!(
+                "text-encoding",
+                encoding = preview.encoding
+            )));
+        }
         column = column.push(details);
 
         if let Some(path) = self.path_opt() {
@@ -2457,6 +3075,11 @@ pub enum HeadingOptions {
     Modified,
     Size,
     TrashedOn,
+    Duration,
+    Artist,
+    Album,
+    Title,
+    DateTaken,
 }
 
 impl fmt::Display for HeadingOptions {
@@ -2466,6 +3089,11 @@ impl fmt::Display for HeadingOptions {
             Self::Modified => write!(f, "{}", fl!("modified")),
             Self::Size => write!(f, "{}", fl!("size")),
             Self::TrashedOn => write!(f, "{}", fl!("trashed-on")),
+            Self::Duration => write!(f, "{}", fl!("duration")),
+            Self::Artist => write!(f, "{}", fl!("artist")),
+            Self::Album => write!(f, "{}", fl!("album")),
+            Self::Title => write!(f, "{}", fl!("title")),
+            Self::DateTaken => write!(f, "{}", fl!("date-taken")),
         }
     }
 }
@@ -2477,6 +3105,11 @@ impl HeadingOptions {
             Self::Modified.to_string(),
             Self::Size.to_string(),
             Self::TrashedOn.to_string(),
+            Self::Duration.to_string(),
+            Self::Artist.to_string(),
+            Self::Album.to_string(),
+            Self::Title.to_string(),
+            Self::DateTaken.to_string(),
         ]
     }
 }
@@ -2518,6 +3151,14 @@ impl fmt::Debug for SearchContextWrapper {
     }
 }
 
+/// Entry of the grid view
+#[derive(Clone, Copy)]
+enum GridEntry<'a> {
+    Item(usize, &'a Item),
+    /// Pile of desktop files and the number of files in it
+    Stack(Stack, usize),
+}
+
 // TODO when creating items, pass <Arc<SelectedItems>> to each item
 // as a drag data, so that when dnd is initiated, they are all included
 pub struct Tab {
@@ -2529,6 +3170,10 @@ pub struct Tab {
     pub location_context_menu_index: Option<usize>,
     pub context_menu: Option<Point>,
     pub mode: Mode,
+    /// Pinned tabs are kept in front of the others and are not closed with the tab
+    pub pinned: bool,
+    /// Name of the tab group, whose tabs are kept next to each other in the tab bar
+    pub group_opt: Option<String>,
     pub scroll_opt: Option<AbsoluteOffset>,
     pub size_opt: Cell<Option<Size>>,
     pub viewport_opt: Option<Rectangle>,
@@ -2539,12 +3184,25 @@ pub struct Tab {
     pub history: Vec<Location>,
     pub config: TabConfig,
     pub thumb_config: ThumbCfg,
+    pub remote_thumbnails: RemoteThumbnailPolicy,
     pub sort_name: HeadingOptions,
     pub sort_direction: bool,
     pub gallery: bool,
+    gallery_zoom: ImageZoom,
+    slideshow: bool,
     pub(crate) parent_item_opt: Option<Item>,
     pub(crate) items_opt: Option<Vec<Item>>,
     pub dnd_hovered: Option<(Location, Instant)>,
+    dnd_point_opt: Option<Point>,
+    pub desktop_layout: DesktopLayout,
+    desktop_grid_opt: Cell<Option<desktop_layout::Grid>>,
+    expanded_stacks: BTreeSet<Stack>,
+    /// All files in Recents, before the filter and the limit are applied
+    recent_items: Vec<Item>,
+    /// Labels of the application filter, the first one shows files from all applications
+    recent_apps: Vec<String>,
+    recents_filter: RecentsFilter,
+    recents_clear: ClearOlderThan,
     pub(crate) scrollable_id: widget::Id,
     select_focus: Option<usize>,
     select_range: Option<(usize, usize)>,
@@ -2650,6 +3308,8 @@ impl Tab {
             location_context_menu_point: None,
             location_context_menu_index: None,
             mode: Mode::App,
+            pinned: false,
+            group_opt: None,
             scroll_opt: None,
             size_opt: Cell::new(None),
             viewport_opt: None,
@@ -2660,9 +3320,12 @@ impl Tab {
             history,
             config,
             thumb_config,
+            remote_thumbnails: RemoteThumbnailPolicy::default(),
             sort_name,
             sort_direction,
             gallery: false,
+            gallery_zoom: ImageZoom::Fit,
+            slideshow: false,
             parent_item_opt: None,
             items_opt: None,
             scrollable_id,
@@ -2670,6 +3333,14 @@ impl Tab {
             select_range: None,
             clicked: None,
             dnd_hovered: None,
+            dnd_point_opt: None,
+            desktop_layout: DesktopLayout::default(),
+            desktop_grid_opt: Cell::new(None),
+            expanded_stacks: BTreeSet::new(),
+            recent_items: Vec::new(),
+            recent_apps: Vec::new(),
+            recents_filter: RecentsFilter::default(),
+            recents_clear: ClearOlderThan::default(),
             selected_clicked: false,
             last_right_click: None,
             search_context: None,
@@ -2694,7 +3365,50 @@ impl Tab {
         self.items_opt.as_mut()
     }
 
-    pub fn set_items(&mut self, mut items: Vec<Item>) {
+    pub fn set_items(&mut self, items: Vec<Item>) {
+        if self.location == Location::Recents {
+            let mut apps: Vec<_> = items
+                .iter()
+                .filter_map(|item| item.recent_opt.as_ref())
+                .flat_map(|recent| recent.apps.iter().cloned())
+                .collect();
+            apps.sort_by(|a, b| LANGUAGE_SORTER.compare(a, b));
+            apps.dedup();
+            // Forget the application filter when it has no recent files left
+            if let Some(app) = &self.recents_filter.app_opt {
+                if !apps.contains(app) {
+                    self.recents_filter.app_opt = None;
+                }
+            }
+            self.recent_apps = Some(fl!("all-applications"))
+                .into_iter()
+                .chain(apps)
+                .collect();
+            self.recent_items = items;
+            self.filter_recents();
+        } else {
+            self.recent_items.clear();
+            self.replace_items(items);
+        }
+    }
+
+    /// Show recent files that match the filter, up to the limit
+    fn filter_recents(&mut self) {
+        let items = self
+            .recent_items
+            .iter()
+            .filter(|item| {
+                item.recent_opt
+                    .as_ref()
+                    .is_some_and(|recent| self.recents_filter.matches(&item.mime, recent))
+            })
+            .take(self.config.recents_limit)
+            .cloned()
+            .collect();
+        self.replace_items(items);
+    }
+
+    fn replace_items(&mut self, mut items: Vec<Item>) {
         let selected = self.selected_locations();
         for item in &mut items {
             item.selected = false;
@@ -2715,10 +3429,58 @@ impl Tab {
         }
     }
 
-    pub fn refresh_cut(&mut self, locations: &[PathBuf]) {
+    /// Folders shown with a preview of their contents, which need to be watched for changes
+    pub fn folder_preview_paths(&self) -> impl Iterator<Item = &PathBuf> {
+        self.items_opt
+            .iter()
+            .flatten()
+            .filter(|item| {
+                self.thumb_config.folder_previews
+                    && item.metadata.is_dir()
+                    && item.thumbnail_opt.is_some()
+            })
+            .filter_map(Item::path_opt)
+    }
+
+    pub fn has_folder_preview(&self, path: &Path) -> bool {
+        self.folder_preview_paths()
+            .any(|preview_path| preview_path == path)
+    }
+
+    /// Generate the preview of a folder again after its contents changed
+    pub fn invalidate_folder_preview(&mut self, path: &Path) {
         if let Some(ref mut items) = self.items_opt {
             for item in items.iter_mut() {
-                item.cut = false;
+                if item.metadata.is_dir() && item.path_opt().is_some_and(|x| x == path) {
+                    item.thumbnail_opt = None;
+                }
+            }
+        }
+    }
+
+    /// Generate all folder thumbnails again, after folder previews were enabled or disabled
+    pub fn reset_folder_previews(&mut self) {
+        let grid_icon_size = self.config.icon_sizes.grid();
+        if let Some(ref mut items) = self.items_opt {
+            for item in items.iter_mut() {
+                if !item.metadata.is_dir() || item.thumbnail_opt.is_none() {
+                    continue;
+                }
+                item.thumbnail_opt = None;
+                if let Some(icon) = item
+                    .path_opt()
+                    .map(|path| folder_icon(path, grid_icon_size))
+                {
+                    item.icon_handle_grid = icon;
+                }
+            }
+        }
+    }
+
+    pub fn refresh_cut(&mut self, locations: &[PathBuf]) {
+        if let Some(ref mut items) = self.items_opt {
+            for item in items.iter_mut() {
+                item.cut = false;
                 if let Some(location_path) = item.location_opt.as_ref().and_then(Location::path_opt)
                 {
                     if locations.contains(location_path) {
@@ -2971,6 +3733,176 @@ impl Tab {
         last
     }
 
+    /// Item shown in the gallery
+    fn gallery_item(&self) -> Option<&Item> {
+        self.items_opt.as_ref()?.get(self.select_focus?)
+    }
+
+    /// Start, pause or seek playback of an audio or video file
+    #[cfg(feature = "playback")]
+    fn playback(&mut self, path: &Path, playback_message: PlaybackMessage) {
+        let Some(ref mut items) = self.items_opt else {
+            return;
+        };
+        match playback_message {
+            // Only one file is played at a time
+            PlaybackMessage::Play => {
+                for item in items.iter_mut() {
+                    if item.path_opt().is_some_and(|item_path| item_path != path) {
+                        item.playback_opt = None;
+                    }
+                }
+            }
+            // Moving between items stops the previous hover playback
+            PlaybackMessage::Hover(true) => {
+                for item in items.iter_mut() {
+                    if item
+                        .playback_opt
+                        .as_ref()
+                        .is_some_and(|playback| playback.hover)
+                        && item.path_opt().is_some_and(|item_path| item_path != path)
+                    {
+                        item.playback_opt = None;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        let Some(item) = items
+            .iter_mut()
+            .find(|item| item.path_opt().is_some_and(|item_path| item_path == path))
+        else {
+            return;
+        };
+        match playback_message {
+            PlaybackMessage::Play => match &mut item.playback_opt {
+                Some(playback) => {
+                    if playback.hover {
+                        playback.hover = false;
+                        playback.player.set_muted(false);
+                    }
+                    playback.player.set_playing(true);
+                    playback.playing = true;
+                }
+                None => {
+                    item.playback_opt = Playback::start(path, false);
+                }
+            },
+            PlaybackMessage::Pause => {
+                if let Some(playback) = &mut item.playback_opt {
+                    playback.player.set_playing(false);
+                    playback.playing = false;
+                }
+            }
+            PlaybackMessage::Seek(position) => {
+                if let Some(playback) = &mut item.playback_opt {
+                    playback.player.seek(position);
+                    playback.position = position;
+                    playback.waiting = !playback.playing && item.mime.type_() == mime::VIDEO;
+                }
+            }
+            PlaybackMessage::Hover(true) => {
+                if item.playback_opt.is_none() {
+                    item.playback_opt = Playback::start(path, true);
+                }
+            }
+            PlaybackMessage::Hover(false) => {
+                if item
+                    .playback_opt
+                    .as_ref()
+                    .is_some_and(|playback| playback.hover)
+                {
+                    item.playback_opt = None;
+                }
+            }
+        }
+    }
+
+    /// Play videos muted while the pointer is over them in the grid view
+    #[cfg(feature = "playback")]
+    fn hover_playback(&mut self, i: usize, hover: bool) {
+        if hover && !(self.config.hover_playback && self.config.view == View::Grid) {
+            return;
+        }
+        // Remote files are not played, as that could transfer a lot of data
+        let Some(path) = self
+            .items_opt
+            .as_ref()
+            .and_then(|items| items.get(i))
+            .filter(|item| item.mime.type_() == mime::VIDEO && !item.remote)
+            .and_then(Item::path_opt)
+            .cloned()
+        else {
+            return;
+        };
+        self.playback(&path, PlaybackMessage::Hover(hover));
+    }
+
+    /// Show the latest frames and positions of playing files
+    #[cfg(feature = "playback")]
+    fn playback_tick(&mut self) {
+        let Some(ref mut items) = self.items_opt else {
+            return;
+        };
+        for item in items.iter_mut() {
+            let Some(playback) = &mut item.playback_opt else {
+                continue;
+            };
+            // Playback stops once the file is no longer shown
+            if !playback.hover && !item.selected {
+                item.playback_opt = None;
+                continue;
+            }
+            if let Some(frame) = playback.player.take_frame() {
+                playback.frame_opt = Some(widget::image::Handle::from_rgba(
+                    frame.width,
+                    frame.height,
+                    frame.pixels,
+                ));
+                playback.waiting = false;
+            }
+            if let Some(position) = playback.player.position() {
+                playback.position = position;
+            }
+            if playback.duration_opt.is_none() {
+                playback.duration_opt = playback.player.duration();
+            }
+            match playback.player.poll() {
+                Some(crate::playback::Event::EndOfStream) => {
+                    // Hover playback loops, other playback stops at the end
+                    playback.player.seek(Duration::ZERO);
+                    playback.position = Duration::ZERO;
+                    if !playback.hover {
+                        playback.player.set_playing(false);
+                        playback.playing = false;
+                    }
+                }
+                Some(crate::playback::Event::Error(err)) => {
+                    if let Some(path) = item.path_opt() {
+                        log::warn!("failed to play {}: {}", path.display(), err);
+                    }
+                    item.playback_opt = None;
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Load images again after their files were changed, like after rotating them
+    pub fn reload_images(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            self.large_image_manager.invalidate(path);
+        }
+        if let Some(ref mut items) = self.items_opt {
+            for item in items.iter_mut() {
+                if item.path_opt().is_some_and(|path| paths.contains(path)) {
+                    item.thumbnail_opt = None;
+                }
+            }
+        }
+    }
+
     fn trigger_async_decode(&mut self) -> Vec<Command> {
         // Only trigger decode in gallery mode for the currently selected image
         if !self.gallery {
@@ -2989,28 +3921,42 @@ impl Tab {
             return Vec::new();
         };
 
+        // Videos have image thumbnails but are played instead
+        if item.mime.type_() != mime::IMAGE {
+            return Vec::new();
+        }
+
         let Some(ItemThumbnail::Image(_, original_dims)) = &item.thumbnail_opt else {
             return Vec::new();
         };
 
+        let Some(path) = item.path_opt() else {
+            return Vec::new();
+        };
+
+        // Images changed on disk are decoded even if small, as the renderer caches images loaded
+        // from a path
         if let Some((w, h)) = original_dims {
-            if !should_use_tiling(*w, *h) {
+            if !should_use_tiling(*w, *h) && !self.large_image_manager.is_modified(path) {
                 return Vec::new();
             }
         }
 
-        let Some(path) = item.path_opt() else {
-            return Vec::new();
-        };
-
         // Clone path to avoid borrow checker issues
         let path = path.to_path_buf();
 
-        // Get display size for adaptive resolution
-        let display_dimensions = self
-            .size_opt
-            .get()
-            .map(|size| (size.width as u32, size.height as u32));
+        // Get display size for adaptive resolution, which is the size of the image when zoomed in
+        let display_dimensions = match (self.gallery_zoom, original_dims) {
+            (ImageZoom::Scale { scale, .. }, Some((w, h))) => {
+                // More pixels than the image has are never needed
+                let scale = scale.min(1.0);
+                Some(((*w as f32 * scale) as u32, (*h as f32 * scale) as u32))
+            }
+            _ => self
+                .size_opt
+                .get()
+                .map(|size| (size.width as u32, size.height as u32)),
+        };
 
         // Try to decode the image using LargeImageManager with adaptive resolution
         let (should_decode, target_dimensions, generation) = self
@@ -3142,6 +4088,22 @@ impl Tab {
                 self.clicked = None;
                 self.watch_drag = true;
             }
+            Message::DesktopAlignToGrid => {
+                if let Some(grid) = self.desktop_grid_opt.get() {
+                    self.desktop_layout.positions =
+                        desktop_layout::align(&grid, &self.desktop_layout.positions);
+                    commands.extend(self.desktop_layout_command());
+                }
+            }
+            Message::DesktopAutoArrange => {
+                self.desktop_layout.positions.clear();
+                commands.extend(self.desktop_layout_command());
+            }
+            Message::DesktopStack(stack) => {
+                if !self.expanded_stacks.remove(&stack) {
+                    self.expanded_stacks.insert(stack);
+                }
+            }
             Message::DoubleClick(click_i_opt) => {
                 if let Some(clicked_item) = self
                     .items_opt
@@ -3281,12 +4243,16 @@ impl Tab {
                 let view = self.config.view;
                 let military_time_changed = self.config.military_time != config.military_time;
                 let show_hidden_changed = self.config.show_hidden != config.show_hidden;
+                let recents_limit_changed = self.config.recents_limit != config.recents_limit;
                 self.config = config;
                 self.config.view = view;
                 if military_time_changed {
                     self.date_time_formatter = date_time_formatter(self.config.military_time);
                     self.time_formatter = time_formatter(self.config.military_time);
                 }
+                if recents_limit_changed && self.location == Location::Recents {
+                    self.filter_recents();
+                }
                 if show_hidden_changed {
                     if let Location::Search(path, term, ..) = &self.location {
                         cd = Some(Location::Search(
@@ -3451,9 +4417,12 @@ impl Tab {
             }
             Message::Gallery(gallery) => {
                 self.gallery = gallery;
+                self.gallery_zoom = ImageZoom::Fit;
 
                 if gallery {
                     commands.extend(self.trigger_async_decode());
+                } else {
+                    self.slideshow = false;
                 }
             }
             Message::GalleryPrevious | Message::GalleryNext => {
@@ -3482,6 +4451,7 @@ impl Tab {
                 if let Some((row, col)) = pos_opt {
                     // Should mod_shift be available?
                     self.select_position(row, col, mod_shift);
+                    self.gallery_zoom = ImageZoom::Fit;
 
                     commands.extend(self.trigger_async_decode());
                 }
@@ -3494,20 +4464,48 @@ impl Tab {
                     commands.push(Command::Iced(widget::button::focus(id).into()));
                 }
             }
+            Message::GalleryRotate(rotation) => {
+                if let Some(path) = self.gallery_item().and_then(Item::path_opt) {
+                    commands.push(Command::Rotate(vec![path.clone()], rotation));
+                }
+            }
+            Message::GallerySlideshow(slideshow) => {
+                self.slideshow = slideshow;
+            }
+            Message::GallerySlideshowNext => {
+                let focus = self.select_focus;
+                commands.append(&mut self.update(Message::GalleryNext, modifiers));
+                if self.select_focus == focus {
+                    // Start over after the last image
+                    self.select_focus = None;
+                    commands.append(&mut self.update(Message::GalleryNext, modifiers));
+                    if self.select_focus.is_none() {
+                        self.select_focus = focus;
+                    }
+                }
+            }
             Message::GalleryToggle => {
                 if let Some(indices) = self.column_sort() {
                     for (_, item) in &indices {
                         if item.selected && item.can_gallery() {
                             self.gallery = !self.gallery;
+                            self.gallery_zoom = ImageZoom::Fit;
 
                             if self.gallery {
                                 commands.extend(self.trigger_async_decode());
+                            } else {
+                                self.slideshow = false;
                             }
                             break;
                         }
                     }
                 }
             }
+            Message::GalleryZoom(zoom) => {
+                self.gallery_zoom = zoom;
+                // Zooming in on a large image needs it at a higher resolution
+                commands.extend(self.trigger_async_decode());
+            }
             Message::GoNext => {
                 if let Some(history_i) = self.history_i.checked_add(1) {
                     if let Some(location) = self.history.get(history_i) {
@@ -3738,6 +4736,42 @@ impl Tab {
                     }
                 }
             }
+            Message::RecentsApp(index) => {
+                self.recents_filter.app_opt = if index == 0 {
+                    None
+                } else {
+                    self.recent_apps.get(index).cloned()
+                };
+                self.filter_recents();
+            }
+            Message::RecentsClear => {
+                // Clears all recent files of that age, including those hidden by the filter
+                let cutoff = self.recents_clear.cutoff(Utc::now());
+                let paths: Vec<_> = self
+                    .recent_items
+                    .iter()
+                    .filter(|item| {
+                        item.recent_opt
+                            .as_ref()
+                            .is_some_and(|recent| recent.used < cutoff)
+                    })
+                    .filter_map(|item| item.path_opt().cloned())
+                    .collect();
+                if !paths.is_empty() {
+                    commands.push(Command::RemoveFromRecents(paths));
+                }
+            }
+            Message::RecentsClearAge(index) => {
+                if let Some(age) = ClearOlderThan::ALL.get(index) {
+                    self.recents_clear = *age;
+                }
+            }
+            Message::RecentsKind(index) => {
+                self.recents_filter.kind_opt = index
+                    .checked_sub(1)
+                    .and_then(|index| RecentKind::ALL.get(index).copied());
+                self.filter_recents();
+            }
             Message::Reload => {
                 //TODO: support keeping selected locations without paths
                 let selected_paths = self
@@ -3803,12 +4837,16 @@ impl Tab {
                 if let Some(item) = self.items_opt.as_mut().and_then(|f| f.get_mut(i)) {
                     item.highlighted = false;
                 }
+                #[cfg(feature = "playback")]
+                self.hover_playback(i, false);
             }
             Message::HighlightActivate(i) => {
                 self.watch_drag = true;
                 if let Some(item) = self.items_opt.as_mut().and_then(|f| f.get_mut(i)) {
                     item.highlighted = true;
                 }
+                #[cfg(feature = "playback")]
+                self.hover_playback(i, true);
             }
             Message::Resize(viewport) => {
                 // Scroll to ensure focused item still in view
@@ -3934,6 +4972,7 @@ impl Tab {
             Message::SetOpenWith(mime, id) => {
                 commands.push(Command::SetOpenWith(mime, id));
             }
+            #[cfg(unix)]
             Message::SetPermissions(path, mode) => {
                 commands.push(Command::SetPermissions(path, mode));
             }
@@ -3941,7 +4980,9 @@ impl Tab {
                 if !matches!(self.location, Location::Search(..)) {
                     self.sort_name = heading_option;
                     self.sort_direction = dir;
-                    if !matches!(self.location, Location::Desktop(..)) {
+                    if matches!(self.location, Location::Desktop(..)) {
+                        commands.extend(self.desktop_sort(heading_option, dir));
+                    } else {
                         commands.push(Command::SetSort(
                             self.location.normalize().to_string(),
                             heading_option,
@@ -3960,11 +5001,63 @@ impl Tab {
                     }
                 }
             }
+            Message::TextPreview(path, text_preview) => {
+                let location = Location::Path(path);
+                if let Some(ref mut items) = self.items_opt {
+                    if let Some(item) = items
+                        .iter_mut()
+                        .find(|item| item.location_opt.as_ref() == Some(&location))
+                    {
+                        item.text_preview_opt = Some(text_preview);
+                    }
+                }
+            }
+            Message::TextPreviewLines(path, first_line) => {
+                commands.push(Command::Iced(
+                    cosmic::iced::Task::perform(
+                        async move {
+                            let load_path = path.clone();
+                            let text_preview = tokio::task::spawn_blocking(move || {
+                                TextPreviewState::load(&load_path, first_line)
+                            })
+                            .await
+                            .unwrap_or(TextPreviewState::Unavailable);
+                            (path, text_preview)
+                        },
+                        |(path, text_preview)| Message::TextPreview(path, text_preview),
+                    )
+                    .into(),
+                ));
+            }
             Message::Thumbnail(path, thumbnail) => {
+                let folder_previews = self.thumb_config.folder_previews;
+                let grid_icon_size = self.config.icon_sizes.grid();
+                let location = Location::Path(path);
                 if let Some(ref mut items) = self.items_opt {
-                    let location = Location::Path(path);
                     for item in items.iter_mut() {
                         if item.location_opt.as_ref() == Some(&location) {
+                            if folder_previews && item.metadata.is_dir() {
+                                // Folder previews are only shown in the grid view
+                                let Location::Path(path) = &location else {
+                                    break;
+                                };
+                                item.icon_handle_grid = match &thumbnail {
+                                    ItemThumbnail::Image(handle, _) => widget::icon::Handle {
+                                        symbolic: false,
+                                        data: widget::icon::Data::Image(handle.clone()),
+                                    },
+                                    ItemThumbnail::Svg(handle) => widget::icon::Handle {
+                                        symbolic: false,
+                                        data: widget::icon::Data::Svg(handle.clone()),
+                                    },
+                                    ItemThumbnail::NotImage => folder_icon(path, grid_icon_size),
+                                };
+                                item.thumbnail_opt = Some(thumbnail);
+                                // Changes to the folder contents have to be watched
+                                commands.push(Command::UpdateWatcher);
+                                break;
+                            }
+
                             let handle_opt = match &thumbnail {
                                 ItemThumbnail::NotImage => None,
                                 ItemThumbnail::Image(handle, _) => Some(widget::icon::Handle {
@@ -3975,8 +5068,6 @@ impl Tab {
                                     symbolic: false,
                                     data: widget::icon::Data::Svg(handle.clone()),
                                 }),
-                                //TODO: text thumbnails?
-                                ItemThumbnail::Text(_text) => None,
                             };
                             if let Some(handle) = handle_opt {
                                 item.icon_handle_grid.clone_from(&handle);
@@ -3988,6 +5079,37 @@ impl Tab {
                         }
                     }
                 }
+                // The gallery waits for the thumbnail to know the size of the image
+                if self.gallery
+                    && self
+                        .gallery_item()
+                        .and_then(|item| item.location_opt.as_ref())
+                        == Some(&location)
+                {
+                    commands.extend(self.trigger_async_decode());
+                }
+            }
+            Message::MediaInfo(path, media_info) => {
+                if let Some(ref mut items) = self.items_opt {
+                    let location = Location::Path(path);
+                    if let Some(item) = items
+                        .iter_mut()
+                        .find(|item| item.location_opt.as_ref() == Some(&location))
+                    {
+                        item.media_opt = Some(media_info);
+                    }
+                }
+            }
+            Message::PhotoInfo(path, photo_info) => {
+                if let Some(ref mut items) = self.items_opt {
+                    let location = Location::Path(path);
+                    if let Some(item) = items
+                        .iter_mut()
+                        .find(|item| item.location_opt.as_ref() == Some(&location))
+                    {
+                        item.photo_opt = Some(photo_info);
+                    }
+                }
             }
             Message::ImageDecoded(path, width, height, pixels, display_size, generation) => {
                 // Create handle from pre-decoded RGBA data (fast!)
@@ -4006,11 +5128,16 @@ impl Tab {
                     let heading_sort = if self.sort_name == heading_option {
                         !self.sort_direction
                     } else {
-                        // Default modified to descending, and others to ascending.
-                        heading_option != HeadingOptions::Modified
+                        // Default dates to descending, and others to ascending.
+                        !matches!(
+                            heading_option,
+                            HeadingOptions::Modified | HeadingOptions::DateTaken
+                        )
                     };
 
-                    if !matches!(self.location, Location::Desktop(..)) {
+                    if matches!(self.location, Location::Desktop(..)) {
+                        commands.extend(self.desktop_sort(heading_option, heading_sort));
+                    } else {
                         commands.push(Command::SetSort(
                             self.location.normalize().to_string(),
                             heading_option,
@@ -4024,16 +5151,24 @@ impl Tab {
             }
             Message::Drop(Some((to, mut from))) => {
                 self.dnd_hovered = None;
+                let dnd_point_opt = self.dnd_point_opt.take();
                 match to {
                     Location::Desktop(to, ..)
                     | Location::Path(to)
                     | Location::Network(_, _, Some(to)) => {
+                        let dropped = from.paths.clone();
                         if let Ok(entries) = fs::read_dir(&to) {
                             for i in entries.into_iter().filter_map(Result::ok) {
                                 let i = i.path();
                                 from.paths.retain(|p| &i != p);
                                 if from.paths.is_empty() {
                                     log::info!("All dropped files already in target directory.");
+                                    // Icons dropped on their own desktop are moved there
+                                    if let Some(point) = dnd_point_opt {
+                                        if matches!(self.location, Location::Desktop(..)) {
+                                            commands.extend(self.desktop_move(&dropped, point));
+                                        }
+                                    }
                                     return commands;
                                 }
                             }
@@ -4077,6 +5212,9 @@ impl Tab {
                     self.dnd_hovered = None;
                 }
             }
+            Message::DndMotion(point) => {
+                self.dnd_point_opt = Some(point);
+            }
             Message::WindowDrag => {
                 commands.push(Command::WindowDrag);
             }
@@ -4089,6 +5227,67 @@ impl Tab {
             Message::ZoomOut => {
                 commands.push(Command::Action(Action::ZoomOut));
             }
+            #[cfg(feature = "pdf")]
+            Message::PdfPage(path, page) => {
+                commands.push(Command::Iced(
+                    cosmic::iced::Task::perform(
+                        async move {
+                            let render_path = path.clone();
+                            let result = tokio::task::spawn_blocking(move || {
+                                crate::pdf::render_page(&render_path, page, THUMBNAIL_SIZE * 2)
+                            })
+                            .await
+                            .map_err(|err| err.to_string())
+                            .and_then(|result| result)
+                            .map(|pdf_page| {
+                                let rgba = pdf_page.image.into_rgba8();
+                                PdfPreview {
+                                    page: pdf_page.page,
+                                    page_count: pdf_page.page_count,
+                                    handle: widget::image::Handle::from_rgba(
+                                        rgba.width(),
+                                        rgba.height(),
+                                        rgba.into_raw(),
+                                    ),
+                                }
+                            });
+                            (path, result)
+                        },
+                        |(path, result)| Message::PdfPageRendered(path, result),
+                    )
+                    .into(),
+                ));
+            }
+            #[cfg(not(feature = "pdf"))]
+            Message::PdfPage(..) => {}
+            #[cfg(feature = "playback")]
+            Message::Playback(path, playback_message) => {
+                self.playback(&path, playback_message);
+            }
+            #[cfg(not(feature = "playback"))]
+            Message::Playback(..) => {}
+            #[cfg(feature = "playback")]
+            Message::PlaybackTick => {
+                self.playback_tick();
+            }
+            #[cfg(not(feature = "playback"))]
+            Message::PlaybackTick => {}
+            Message::PdfPageRendered(path, result) => match result {
+                Ok(pdf_preview) => {
+                    let location = Location::Path(path);
+                    if let Some(ref mut items) = self.items_opt {
+                        if let Some(item) = items
+                            .iter_mut()
+                            .find(|item| item.location_opt.as_ref() == Some(&location))
+                        {
+                            item.pdf_preview_opt = Some(pdf_preview);
+                        }
+                    }
+                }
+                Err(err) => {
+                    log::warn!("failed to render PDF page of {}: {}", path.display(), err);
+                }
+            },
             Message::DirectorySize(path, dir_size) => {
                 let location = Location::Path(path);
                 if let Some(ref mut item) = self.parent_item_opt {
@@ -4182,6 +5381,46 @@ impl Tab {
         }
     }
 
+    /// Media columns are shown for folders with audio or video files, if there is room for them
+    fn media_columns(&self, width: f32) -> bool {
+        //TODO: allow resizing?
+        let min_width = 300.0 + 200.0 + 100.0 + 3.0 * MEDIA_TEXT_WIDTH + MEDIA_DURATION_WIDTH;
+        width >= min_width
+            && !matches!(self.location, Location::Trash | Location::Search(..))
+            && self
+                .items_opt
+                .as_ref()
+                .is_some_and(|items| items.iter().any(|item| media::is_media(&item.mime)))
+    }
+
+    /// Returns true if any photo in this tab has a known date taken
+    pub fn has_date_taken(&self) -> bool {
+        self.items_opt.as_ref().is_some_and(|items| {
+            items.iter().any(|item| {
+                item.photo_opt
+                    .as_ref()
+                    .is_some_and(|photo| photo.date_taken.is_some())
+            })
+        })
+    }
+
+    /// The date taken column is shown for folders with dated photos, if there is room for it
+    fn date_taken_column(&self, width: f32) -> bool {
+        //TODO: allow resizing?
+        let mut min_width = 300.0 + 200.0 + 100.0 + 200.0;
+        if self.media_columns(width) {
+            min_width += 3.0 * MEDIA_TEXT_WIDTH + MEDIA_DURATION_WIDTH;
+        }
+        width >= min_width
+            && !matches!(self.location, Location::Trash | Location::Search(..))
+            && self.has_date_taken()
+    }
+
+    /// Whether trashed items are grouped by their original folder
+    fn trash_groups(&self) -> bool {
+        self.location == Location::Trash && self.config.trash_by_origin
+    }
+
     fn column_sort(&self) -> Option<Vec<(usize, &Item)>> {
         let check_reverse = |ord: Ordering, sort: bool| {
             if sort { ord } else { ord.reverse() }
@@ -4248,9 +5487,14 @@ impl Tab {
                 }
             }),
             HeadingOptions::Modified => {
+                // Recent files are sorted by when they were last used
+                let modified = |item: &Item| match &item.recent_opt {
+                    Some(recent) => Some(SystemTime::from(recent.used)),
+                    None => item.metadata.modified(),
+                };
                 items.sort_by(|a, b| {
-                    let a_modified = a.1.metadata.modified();
-                    let b_modified = b.1.metadata.modified();
+                    let a_modified = modified(a.1);
+                    let b_modified = modified(b.1);
                     if folders_first {
                         match (a.1.metadata.is_dir(), b.1.metadata.is_dir()) {
                             (true, false) => Ordering::Less,
@@ -4262,6 +5506,59 @@ impl Tab {
                     }
                 });
             }
+            HeadingOptions::Duration
+            | HeadingOptions::Artist
+            | HeadingOptions::Album
+            | HeadingOptions::Title => {
+                items.sort_by(|a, b| {
+                    if folders_first {
+                        match (a.1.metadata.is_dir(), b.1.metadata.is_dir()) {
+                            (true, false) => return Ordering::Less,
+                            (false, true) => return Ordering::Greater,
+                            _ => {}
+                        }
+                    }
+                    let ordering = if sort_name == HeadingOptions::Duration {
+                        let duration =
+                            |x: &Item| x.media_opt.as_ref().and_then(|media| media.duration);
+                        compare_missing_last(
+                            duration(a.1),
+                            duration(b.1),
+                            sort_direction,
+                            |a, b| a.cmp(&b),
+                        )
+                    } else {
+                        compare_missing_last(
+                            a.1.media_text(sort_name),
+                            b.1.media_text(sort_name),
+                            sort_direction,
+                            |a, b| LANGUAGE_SORTER.compare(a, b),
+                        )
+                    };
+                    ordering
+                        .then_with(|| LANGUAGE_SORTER.compare(&a.1.display_name, &b.1.display_name))
+                });
+            }
+            HeadingOptions::DateTaken => {
+                items.sort_by(|a, b| {
+                    if folders_first {
+                        match (a.1.metadata.is_dir(), b.1.metadata.is_dir()) {
+                            (true, false) => return Ordering::Less,
+                            (false, true) => return Ordering::Greater,
+                            _ => {}
+                        }
+                    }
+                    let date_taken =
+                        |x: &Item| x.photo_opt.as_ref().and_then(|photo| photo.date_taken);
+                    compare_missing_last(
+                        date_taken(a.1),
+                        date_taken(b.1),
+                        sort_direction,
+                        |a, b| a.cmp(&b),
+                    )
+                    .then_with(|| LANGUAGE_SORTER.compare(&a.1.display_name, &b.1.display_name))
+                });
+            }
             HeadingOptions::TrashedOn => {
                 let time_deleted = |x: &Item| match &x.metadata {
                     ItemMetadata::Trash { entry, .. } => Some(entry.time_deleted),
@@ -4278,14 +5575,73 @@ impl Tab {
                             _ => check_reverse(a_time_deleted.cmp(&b_time_deleted), sort_direction),
                         }
                     } else {
-                        check_reverse(b_time_deleted.cmp(&a_time_deleted), sort_direction)
+                        check_reverse(a_time_deleted.cmp(&b_time_deleted), sort_direction)
                     }
                 });
             }
         }
+        if self.trash_groups() {
+            // Stable sort keeps the chosen order inside each original folder
+            items.sort_by(|a, b| a.1.original_parent_opt().cmp(&b.1.original_parent_opt()));
+        }
         Some(items)
     }
 
+    /// Command that saves the icon arrangement, if this is a desktop
+    fn desktop_layout_command(&self) -> Option<Command> {
+        match &self.location {
+            Location::Desktop(_, display, _) => Some(Command::SetDesktopLayout(
+                display.clone(),
+                self.desktop_layout.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Sorting the desktop moves all icons back into the grid
+    fn desktop_sort(&mut self, heading_option: HeadingOptions, dir: bool) -> Option<Command> {
+        self.desktop_layout.positions.clear();
+        self.desktop_layout.sort = Some((heading_option, dir));
+        self.desktop_layout_command()
+    }
+
+    /// Move the desktop icons of `paths` so that the top left one is at `point`, keeping their
+    /// places relative to each other. All other icons are pinned where they are shown, so that
+    /// they do not fill the cells that were freed.
+    fn desktop_move(&mut self, paths: &[PathBuf], point: Point) -> Option<Command> {
+        let items = self.items_opt.as_ref()?;
+        let moved: Vec<_> = items
+            .iter()
+            .filter(|item| item.path_opt().is_some_and(|path| paths.contains(path)))
+            .filter_map(|item| Some((item.name.clone(), item.rect_opt.get()?)))
+            .collect();
+        let left = moved.iter().map(|(_, rect)| rect.x).reduce(f32::min)?;
+        let top = moved.iter().map(|(_, rect)| rect.y).reduce(f32::min)?;
+
+        let mut positions = BTreeMap::new();
+        for item in items {
+            if let Some(rect) = item.rect_opt.get() {
+                positions.insert(item.name.clone(), (rect.x as i32, rect.y as i32));
+            } else if let Some(position) = self.desktop_layout.positions.get(&item.name) {
+                // Hidden icons keep their place for when they are shown again
+                positions.insert(item.name.clone(), *position);
+            }
+        }
+        let grid_opt = self.desktop_grid_opt.get();
+        for (name, rect) in moved {
+            let mut position = (
+                (point.x + rect.x - left).round() as i32,
+                (point.y + rect.y - top).round() as i32,
+            );
+            if let Some(grid) = &grid_opt {
+                position = grid.clamp(position);
+            }
+            positions.insert(name, position);
+        }
+        self.desktop_layout.positions = positions;
+        self.desktop_layout_command()
+    }
+
     fn dnd_dest<'a>(
         &self,
         location: &Location,
@@ -4345,98 +5701,122 @@ impl Tab {
         //TODO: display error messages when image not found?
         let mut name_opt = None;
         let mut element_opt: Option<Element<Message>> = None;
+        let mut zoomable = false;
+        let mut rotatable = false;
         if let Some(index) = self.select_focus {
             if let Some(items) = &self.items_opt {
                 if let Some(item) = items.get(index) {
                     name_opt = Some(widget::text::heading(&item.display_name));
-                    match item
-                        .thumbnail_opt
-                        .as_ref()
-                        .unwrap_or(&ItemThumbnail::NotImage)
+                    rotatable = item.mime == mime::IMAGE_JPEG
+                        && matches!(item.metadata, ItemMetadata::Path { .. });
+                    #[cfg(feature = "playback")]
                     {
-                        ItemThumbnail::NotImage => {}
-                        ItemThumbnail::Image(handle, original_dims) => {
-                            // Determine which image to show based on async decode state
-                            let mut is_loading = false;
-                            let mut error_msg_opt = None;
-                            let image_handle = if let Some(path) = item.path_opt() {
-                                if let Some(error_msg) = self.large_image_manager.get_error(path) {
-                                    error_msg_opt = Some(error_msg.clone());
-                                    handle.clone()
-                                } else if self.large_image_manager.is_decoding(path) {
-                                    // Currently decoding (initial or re-decode) --> show cached/thumbnail with loading indicator
-                                    is_loading = true;
-                                    // Use decoded handle if available (re-decode), otherwise thumbnail (initial decode)
-                                    self.large_image_manager
-                                        .get_decoded(path)
-                                        .cloned()
-                                        .unwrap_or_else(|| handle.clone())
-                                } else if let Some(decoded_handle) =
-                                    self.large_image_manager.get_decoded(path)
-                                {
-                                    // Decoded and not currently decoding --> use it
-                                    decoded_handle.clone()
-                                } else if let Some((w, h)) = original_dims {
-                                    // Check if image needs tiling
-                                    if should_use_tiling(*w, *h) {
-                                        // Large image --> show thumbnail only
+                        element_opt = item.playback_view();
+                    }
+                    if element_opt.is_none() {
+                        match item
+                            .thumbnail_opt
+                            .as_ref()
+                            .unwrap_or(&ItemThumbnail::NotImage)
+                        {
+                            ItemThumbnail::NotImage => {}
+                            ItemThumbnail::Image(handle, original_dims) => {
+                                // Determine which image to show based on async decode state
+                                let mut is_loading = false;
+                                let mut error_msg_opt = None;
+                                let image_handle = if let Some(path) = item.path_opt() {
+                                    if let Some(error_msg) =
+                                        self.large_image_manager.get_error(path)
+                                    {
+                                        error_msg_opt = Some(error_msg.clone());
                                         handle.clone()
+                                    } else if self.large_image_manager.is_decoding(path) {
+                                        // Currently decoding (initial or re-decode) --> show cached/thumbnail with loading indicator
+                                        is_loading = true;
+                                        // Use decoded handle if available (re-decode), otherwise thumbnail (initial decode)
+                                        self.large_image_manager
+                                            .get_decoded(path)
+                                            .cloned()
+                                            .unwrap_or_else(|| handle.clone())
+                                    } else if let Some(decoded_handle) =
+                                        self.large_image_manager.get_decoded(path)
+                                    {
+                                        // Decoded and not currently decoding --> use it
+                                        decoded_handle.clone()
+                                    } else if let Some((w, h)) = original_dims {
+                                        // Check if image needs tiling
+                                        if should_use_tiling(*w, *h) {
+                                            // Large image --> show thumbnail only
+                                            handle.clone()
+                                        } else {
+                                            // Normal-sized image --> load full resolution directly
+                                            widget::image::Handle::from_path(path)
+                                        }
                                     } else {
-                                        // Normal-sized image --> load full resolution directly
-                                        widget::image::Handle::from_path(path)
+                                        // No dimensions available --> show thumbnail
+                                        handle.clone()
                                     }
                                 } else {
-                                    // No dimensions available --> show thumbnail
                                     handle.clone()
-                                }
-                            } else {
-                                handle.clone()
-                            };
+                                };
+
+                                // Images are shown at their original size, whatever the resolution of
+                                // the handle
+                                let image: Element<'_, Message> = match original_dims {
+                                    Some((w, h)) => {
+                                        zoomable = true;
+                                        ImageViewer::new(
+                                            image_handle,
+                                            Size::new(*w as f32, *h as f32),
+                                        )
+                                        .zoom(self.gallery_zoom)
+                                        .on_zoom(Message::GalleryZoom)
+                                        .into()
+                                    }
+                                    None => widget::image(image_handle).into(),
+                                };
 
-                            let content: cosmic::Element<'_, Message> =
-                                if let Some(error_msg) = error_msg_opt {
+                                let content: cosmic::Element<'_, Message> = if let Some(error_msg) =
+                                    error_msg_opt
+                                {
                                     widget::column()
-                                        .push(widget::image(image_handle))
+                                        .push(image)
                                         .push(widget::text(format!("⚠ {}", error_msg)).size(13))
                                         .padding(space_xs)
                                         .align_x(cosmic::iced::Alignment::Center)
                                         .into()
                                 } else if is_loading {
                                     widget::column()
-                                        .push(widget::image(image_handle))
+                                        .push(image)
                                         .push(widget::text("Loading higher resolution...").size(14))
                                         .padding(space_xs)
                                         .align_x(cosmic::iced::Alignment::Center)
                                         .into()
                                 } else {
-                                    //TODO: use widget::image::viewer, when its zoom can be reset
-                                    widget::image(image_handle).into()
+                                    image
                                 };
 
-                            element_opt =
-                                Some(widget::container(content).center(Length::Fill).into());
-                        }
-                        ItemThumbnail::Svg(handle) => {
-                            element_opt = Some(
-                                widget::svg(handle.clone())
-                                    .width(Length::Fill)
-                                    .height(Length::Fill)
-                                    .into(),
-                            );
+                                element_opt =
+                                    Some(widget::container(content).center(Length::Fill).into());
+                            }
+                            ItemThumbnail::Svg(handle) => {
+                                element_opt = Some(
+                                    widget::svg(handle.clone())
+                                        .width(Length::Fill)
+                                        .height(Length::Fill)
+                                        .into(),
+                                );
+                            }
                         }
-                        ItemThumbnail::Text(text) => {
-                            element_opt = Some(
-                                widget::container(
-                                    widget::text_editor(text).padding(space_xxs).class(
-                                        cosmic::theme::iced::TextEditor::Custom(Box::new(
-                                            text_editor_class,
-                                        )),
-                                    ),
-                                )
-                                .center(Length::Fill)
-                                .into(),
-                            );
+                    }
+                    if let Some(TextPreviewState::Loaded(preview)) = &item.text_preview_opt {
+                        let mut column = widget::column::with_capacity(2)
+                            .spacing(space_xxs)
+                            .push(text_preview_editor(preview).padding(space_xxs));
+                        if let Some(controls) = item.text_preview_controls() {
+                            column = column.push(controls);
                         }
+                        element_opt = Some(widget::container(column).center(Length::Fill).into());
                     }
                 }
             }
@@ -4445,12 +5825,63 @@ impl Tab {
         let mut column = widget::column::with_capacity(2);
         column = column.push(widget::Space::with_height(Length::Fixed(space_m.into())));
         {
-            let mut row = widget::row::with_capacity(5).align_y(Alignment::Center);
+            let mut row = widget::row::with_capacity(10)
+                .spacing(space_xxs)
+                .align_y(Alignment::Center);
             row = row.push(widget::horizontal_space());
             if let Some(name) = name_opt {
                 row = row.push(name);
             }
             row = row.push(widget::horizontal_space());
+            let button = |icon: &'static str, message: Message, tooltip: String| {
+                widget::tooltip(
+                    widget::button::icon(widget::icon::from_name(icon))
+                        .class(theme::Button::Standard)
+                        .on_press(message),
+                    widget::text::body(tooltip),
+                    widget::tooltip::Position::Bottom,
+                )
+            };
+            if rotatable {
+                row = row.push(button(
+                    "object-rotate-left-symbolic",
+                    Message::GalleryRotate(Rotation::CounterClockwise),
+                    fl!("rotate-left"),
+                ));
+                row = row.push(button(
+                    "object-rotate-right-symbolic",
+                    Message::GalleryRotate(Rotation::Clockwise),
+                    fl!("rotate-right"),
+                ));
+            }
+            if zoomable {
+                row = row.push(if self.gallery_zoom == ImageZoom::Fit {
+                    button(
+                        "zoom-original-symbolic",
+                        Message::GalleryZoom(ImageZoom::ORIGINAL),
+                        fl!("original-size"),
+                    )
+                } else {
+                    button(
+                        "zoom-fit-best-symbolic",
+                        Message::GalleryZoom(ImageZoom::Fit),
+                        fl!("zoom-to-fit"),
+                    )
+                });
+            }
+            row = row.push(if self.slideshow {
+                button(
+                    "media-playback-pause-symbolic",
+                    Message::GallerySlideshow(false),
+                    fl!("stop-slideshow"),
+                )
+            } else {
+                button(
+                    "media-playback-start-symbolic",
+                    Message::GallerySlideshow(true),
+                    fl!("start-slideshow"),
+                )
+            });
             row = row.push(
                 widget::button::icon(widget::icon::from_name("window-close-symbolic"))
                     .class(theme::Button::Standard)
@@ -4602,26 +6033,63 @@ impl Tab {
                 .into()
         };
 
-        let heading_row = widget::row::with_children([
-            heading_item(fl!("name"), Length::Fill, HeadingOptions::Name),
-            if self.location == Location::Trash {
-                heading_item(
-                    fl!("trashed-on"),
-                    Length::Fixed(modified_width),
-                    HeadingOptions::TrashedOn,
-                )
-            } else {
-                heading_item(
-                    fl!("modified"),
-                    Length::Fixed(modified_width),
-                    HeadingOptions::Modified,
-                )
-            },
-            heading_item(fl!("size"), Length::Fixed(size_width), HeadingOptions::Size),
-        ])
-        .align_y(Alignment::Center)
-        .height(Length::Fixed((space_m + 4).into()))
-        .padding([0, space_xxs]);
+        let mut heading_items = Vec::with_capacity(8);
+        heading_items.push(heading_item(
+            fl!("name"),
+            Length::Fill,
+            HeadingOptions::Name,
+        ));
+        if self.media_columns(size.width) {
+            heading_items.push(heading_item(
+                fl!("title"),
+                Length::Fixed(MEDIA_TEXT_WIDTH),
+                HeadingOptions::Title,
+            ));
+            heading_items.push(heading_item(
+                fl!("artist"),
+                Length::Fixed(MEDIA_TEXT_WIDTH),
+                HeadingOptions::Artist,
+            ));
+            heading_items.push(heading_item(
+                fl!("album"),
+                Length::Fixed(MEDIA_TEXT_WIDTH),
+                HeadingOptions::Album,
+            ));
+            heading_items.push(heading_item(
+                fl!("duration"),
+                Length::Fixed(MEDIA_DURATION_WIDTH),
+                HeadingOptions::Duration,
+            ));
+        }
+        if self.date_taken_column(size.width) {
+            heading_items.push(heading_item(
+                fl!("date-taken"),
+                Length::Fixed(modified_width),
+                HeadingOptions::DateTaken,
+            ));
+        }
+        heading_items.push(if self.location == Location::Trash {
+            heading_item(
+                fl!("trashed-on"),
+                Length::Fixed(modified_width),
+                HeadingOptions::TrashedOn,
+            )
+        } else {
+            heading_item(
+                fl!("modified"),
+                Length::Fixed(modified_width),
+                HeadingOptions::Modified,
+            )
+        });
+        heading_items.push(heading_item(
+            fl!("size"),
+            Length::Fixed(size_width),
+            HeadingOptions::Size,
+        ));
+        let heading_row = widget::row::with_children(heading_items)
+            .align_y(Alignment::Center)
+            .height(Length::Fixed((space_m + 4).into()))
+            .padding([0, space_xxs]);
 
         let accent_rule =
             horizontal_rule(1).class(theme::Rule::Custom(Box::new(|theme| rule::Style {
@@ -4881,6 +6349,104 @@ impl Tab {
         .into()
     }
 
+    /// Visible items in the order they are shown in the grid. On a desktop with stacks, files of
+    /// a stack are replaced by a pile where the first of them would be, which is followed by the
+    /// files when it is expanded.
+    fn grid_entries<'a>(
+        &self,
+        items: &[(usize, &'a Item)],
+        show_hidden: bool,
+        hidden: &mut usize,
+    ) -> Vec<GridEntry<'a>> {
+        let mut visible = Vec::with_capacity(items.len());
+        for &(i, item) in items {
+            if !show_hidden && item.hidden {
+                item.pos_opt.set(None);
+                item.rect_opt.set(None);
+                *hidden += 1;
+            } else {
+                visible.push((i, item));
+            }
+        }
+
+        let mut members: BTreeMap<Stack, Vec<(usize, &Item)>> = BTreeMap::new();
+        if matches!(self.mode, Mode::Desktop) {
+            for &(i, item) in &visible {
+                if let Some(stack) = item.stack_opt {
+                    members.entry(stack).or_default().push((i, item));
+                }
+            }
+        }
+        // A single file is not worth a pile
+        members.retain(|_, stack_items| stack_items.len() > 1);
+
+        let mut placed = BTreeSet::new();
+        let mut entries = Vec::with_capacity(visible.len());
+        for &(i, item) in &visible {
+            let stack_opt = item
+                .stack_opt
+                .and_then(|stack| Some((stack, members.get(&stack)?)));
+            let Some((stack, stack_items)) = stack_opt else {
+                entries.push(GridEntry::Item(i, item));
+                continue;
+            };
+            if !placed.insert(stack) {
+                continue;
+            }
+            entries.push(GridEntry::Stack(stack, stack_items.len()));
+            if self.expanded_stacks.contains(&stack) {
+                entries.extend(
+                    stack_items
+                        .iter()
+                        .map(|&(i, item)| GridEntry::Item(i, item)),
+                );
+            } else {
+                for (_, item) in stack_items {
+                    item.pos_opt.set(None);
+                    item.rect_opt.set(None);
+                }
+            }
+        }
+        entries
+    }
+
+    /// Pile of desktop files, which is expanded or collapsed when clicked
+    fn stack_view(
+        &self,
+        stack: Stack,
+        size: usize,
+        icon_size: u16,
+        width: f32,
+        height: f32,
+    ) -> Element<'static, Message> {
+        let cosmic_theme::Spacing { space_xxxs, .. } = theme::active().cosmic().spacing;
+        let expanded = self.expanded_stacks.contains(&stack);
+        let column = widget::column::with_children([
+            widget::button::custom(
+                widget::icon::from_name(stack.icon_name())
+                    .size(icon_size)
+                    .icon(),
+            )
+            .padding(space_xxxs)
+            .class(button_style(expanded, false, false, false, false, false))
+            .into(),
+            widget::button::custom(widget::text::body(fl!(
+                "stack-size",
+                name = stack.name(),
+                size = size
+            )))
+            .padding([0, space_xxxs])
+            .class(button_style(expanded, false, false, true, true, true))
+            .into(),
+        ])
+        .align_x(Alignment::Center)
+        .width(Length::Fixed(width))
+        .height(Length::Fixed(height));
+        mouse_area::MouseArea::new(column)
+            .on_press(move |_| Message::DesktopStack(stack))
+            .into()
+    }
+
     pub fn grid_view(
         &self,
     ) -> (
@@ -4938,6 +6504,25 @@ impl Tab {
             rows_m1 + 1
         };
 
+        // Desktop icons moved by the user are shown at their own positions instead of a grid
+        let free_grid_opt = if matches!(self.mode, Mode::Desktop) {
+            let grid = desktop_layout::Grid {
+                padding: space_xxs.into(),
+                item_width: item_width as i32,
+                item_height: item_height as i32,
+                column_step: (item_width + column_spacing as usize) as i32,
+                row_step: (item_height + grid_spacing as usize) as i32,
+                cols,
+                rows,
+                width: (width + 2 * space_xxs as usize) as i32,
+                height: height as i32,
+            };
+            self.desktop_grid_opt.set(Some(grid));
+            (!self.desktop_layout.positions.is_empty()).then_some(grid)
+        } else {
+            None
+        };
+
         //TODO: move to function
         let visible_rect = {
             let point = match self.scroll_opt {
@@ -4966,48 +6551,107 @@ impl Tab {
             let mut page_row = 0;
             let mut hidden = 0;
             let mut grid_elements = Vec::new();
-            for &(i, item) in &items {
-                if !show_hidden && item.hidden {
-                    item.pos_opt.set(None);
-                    item.rect_opt.set(None);
-                    hidden += 1;
-                    continue;
+            let entries = self.grid_entries(&items, show_hidden, &mut hidden);
+            let mut arranged_opt = free_grid_opt.map(|grid| {
+                let saved: Vec<_> = entries
+                    .iter()
+                    .map(|entry| match entry {
+                        GridEntry::Item(_, item) => {
+                            self.desktop_layout.positions.get(&item.name).copied()
+                        }
+                        GridEntry::Stack(..) => None,
+                    })
+                    .collect();
+                desktop_layout::arrange(&grid, &saved).into_iter()
+            });
+            let mut free_layout = FreeLayout::new();
+            let mut free_bottom = 0.0f32;
+            for entry in &entries {
+                if count > 0 {
+                    if matches!(self.mode, Mode::Desktop) {
+                        row += 1;
+                        if row >= page_row + rows {
+                            row = 0;
+                            col += 1;
+                        }
+                        if col >= cols {
+                            col = 0;
+                            page_row += rows;
+                            row = page_row;
+                        }
+                    } else {
+                        col += 1;
+                        if col >= cols {
+                            col = 0;
+                            row += 1;
+                        }
+                    }
                 }
-                item.pos_opt.set(Some((row, col)));
-                let item_rect = Rectangle::new(
-                    Point::new(
+                count += 1;
+
+                let item_point = match arranged_opt.as_mut().and_then(Iterator::next) {
+                    Some(((x, y), cell)) => {
+                        (row, col) = cell;
+                        Point::new(x as f32, y as f32)
+                    }
+                    None => Point::new(
                         (col * (item_width + column_spacing as usize) + space_xxs as usize) as f32,
                         (row * (item_height + grid_spacing as usize) + space_xxs as usize) as f32,
                     ),
-                    Size::new(item_width as f32, item_height as f32),
-                );
-                item.rect_opt.set(Some(item_rect));
+                };
+                let item_rect =
+                    Rectangle::new(item_point, Size::new(item_width as f32, item_height as f32));
+                free_bottom = free_bottom.max(item_rect.y + item_rect.height);
 
                 //TODO: error if the row or col is already set?
-                while grid_elements.len() <= row {
+                while arranged_opt.is_none() && grid_elements.len() <= row {
                     grid_elements.push(Vec::new());
                 }
 
+                let (i, item) = match *entry {
+                    GridEntry::Item(i, item) => (i, item),
+                    GridEntry::Stack(stack, size) => {
+                        if item_rect.intersects(&visible_rect) {
+                            let element = self.stack_view(
+                                stack,
+                                size,
+                                icon_sizes.grid(),
+                                item_width as f32,
+                                item_height as f32,
+                            );
+                            if arranged_opt.is_some() {
+                                free_layout = free_layout.push(item_rect.position(), element);
+                            } else {
+                                grid_elements[row].push(element);
+                            }
+                        } else if arranged_opt.is_none() && grid_elements[row].is_empty() {
+                            grid_elements[row].push(Element::from(
+                                widget::column()
+                                    .width(Length::Fill)
+                                    .height(Length::Fixed(item_height as f32)),
+                            ));
+                        }
+                        continue;
+                    }
+                };
+                item.pos_opt.set(Some((row, col)));
+                item.rect_opt.set(Some(item_rect));
+
                 // Only build elements if visible (for performance)
                 if item_rect.intersects(&visible_rect) {
                     //TODO: one focus group per grid item (needs custom widget)
                     let buttons: Vec<Element<Message>> = vec![
-                        widget::button::custom(
-                            widget::icon::icon(item.icon_handle_grid.clone())
-                                .content_fit(ContentFit::Contain)
-                                .size(icon_sizes.grid())
-                                .width(Length::Shrink),
-                        )
-                        .padding(space_xxxs)
-                        .class(button_style(
-                            item.selected,
-                            item.highlighted,
-                            item.cut,
-                            false,
-                            false,
-                            false,
-                        ))
-                        .into(),
+                        widget::button::custom(item.grid_icon(icon_sizes.grid()))
+                            .padding(space_xxxs)
+                            .class(button_style(
+                                item.selected,
+                                item.highlighted,
+                                item.cut,
+                                false,
+                                false,
+                                false,
+                            ))
+                            .into(),
                         widget::tooltip(
                             widget::button::custom(widget::text::body(&item.display_name))
                                 .id(item.button_id.clone())
@@ -5066,8 +6710,12 @@ impl Tab {
                         .on_middle_press(move |_| Message::MiddleClick(i))
                         .on_enter(move || Message::HighlightActivate(i))
                         .on_exit(move || Message::HighlightDeactivate(i));
-                    grid_elements[row].push(Element::from(mouse_area));
-                } else {
+                    if arranged_opt.is_some() {
+                        free_layout = free_layout.push(item_rect.position(), mouse_area);
+                    } else {
+                        grid_elements[row].push(Element::from(mouse_area));
+                    }
+                } else if arranged_opt.is_none() {
                     // Add a spacer if the row is empty, so scroll works
                     if grid_elements[row].is_empty() {
                         grid_elements[row].push(Element::from(
@@ -5077,26 +6725,6 @@ impl Tab {
                         ));
                     }
                 }
-
-                count += 1;
-                if matches!(self.mode, Mode::Desktop) {
-                    row += 1;
-                    if row >= page_row + rows {
-                        row = 0;
-                        col += 1;
-                    }
-                    if col >= cols {
-                        col = 0;
-                        page_row += rows;
-                        row = page_row;
-                    }
-                } else {
-                    col += 1;
-                    if col >= cols {
-                        col = 0;
-                        row += 1;
-                    }
-                }
             }
 
             for row_elements in grid_elements {
@@ -5110,7 +6738,12 @@ impl Tab {
                 return (None, self.empty_view(hidden > 0), false);
             }
 
-            column = column.push(grid);
+            if arranged_opt.is_some() {
+                column = column
+                    .push(free_layout.height(Length::Fixed(free_bottom + f32::from(space_xxs))));
+            } else {
+                column = column.push(grid);
+            }
 
             //TODO: HACK If we don't reach the bottom of the view, go ahead and add a spacer to do that
             {
@@ -5240,8 +6873,15 @@ impl Tab {
         let modified_width = 200.0;
         let size_width = 100.0;
         let condensed = size.width < (name_width + modified_width + size_width);
-        let is_search = matches!(self.location, Location::Search(..));
-        let icon_size = if condensed || is_search {
+        // Show the folder of each item when it is not shown in a group header
+        let show_parents = match self.location {
+            Location::Search(..) => true,
+            Location::Trash => !self.trash_groups(),
+            _ => false,
+        };
+        let media_columns = self.media_columns(size.width);
+        let date_taken_column = self.date_taken_column(size.width);
+        let icon_size = if condensed || show_parents {
             icon_sizes.list_condensed()
         } else {
             icon_sizes.list()
@@ -5263,10 +6903,18 @@ impl Tab {
             Rectangle::new(point, size)
         };
 
+        // Recent files are listed under the day they were used, and trashed items under the
+        // folder they were deleted from
+        let recent_groups =
+            self.location == Location::Recents && self.sort_options().0 == HeadingOptions::Modified;
+        let trash_groups = self.trash_groups();
+        let today = chrono::Local::now().date_naive();
+
         let mut drag_items = Vec::new();
         if let Some(items) = self.column_sort() {
             let mut count = 0;
             let mut hidden = 0;
+            let mut group_opt = None;
             for (i, item) in items {
                 if item.hidden && !show_hidden {
                     item.pos_opt.set(None);
@@ -5275,7 +6923,27 @@ impl Tab {
                     continue;
                 }
 
-                if count > 0 {
+                let group_name_opt = if recent_groups {
+                    item.recent_opt
+                        .as_ref()
+                        .map(|recent| RecentGroup::new(recent.used, today).name())
+                } else if trash_groups {
+                    item.original_parent_opt()
+                        .map(|parent| parent.display().to_string())
+                } else {
+                    None
+                };
+                let header_opt = group_name_opt.filter(|name| group_opt.as_ref() != Some(name));
+                if let Some(name) = header_opt {
+                    group_opt = Some(name.clone());
+                    column = column.push(
+                        widget::container(widget::text::heading(name))
+                            .align_y(Alignment::End)
+                            .padding([0, space_xxs])
+                            .height(Length::Fixed(f32::from(row_height))),
+                    );
+                    y += f32::from(row_height);
+                } else if count > 0 {
                     column = column
                         .push(widget::container(horizontal_rule(1)).padding([0, rule_padding]));
                     y += 1.0;
@@ -5384,7 +7052,7 @@ impl Tab {
                         .height(Length::Fixed(f32::from(row_height)))
                         .align_y(Alignment::Center)
                         .spacing(space_xxs)
-                    } else if is_search {
+                    } else if show_parents {
                         widget::row::with_children([
                             widget::icon::icon(item.icon_handle_list_condensed.clone())
                                 .content_fit(ContentFit::Contain)
@@ -5392,11 +7060,7 @@ impl Tab {
                                 .into(),
                             widget::column::with_children([
                                 widget::text::body(item.display_name.clone()).into(),
-                                widget::text::caption(match item.path_opt() {
-                                    Some(path) => path.display().to_string(),
-                                    None => String::new(),
-                                })
-                                .into(),
+                                widget::text::caption(item.parent_caption()).into(),
                             ])
                             .width(Length::Fill)
                             .into(),
@@ -5411,24 +7075,72 @@ impl Tab {
                         .align_y(Alignment::Center)
                         .spacing(space_xxs)
                     } else {
-                        widget::row::with_children([
+                        let mut children: Vec<Element<_>> = Vec::with_capacity(9);
+                        children.push(
                             widget::icon::icon(item.icon_handle_list.clone())
                                 .content_fit(ContentFit::Contain)
                                 .size(icon_size)
                                 .into(),
+                        );
+                        children.push(
                             widget::text::body(item.display_name.clone())
                                 .width(Length::Fill)
                                 .into(),
+                        );
+                        if media_columns {
+                            for heading in [
+                                HeadingOptions::Title,
+                                HeadingOptions::Artist,
+                                HeadingOptions::Album,
+                            ] {
+                                children.push(
+                                    widget::text::body(
+                                        item.media_text(heading).unwrap_or_default().to_string(),
+                                    )
+                                    .width(Length::Fixed(MEDIA_TEXT_WIDTH))
+                                    .wrapping(text::Wrapping::None)
+                                    .into(),
+                                );
+                            }
+                            children.push(
+                                widget::text::body(
+                                    item.media_opt
+                                        .as_ref()
+                                        .and_then(|media| media.duration)
+                                        .map(media::format_duration)
+                                        .unwrap_or_default(),
+                                )
+                                .width(Length::Fixed(MEDIA_DURATION_WIDTH))
+                                .into(),
+                            );
+                        }
+                        if date_taken_column {
+                            children.push(
+                                widget::text::body(
+                                    item.photo_opt
+                                        .as_ref()
+                                        .and_then(|photo| photo.date_taken)
+                                        .map(|time| self.format_time(time).to_string())
+                                        .unwrap_or_default(),
+                                )
+                                .width(Length::Fixed(modified_width))
+                                .into(),
+                            );
+                        }
+                        children.push(
                             widget::text::body(modified_text.clone())
                                 .width(Length::Fixed(modified_width))
                                 .into(),
+                        );
+                        children.push(
                             widget::text::body(size_text.clone())
                                 .width(Length::Fixed(size_width))
                                 .into(),
-                        ])
-                        .height(Length::Fixed(f32::from(row_height)))
-                        .align_y(Alignment::Center)
-                        .spacing(space_xxs)
+                        );
+                        widget::row::with_children(children)
+                            .height(Length::Fixed(f32::from(row_height)))
+                            .align_y(Alignment::Center)
+                            .spacing(space_xxs)
                     };
 
                     let button = |row| {
@@ -5493,7 +7205,7 @@ impl Tab {
                             .align_y(Alignment::Center)
                             .spacing(space_xxs)
                             .into()
-                        } else if is_search {
+                        } else if show_parents {
                             widget::row::with_children([
                                 widget::icon::icon(item.icon_handle_list_condensed.clone())
                                     .content_fit(ContentFit::Contain)
@@ -5501,11 +7213,7 @@ impl Tab {
                                     .into(),
                                 widget::column::with_children([
                                     widget::text::body(item.display_name.clone()).into(),
-                                    widget::text::caption(match item.path_opt() {
-                                        Some(path) => path.display().to_string(),
-                                        None => String::new(),
-                                    })
-                                    .into(),
+                                    widget::text::caption(item.parent_caption()).into(),
                                 ])
                                 .width(Length::Fill)
                                 .into(),
@@ -5569,7 +7277,7 @@ impl Tab {
         }
         //TODO: HACK If we don't reach the bottom of the view, go ahead and add a spacer to do that
         {
-            let top_deduct = (if condensed || is_search { 6 } else { 9 }) * space_xxs;
+            let top_deduct = (if condensed || show_parents { 6 } else { 9 }) * space_xxs;
 
             self.item_view_size_opt
                 .set(self.size_opt.get().map(|s| Size {
@@ -5745,6 +7453,62 @@ impl Tab {
                     .padding([0, 0, 7, 0]),
                 );
             }
+            Location::Recents if !self.recent_items.is_empty() => {
+                let app_selected = match &self.recents_filter.app_opt {
+                    Some(app) => self
+                        .recent_apps
+                        .iter()
+                        .skip(1)
+                        .position(|x| x == app)
+                        .map(|i| i + 1),
+                    None => Some(0),
+                };
+                let kind_selected = match self.recents_filter.kind_opt {
+                    Some(kind) => RecentKind::ALL
+                        .iter()
+                        .position(|x| *x == kind)
+                        .map(|i| i + 1),
+                    None => Some(0),
+                };
+                let clear_selected = ClearOlderThan::ALL
+                    .iter()
+                    .position(|x| *x == self.recents_clear);
+                tab_column = tab_column.push(
+                    widget::layer_container(
+                        widget::row::with_children([
+                            widget::dropdown(
+                                Cow::Borrowed(self.recent_apps.as_slice()),
+                                app_selected,
+                                Message::RecentsApp,
+                            )
+                            .into(),
+                            widget::dropdown(
+                                Cow::Borrowed(recents::KIND_NAMES.as_slice()),
+                                kind_selected,
+                                Message::RecentsKind,
+                            )
+                            .into(),
+                            widget::horizontal_space().into(),
+                            widget::text::body(fl!("clear-older-than")).into(),
+                            widget::dropdown(
+                                Cow::Borrowed(recents::CLEAR_NAMES.as_slice()),
+                                clear_selected,
+                                Message::RecentsClearAge,
+                            )
+                            .into(),
+                            widget::button::standard(fl!("clear"))
+                                .on_press(Message::RecentsClear)
+                                .into(),
+                        ])
+                        .align_y(Alignment::Center)
+                        .spacing(space_xs),
+                    )
+                    .padding([space_xxs, space_xs])
+                    .layer(cosmic_theme::Layer::Primary)
+                    .apply(widget::container)
+                    .padding([0, 0, 7, 0]),
+                );
+            }
             _ => {}
         }
         let mut tab_view = widget::container(tab_column)
@@ -5785,7 +7549,8 @@ impl Tab {
             }
         })
         .on_enter(move |_, _, _| Message::DndEnter(tab_location_2.clone()))
-        .on_leave(move || Message::DndLeave(tab_location_3.clone()));
+        .on_leave(move || Message::DndLeave(tab_location_3.clone()))
+        .on_motion(|x, y| Message::DndMotion(Point::new(x as f32, y as f32)));
 
         dnd_dest.into()
     }
@@ -5801,7 +7566,9 @@ impl Tab {
     pub fn subscription(&self, preview: bool) -> Subscription<Message> {
         //TODO: how many thumbnail loads should be in flight at once?
         let jobs = self.thumb_config.jobs.get() as usize;
-        let mut subscriptions = Vec::with_capacity(jobs + 3);
+        // Remote files have their own limit, lowered when the connection is slow
+        let remote_jobs = remote::remote_jobs(self.thumb_config.remote_jobs.get() as usize);
+        let mut subscriptions = Vec::with_capacity(jobs + remote_jobs + 3);
 
         if let Some(items) = &self.items_opt {
             //TODO: move to function
@@ -5814,12 +7581,25 @@ impl Tab {
                 Rectangle::new(point, size)
             };
 
+            let mut local_count = 0;
+            let mut remote_count = 0;
             for item in items {
+                if local_count >= jobs && remote_count >= remote_jobs {
+                    break;
+                }
+
                 if item.thumbnail_opt.is_some() {
                     // Skip items that already have a mime type and thumbnail
                     continue;
                 }
 
+                if (item.remote && remote_count >= remote_jobs)
+                    || (!item.remote && local_count >= jobs)
+                {
+                    // The job limit for this kind of file is reached
+                    continue;
+                }
+
                 match item.rect_opt.get() {
                     Some(rect) => {
                         if !rect.intersects(&visible_rect) {
@@ -5836,6 +7616,9 @@ impl Tab {
                 let Some(path) = item.path_opt().cloned() else {
                     continue;
                 };
+                if item.remote && !self.remote_thumbnails.allows(&path) {
+                    continue;
+                }
 
                 let metadata = item.metadata.clone();
                 let can_thumbnail = match metadata {
@@ -5846,24 +7629,12 @@ impl Tab {
                 };
                 if can_thumbnail {
                     let mime = item.mime.clone();
-                    let max_jobs = jobs;
+                    let remote = item.remote;
+                    let folder_preview = self.thumb_config.folder_previews && metadata.is_dir();
+                    let max_jobs = if remote { remote_jobs } else { jobs };
                     let max_mb = u64::from(self.thumb_config.max_mem_mb.get());
                     let max_size = u64::from(self.thumb_config.max_size_mb.get());
 
-                    // Determine effective memory budget based on image size
-                    let (effective_max_mb, effective_jobs) = if mime.type_() == mime::IMAGE {
-                        match image::image_dimensions(&path) {
-                            Ok((width, height)) => {
-                                let (_use_dedicated, eff_mb, eff_jobs) =
-                                    should_use_dedicated_worker(width, height, max_mb, max_jobs);
-                                (eff_mb, eff_jobs)
-                            }
-                            Err(_) => (max_mb, max_jobs),
-                        }
-                    } else {
-                        (max_mb, max_jobs)
-                    };
-
                     subscriptions.push(Subscription::run_with_id(
                         ("thumbnail", path.clone()),
                         stream::channel(1, move |mut output| async move {
@@ -5875,20 +7646,43 @@ impl Tab {
 
                                 tokio::task::spawn_blocking(move || {
                                     let start = Instant::now();
-                                    let thumbnail = ItemThumbnail::new(
-                                        &path,
-                                        metadata,
-                                        mime,
-                                        THUMBNAIL_SIZE,
-                                        effective_max_mb,
-                                        effective_jobs,
-                                        max_size,
-                                    );
-                                    log::debug!(
-                                        "thumbnailed {} in {:?}",
-                                        path.display(),
-                                        start.elapsed()
-                                    );
+                                    let file_size = metadata.file_size().unwrap_or_default();
+
+                                    // Determine effective memory budget based on image size,
+                                    // reading the header here as it may be slow on remote files
+                                    let (effective_max_mb, effective_jobs) =
+                                        if mime.type_() == mime::IMAGE {
+                                            match image::image_dimensions(&path) {
+                                                Ok((width, height)) => {
+                                                    let (_use_dedicated, eff_mb, eff_jobs) =
+                                                        should_use_dedicated_worker(
+                                                            width, height, max_mb, max_jobs,
+                                                        );
+                                                    (eff_mb, eff_jobs)
+                                                }
+                                                Err(_) => (max_mb, max_jobs),
+                                            }
+                                        } else {
+                                            (max_mb, max_jobs)
+                                        };
+                                    let thumbnail = if folder_preview {
+                                        ItemThumbnail::folder(&path, THUMBNAIL_SIZE, max_size)
+                                    } else {
+                                        ItemThumbnail::new(
+                                            &path,
+                                            metadata,
+                                            mime,
+                                            THUMBNAIL_SIZE,
+                                            effective_max_mb,
+                                            effective_jobs,
+                                            max_size,
+                                        )
+                                    };
+                                    let elapsed = start.elapsed();
+                                    if remote {
+                                        remote::record_transfer(file_size, elapsed);
+                                    }
+                                    log::debug!("thumbnailed {} in {:?}", path.display(), elapsed);
                                     Message::Thumbnail(path, thumbnail)
                                 })
                                 .await
@@ -5909,11 +7703,113 @@ impl Tab {
                             std::future::pending().await
                         }),
                     ));
+                    if remote {
+                        remote_count += 1;
+                    } else {
+                        local_count += 1;
+                    }
                 }
+            }
 
-                if subscriptions.len() >= jobs {
+            // Media and photo information is loaded for all items, not only visible ones, so that
+            // their columns can be sorted
+            let mut info_jobs = 0;
+            for item in items {
+                if info_jobs >= jobs {
                     break;
                 }
+                let needs_media = item.media_opt.is_none() && media::is_media(&item.mime);
+                let needs_photo = item.photo_opt.is_none() && photo::is_photo(&item.mime);
+                if !(needs_media || needs_photo)
+                    || !matches!(item.metadata, ItemMetadata::Path { .. })
+                {
+                    continue;
+                }
+                let Some(path) = item.path_opt().cloned() else {
+                    continue;
+                };
+                if item.remote && !self.remote_thumbnails.allows(&path) {
+                    continue;
+                }
+                let mime = item.mime.clone();
+                info_jobs += 1;
+                subscriptions.push(Subscription::run_with_id(
+                    ("file_info", path.clone()),
+                    stream::channel(1, move |mut output| async move {
+                        let message = {
+                            let path = path.clone();
+                            tokio::task::spawn_blocking(move || {
+                                if needs_photo {
+                                    let photo_info = PhotoInfo::read(&path);
+                                    Message::PhotoInfo(path, photo_info)
+                                } else {
+                                    let media_info = MediaInfo::read(&path, &mime);
+                                    Message::MediaInfo(path, media_info)
+                                }
+                            })
+                            .await
+                            .unwrap()
+                        };
+
+                        match output.send(message).await {
+                            Ok(()) => {}
+                            Err(err) => {
+                                log::warn!(
+                                    "failed to send file info for {}: {}",
+                                    path.display(),
+                                    err
+                                );
+                            }
+                        }
+
+                        std::future::pending().await
+                    }),
+                ));
+            }
+
+            // Load the text file shown in the gallery or the preview pane
+            let text_item_opt = if self.gallery {
+                self.select_focus.and_then(|index| items.get(index))
+            } else if preview {
+                items.iter().find(|item| item.selected)
+            } else {
+                None
+            };
+            if let Some(item) = text_item_opt.filter(|item| {
+                item.text_preview_opt.is_none()
+                    && matches!(item.metadata, ItemMetadata::Path { .. })
+                    && !item.metadata.is_dir()
+                    && text_preview::is_text(&item.mime)
+            }) {
+                if let Some(path) = item.path_opt().cloned() {
+                    subscriptions.push(Subscription::run_with_id(
+                        ("text_preview", path.clone()),
+                        stream::channel(1, |mut output| async move {
+                            let message = {
+                                let load_path = path.clone();
+                                let text_preview = tokio::task::spawn_blocking(move || {
+                                    TextPreviewState::load(&load_path, 0)
+                                })
+                                .await
+                                .unwrap_or(TextPreviewState::Unavailable);
+                                Message::TextPreview(path.clone(), text_preview)
+                            };
+
+                            match output.send(message).await {
+                                Ok(()) => {}
+                                Err(err) => {
+                                    log::warn!(
+                                        "failed to send text preview for {}: {}",
+                                        path.display(),
+                                        err
+                                    );
+                                }
+                            }
+
+                            std::future::pending().await
+                        }),
+                    ));
+                }
             }
 
             if preview {
@@ -6122,6 +8018,25 @@ impl Tab {
             ));
         }
 
+        if self.gallery && self.slideshow {
+            subscriptions.push(
+                cosmic::iced::time::every(SLIDESHOW_INTERVAL)
+                    .map(|_| Message::GallerySlideshowNext),
+            );
+        }
+
+        #[cfg(feature = "playback")]
+        if self.items_opt.as_ref().is_some_and(|items| {
+            items.iter().any(|item| {
+                item.playback_opt
+                    .as_ref()
+                    .is_some_and(|playback| playback.playing || playback.waiting)
+            })
+        }) {
+            subscriptions
+                .push(cosmic::iced::time::every(PLAYBACK_INTERVAL).map(|_| Message::PlaybackTick));
+        }
+
         Subscription::batch(subscriptions)
     }
 
@@ -6286,6 +8201,27 @@ impl<Message: 'static> From<RcElementWrapper<Message>> for Element<'static, Mess
     }
 }
 
+fn text_preview_editor(
+    preview: &TextPreview,
+) -> cosmic::iced_widget::TextEditor<
+    '_,
+    text_preview::Highlighter,
+    Message,
+    cosmic::Theme,
+    cosmic::Renderer,
+> {
+    widget::text_editor(preview.content())
+        .font(cosmic::font::mono())
+        .wrapping(cosmic::iced::widget::text::Wrapping::None)
+        .class(cosmic::theme::iced::TextEditor::Custom(Box::new(
+            text_editor_class,
+        )))
+        .highlight_with::<text_preview::Highlighter>(
+            preview.settings(theme::active().cosmic().is_dark),
+            |highlight, _theme| highlight.to_format(),
+        )
+}
+
 fn text_editor_class(
     theme: &cosmic::Theme,
     status: cosmic::widget::text_editor::Status,
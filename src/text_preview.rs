@@ -0,0 +1,449 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+use cosmic::{
+    iced::{Color, Font},
+    iced_core::text::highlighter::{self, Format},
+    widget::text_editor,
+};
+use encoding_rs::{Encoding, UTF_8};
+use mime_guess::{Mime, mime};
+use std::{
+    cell::OnceCell,
+    fs::File,
+    io::{self, Read},
+    ops::Range,
+    path::Path,
+    sync::LazyLock,
+};
+use syntect::{
+    highlighting::{self, Theme, ThemeSet},
+    parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet},
+};
+
+/// Encoding and binary data are detected from this many bytes at the start of a file,
+/// and at most this much text is shown at once
+const MAX_BYTES: u64 = 128 * 1024;
+/// At most this many lines are shown at once
+pub const MAX_LINES: usize = 1000;
+/// Bytes decoded at a time while looking for the lines to show
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Parser state is kept every this many lines, so that edits restart from the nearest one
+const LINES_PER_SNAPSHOT: usize = 50;
+/// Separator between line numbers and text
+const GUTTER_SEPARATOR: &str = " │ ";
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_nonewlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+fn theme(dark: bool) -> &'static Theme {
+    &THEMES.themes[if dark {
+        "base16-ocean.dark"
+    } else {
+        "InspiredGitHub"
+    }]
+}
+
+/// Window of lines of a text file, decoded and prefixed with line numbers
+#[derive(Debug)]
+pub struct TextPreview {
+    text: String,
+    /// Built when the preview is first shown, as laying out the text is expensive
+    content: OnceCell<text_editor::Content>,
+    /// Name of the syntax used for highlighting
+    pub syntax: String,
+    /// Name of the detected encoding
+    pub encoding: &'static str,
+    /// Index of the first line shown
+    pub first_line: usize,
+    /// Number of lines shown
+    pub lines: usize,
+    /// True if the file continues after the lines shown
+    pub more: bool,
+    gutter: usize,
+}
+
+impl TextPreview {
+    /// Read up to [`MAX_LINES`] lines of a text file, starting at `first_line`.
+    /// Returns `None` if it looks like binary data.
+    pub fn load(path: &Path, first_line: usize) -> io::Result<Option<Self>> {
+        let mut file = File::open(path)?;
+        let mut head = Vec::new();
+        file.by_ref().take(MAX_BYTES + 1).read_to_end(&mut head)?;
+        let mut eof = head.len() as u64 <= MAX_BYTES;
+
+        let detect_len = head.len().min(MAX_BYTES as usize);
+        let encoding = detect_encoding(&head[..detect_len], !eof);
+        // UTF-16 text contains nul bytes, other encodings should not
+        if (encoding.is_single_byte() || encoding == UTF_8)
+            && head.iter().take(8192).any(|&b| b == 0)
+        {
+            return Ok(None);
+        }
+
+        // Lines before the window are only counted, so that any part of a large file can be
+        // shown without keeping all of it in memory
+        let mut decoder = encoding.new_decoder_with_bom_removal();
+        let mut window = LineWindow::new(first_line);
+        let mut chunk = head;
+        loop {
+            let mut text = String::with_capacity(
+                decoder
+                    .max_utf8_buffer_length(chunk.len())
+                    .unwrap_or(chunk.len() * 3),
+            );
+            let (_result, _read, _had_errors) = decoder.decode_to_string(&chunk, &mut text, eof);
+            if window.push(&text) || eof {
+                break;
+            }
+            chunk.resize(CHUNK_BYTES, 0);
+            let read = file.read(&mut chunk)?;
+            chunk.truncate(read);
+            eof = read == 0;
+        }
+        let more = window.finish();
+        let lines = window.lines;
+
+        let syntax = find_syntax(path, lines.first().map(String::as_str));
+        let gutter = (first_line + lines.len()).max(1).to_string().len();
+        let text_len: usize = lines.iter().map(String::len).sum();
+        let mut numbered = String::with_capacity(text_len + lines.len() * (gutter + 4));
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                numbered.push('\n');
+            }
+            numbered.push_str(&format!(
+                "{:>gutter$}{GUTTER_SEPARATOR}{line}",
+                first_line + i + 1
+            ));
+        }
+
+        Ok(Some(Self {
+            text: numbered,
+            content: OnceCell::new(),
+            syntax: syntax.name.clone(),
+            encoding: encoding.name(),
+            first_line,
+            lines: lines.len(),
+            more,
+            gutter,
+        }))
+    }
+
+    pub fn content(&self) -> &text_editor::Content {
+        self.content
+            .get_or_init(|| text_editor::Content::with_text(&self.text))
+    }
+
+    pub fn settings(&self, dark: bool) -> Settings {
+        Settings {
+            syntax: self.syntax.clone(),
+            dark,
+            gutter: self.gutter,
+        }
+    }
+}
+
+impl Clone for TextPreview {
+    fn clone(&self) -> Self {
+        // Content cannot be cloned simply, the clone builds its own when shown
+        Self {
+            text: self.text.clone(),
+            content: OnceCell::new(),
+            syntax: self.syntax.clone(),
+            encoding: self.encoding,
+            first_line: self.first_line,
+            lines: self.lines,
+            more: self.more,
+            gutter: self.gutter,
+        }
+    }
+}
+
+/// Collects the lines of a window from decoded text
+struct LineWindow {
+    first_line: usize,
+    /// Index of the line being read
+    line: usize,
+    partial: String,
+    lines: Vec<String>,
+    bytes: usize,
+    /// Text was found after the window was full
+    more: bool,
+}
+
+impl LineWindow {
+    fn new(first_line: usize) -> Self {
+        Self {
+            first_line,
+            line: 0,
+            partial: String::new(),
+            lines: Vec::new(),
+            bytes: 0,
+            more: false,
+        }
+    }
+
+    fn full(&self) -> bool {
+        self.lines.len() >= MAX_LINES || self.bytes >= MAX_BYTES as usize
+    }
+
+    /// Add decoded text, returns true once the window is full and more text follows
+    fn push(&mut self, text: &str) -> bool {
+        let mut segments = text.split('\n').peekable();
+        while let Some(segment) = segments.next() {
+            if self.full() {
+                if !segment.is_empty() || segments.peek().is_some() {
+                    self.more = true;
+                }
+                return self.more;
+            }
+            if self.line >= self.first_line {
+                // Very long lines are cut off
+                let remaining = (MAX_BYTES as usize).saturating_sub(self.partial.len());
+                let mut end = segment.len().min(remaining);
+                while !segment.is_char_boundary(end) {
+                    end -= 1;
+                }
+                self.partial.push_str(&segment[..end]);
+            }
+            if segments.peek().is_some() {
+                self.end_line();
+            }
+        }
+        false
+    }
+
+    fn end_line(&mut self) {
+        if self.line >= self.first_line {
+            let mut line = std::mem::take(&mut self.partial);
+            if line.ends_with('\r') {
+                line.pop();
+            }
+            self.bytes += line.len();
+            self.lines.push(line);
+        }
+        self.line += 1;
+    }
+
+    /// Add the last line if it has no line ending, returns true if the file continues
+    fn finish(&mut self) -> bool {
+        if !self.more && !self.full() && !self.partial.is_empty() {
+            self.end_line();
+        }
+        self.more
+    }
+}
+
+/// Returns true for text files, including source code that has an application MIME type
+pub fn is_text(mime: &Mime) -> bool {
+    mime.type_() == mime::TEXT
+        || (mime.type_() == mime::APPLICATION
+            && (matches!(
+                mime.subtype().as_str(),
+                "json" | "javascript" | "toml" | "xml" | "x-shellscript" | "x-yaml" | "yaml"
+            ) || mime
+                .suffix()
+                .is_some_and(|suffix| suffix == mime::JSON || suffix == mime::XML)))
+}
+
+fn detect_encoding(bytes: &[u8], truncated: bool) -> &'static Encoding {
+    if let Some((encoding, _bom_len)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => return UTF_8,
+        // A multi-byte character may have been cut off at the end
+        Err(err) if truncated && err.error_len().is_none() => return UTF_8,
+        Err(_) => {}
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, !truncated);
+    detector.guess(None, true)
+}
+
+fn find_syntax(path: &Path, first_line: Option<&str>) -> &'static SyntaxReference {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| SYNTAXES.find_syntax_by_extension(ext))
+        .or_else(|| {
+            // Files like Makefile are matched by name
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| SYNTAXES.find_syntax_by_extension(name))
+        })
+        .or_else(|| first_line.and_then(|line| SYNTAXES.find_syntax_by_first_line(line)))
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    syntax: String,
+    dark: bool,
+    gutter: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Highlight(Option<Color>);
+
+impl Highlight {
+    pub fn to_format(&self) -> Format<Font> {
+        Format {
+            color: self.0,
+            font: None,
+        }
+    }
+}
+
+/// Syntax highlighter for [`TextPreview`] content, which dims the line numbers
+pub struct Highlighter {
+    syntax: &'static SyntaxReference,
+    highlighter: highlighting::Highlighter<'static>,
+    gutter_color: Color,
+    gutter_len: usize,
+    caches: Vec<(ParseState, ScopeStack)>,
+    current_line: usize,
+}
+
+impl Highlighter {
+    fn parse_state(&self) -> (ParseState, ScopeStack) {
+        (ParseState::new(self.syntax), ScopeStack::new())
+    }
+}
+
+impl highlighter::Highlighter for Highlighter {
+    type Settings = Settings;
+    type Highlight = Highlight;
+    type Iterator<'a> = std::vec::IntoIter<(Range<usize>, Highlight)>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        let syntax = SYNTAXES
+            .find_syntax_by_name(&settings.syntax)
+            .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+        let theme = theme(settings.dark);
+        let gutter_color = theme
+            .settings
+            .gutter_foreground
+            .or(theme.settings.foreground)
+            .map_or(Color::from_rgba(0.5, 0.5, 0.5, 1.0), |color| {
+                Color::from_rgba8(color.r, color.g, color.b, 0.6)
+            });
+        let mut highlighter = Self {
+            syntax,
+            highlighter: highlighting::Highlighter::new(theme),
+            gutter_color,
+            gutter_len: settings.gutter + GUTTER_SEPARATOR.len(),
+            caches: Vec::new(),
+            current_line: 0,
+        };
+        let state = highlighter.parse_state();
+        highlighter.caches.push(state);
+        highlighter
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        *self = Self::new(new_settings);
+    }
+
+    fn change_line(&mut self, line: usize) {
+        let snapshot = line / LINES_PER_SNAPSHOT;
+        if snapshot <= self.caches.len() {
+            self.caches.truncate(snapshot);
+            self.current_line = snapshot * LINES_PER_SNAPSHOT;
+        } else {
+            self.caches.truncate(1);
+            self.current_line = 0;
+        }
+        let state = self
+            .caches
+            .last()
+            .cloned()
+            .unwrap_or_else(|| self.parse_state());
+        self.caches.push(state);
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        if self.current_line / LINES_PER_SNAPSHOT >= self.caches.len() {
+            let state = self
+                .caches
+                .last()
+                .cloned()
+                .unwrap_or_else(|| self.parse_state());
+            self.caches.push(state);
+        }
+        self.current_line += 1;
+
+        let gutter_len = self.gutter_len.min(line.len());
+        let mut highlights = vec![(0..gutter_len, Highlight(Some(self.gutter_color)))];
+        let Some(text) = line.get(gutter_len..) else {
+            return highlights.into_iter();
+        };
+
+        let highlighter = &self.highlighter;
+        let Some((parser, stack)) = self.caches.last_mut() else {
+            return highlights.into_iter();
+        };
+        let ops = parser.parse_line(text, &SYNTAXES).unwrap_or_default();
+
+        let mut push = |range: Range<usize>, stack: &ScopeStack| {
+            if !range.is_empty() {
+                let style = highlighter.style_for_stack(stack.as_slice());
+                let color = style.foreground;
+                highlights.push((
+                    range.start + gutter_len..range.end + gutter_len,
+                    Highlight(Some(Color::from_rgba8(
+                        color.r,
+                        color.g,
+                        color.b,
+                        f32::from(color.a) / 255.0,
+                    ))),
+                ));
+            }
+        };
+        let mut start = 0;
+        for (index, op) in ops {
+            push(start..index, stack);
+            start = index;
+            let _ = stack.apply(&op);
+        }
+        push(start..text.len(), stack);
+
+        highlights.into_iter()
+    }
+
+    fn current_line(&self) -> usize {
+        self.current_line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fmt::Write, fs};
+
+    use super::{MAX_LINES, TextPreview};
+
+    #[test]
+    fn loads_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lines.txt");
+        let mut text = String::new();
+        for i in 0..2500 {
+            writeln!(text, "line {i}").unwrap();
+        }
+        fs::write(&path, &text).unwrap();
+
+        let start = TextPreview::load(&path, 0).unwrap().unwrap();
+        assert_eq!(start.lines, MAX_LINES);
+        assert!(start.more);
+        assert!(start.text.starts_with("   1 │ line 0\n"));
+
+        let end = TextPreview::load(&path, 2000).unwrap().unwrap();
+        assert_eq!(end.first_line, 2000);
+        assert_eq!(end.lines, 500);
+        assert!(!end.more);
+        assert!(end.text.starts_with("2001 │ line 2000\n"));
+        assert!(end.text.ends_with("2500 │ line 2499"));
+
+        fs::write(&path, b"\x00\x01binary").unwrap();
+        assert!(TextPreview::load(&path, 0).unwrap().is_none());
+    }
+}
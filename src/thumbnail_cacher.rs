@@ -3,12 +3,12 @@ use md5::{Digest, Md5};
 use rustc_hash::FxHashMap;
 use std::{
     error::Error,
-    fs::{self, File},
+    fs::{self, File, FileTimes},
     io::{self, BufReader, BufWriter},
     os::unix::fs::PermissionsExt,
     path::{Path, PathBuf},
     sync::LazyLock,
-    time::UNIX_EPOCH,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use tempfile::NamedTempFile;
 use url::Url;
@@ -74,6 +74,7 @@ impl ThumbnailCacher {
 
         // Use cached thumbnail if it is valid.
         if self.is_thumbnail_valid(&self.thumbnail_path) {
+            touch_accessed(&self.thumbnail_path);
             return CachedThumbnail::Valid((
                 self.thumbnail_path.clone(),
                 Some(self.thumbnail_size),
@@ -290,6 +291,15 @@ impl ThumbnailCacher {
 
 fn thumbnail_uri(path: &Path) -> io::Result<String> {
     let absolute_path = fs::canonicalize(path)?;
+    // Other file managers see GVFS files by their native URI, like smb://server/share/file,
+    // rather than by the FUSE path. GIO maps the FUSE path back so the thumbnails are shared.
+    #[cfg(feature = "gvfs")]
+    if dirs::runtime_dir().is_some_and(|dir| absolute_path.starts_with(dir.join("gvfs"))) {
+        let uri = gio::prelude::FileExt::uri(&gio::File::for_path(&absolute_path));
+        if !uri.starts_with("file:") {
+            return Ok(uri.into());
+        }
+    }
     let url = Url::from_file_path(&absolute_path).map_err(|()| {
         io::Error::other(format!(
             "failed to create URI for thumbnail_file: {}",
@@ -345,6 +355,250 @@ impl ThumbnailSize {
     }
 }
 
+/// Size of the thumbnail cache
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub thumbnails: u64,
+    /// Total size of thumbnails in bytes
+    pub size: u64,
+    pub fail_markers: u64,
+}
+
+/// Thumbnails or fail markers removed by a cleanup
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CleanupStats {
+    pub removed: u64,
+    /// Bytes freed
+    pub freed: u64,
+}
+
+impl CleanupStats {
+    fn add(&mut self, other: Self) {
+        self.removed += other.removed;
+        self.freed += other.freed;
+    }
+}
+
+struct CacheEntry {
+    path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
+/// Count thumbnails and fail markers in the cache
+pub fn cache_stats() -> io::Result<CacheStats> {
+    cache_stats_in(cache_base_dir()?)
+}
+
+/// Remove thumbnails of local files that no longer exist, then remove least recently used
+/// thumbnails until the cache is no larger than `max_size` bytes
+pub fn clean_cache(max_size: u64) -> io::Result<CleanupStats> {
+    let base_dir = cache_base_dir()?;
+    let mut stats = purge_orphans_in(base_dir)?;
+    stats.add(enforce_max_size_in(base_dir, max_size)?);
+    Ok(stats)
+}
+
+/// Remove least recently used thumbnails until the cache is no larger than `max_size` bytes
+pub fn enforce_max_size(max_size: u64) -> io::Result<CleanupStats> {
+    enforce_max_size_in(cache_base_dir()?, max_size)
+}
+
+/// Remove fail markers created by any version of this application, so that failed thumbnails
+/// are tried again
+pub fn clear_fail_markers() -> io::Result<CleanupStats> {
+    clear_fail_markers_in(cache_base_dir()?)
+}
+
+fn cache_base_dir() -> io::Result<&'static Path> {
+    THUMBNAIL_CACHE_BASE_DIR
+        .as_deref()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no thumbnail cache directory"))
+}
+
+/// Directories containing fail markers of this application
+fn fail_marker_dirs(base_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let entries = match fs::read_dir(base_dir.join("fail")) {
+        Ok(ok) => ok,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(dirs),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        if entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("cosmic-files-")
+            && entry.file_type()?.is_dir()
+        {
+            dirs.push(entry.path());
+        }
+    }
+    Ok(dirs)
+}
+
+fn cache_entries(base_dir: &Path) -> io::Result<Vec<CacheEntry>> {
+    let mut cache_entries = Vec::new();
+    for size in [
+        ThumbnailSize::Normal,
+        ThumbnailSize::Large,
+        ThumbnailSize::XLarge,
+        ThumbnailSize::XXLarge,
+    ] {
+        let entries = match fs::read_dir(base_dir.join(size.subdirectory_name())) {
+            Ok(ok) => ok,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "png") {
+                continue;
+            }
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            // Access times may not be updated by the file system, so use the newest time
+            let last_used = match (metadata.accessed(), metadata.modified()) {
+                (Ok(accessed), Ok(modified)) => accessed.max(modified),
+                (Ok(time), Err(_)) | (Err(_), Ok(time)) => time,
+                (Err(_), Err(_)) => UNIX_EPOCH,
+            };
+            cache_entries.push(CacheEntry {
+                path,
+                size: metadata.len(),
+                last_used,
+            });
+        }
+    }
+    Ok(cache_entries)
+}
+
+fn cache_stats_in(base_dir: &Path) -> io::Result<CacheStats> {
+    let mut stats = CacheStats::default();
+    for entry in cache_entries(base_dir)? {
+        stats.thumbnails += 1;
+        stats.size += entry.size;
+    }
+    for dir in fail_marker_dirs(base_dir)? {
+        stats.fail_markers += fs::read_dir(dir)?.count() as u64;
+    }
+    Ok(stats)
+}
+
+fn purge_orphans_in(base_dir: &Path) -> io::Result<CleanupStats> {
+    let mut stats = CleanupStats::default();
+    for entry in cache_entries(base_dir)? {
+        let Some(uri) = thumbnail_source_uri(&entry.path) else {
+            continue;
+        };
+        // Only local files can be checked, other locations may just be unavailable
+        let Some(source_path) = Url::parse(&uri)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+        else {
+            continue;
+        };
+        // A missing parent folder may be an unmounted drive or network share
+        if !source_path.parent().is_some_and(Path::is_dir) {
+            continue;
+        }
+        if let Err(err) = fs::symlink_metadata(&source_path) {
+            if err.kind() == io::ErrorKind::NotFound {
+                remove_entry(&entry, &mut stats);
+            }
+        }
+    }
+    Ok(stats)
+}
+
+fn enforce_max_size_in(base_dir: &Path, max_size: u64) -> io::Result<CleanupStats> {
+    let mut stats = CleanupStats::default();
+    let mut entries = cache_entries(base_dir)?;
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    if total <= max_size {
+        return Ok(stats);
+    }
+    entries.sort_by_key(|entry| entry.last_used);
+    for entry in &entries {
+        if total <= max_size {
+            break;
+        }
+        if remove_entry(entry, &mut stats) {
+            total = total.saturating_sub(entry.size);
+        }
+    }
+    Ok(stats)
+}
+
+fn clear_fail_markers_in(base_dir: &Path) -> io::Result<CleanupStats> {
+    let mut stats = CleanupStats::default();
+    for dir in fail_marker_dirs(base_dir)? {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let size = entry.metadata().map_or(0, |metadata| metadata.len());
+            fs::remove_file(entry.path())?;
+            stats.removed += 1;
+            stats.freed += size;
+        }
+        fs::remove_dir(&dir)?;
+    }
+    Ok(stats)
+}
+
+fn remove_entry(entry: &CacheEntry, stats: &mut CleanupStats) -> bool {
+    match fs::remove_file(&entry.path) {
+        Ok(()) => {
+            stats.removed += 1;
+            stats.freed += entry.size;
+            true
+        }
+        Err(err) => {
+            log::warn!("failed to remove {}: {}", entry.path.display(), err);
+            false
+        }
+    }
+}
+
+/// Read the URI of the file a thumbnail was created for
+fn thumbnail_source_uri(thumbnail_path: &Path) -> Option<String> {
+    let file = File::open(thumbnail_path).ok()?;
+    let reader = png::Decoder::new(BufReader::new(file)).read_info().ok()?;
+    reader
+        .info()
+        .uncompressed_latin1_text
+        .iter()
+        .find(|text| text.keyword == "Thumb::URI")
+        .map(|text| text.text.clone())
+}
+
+/// Record that a thumbnail was used, as access times are often not updated by file systems.
+/// This is done at most once a day to avoid writes.
+fn touch_accessed(path: &Path) {
+    const INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+    let now = SystemTime::now();
+    let Ok(accessed) = fs::metadata(path).and_then(|metadata| metadata.accessed()) else {
+        return;
+    };
+    if now.duration_since(accessed).unwrap_or_default() < INTERVAL {
+        return;
+    }
+    if let Err(err) = File::options()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_times(FileTimes::new().set_accessed(now)))
+    {
+        log::debug!(
+            "failed to update access time of {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
 pub enum CachedThumbnail {
     /// The cached thumbnail is valid and should be used with size if known.
     Valid((PathBuf, Option<ThumbnailSize>)),
@@ -366,3 +620,94 @@ static THUMBNAIL_CACHE_BASE_DIR: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
 
     None
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_thumbnail(path: &Path, uri: &str, len: usize, last_used: SystemTime) {
+        {
+            let file = File::create(path).unwrap();
+            let mut encoder = png::Encoder::new(BufWriter::new(file), 1, 1);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder
+                .add_text_chunk("Thumb::URI".to_string(), uri.to_string())
+                .unwrap();
+            // Pad the file to the requested length
+            encoder
+                .add_text_chunk("Padding".to_string(), "x".repeat(len))
+                .unwrap();
+            encoder
+                .write_header()
+                .unwrap()
+                .write_image_data(&[0])
+                .unwrap();
+        }
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_times(
+                FileTimes::new()
+                    .set_accessed(last_used)
+                    .set_modified(last_used),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn purge_and_evict() {
+        let cache = tempfile::tempdir().unwrap();
+        let files = tempfile::tempdir().unwrap();
+        let normal = cache.path().join("normal");
+        let large = cache.path().join("large");
+        fs::create_dir_all(&normal).unwrap();
+        fs::create_dir_all(&large).unwrap();
+
+        let existing = files.path().join("existing.jpg");
+        fs::write(&existing, b"").unwrap();
+        let existing_uri = Url::from_file_path(&existing).unwrap().to_string();
+        let missing_uri = Url::from_file_path(files.path().join("missing.jpg"))
+            .unwrap()
+            .to_string();
+        let unmounted_uri = Url::from_file_path(files.path().join("unmounted").join("x.jpg"))
+            .unwrap()
+            .to_string();
+
+        let now = SystemTime::now();
+        let old = now - Duration::from_secs(3600);
+        write_thumbnail(&normal.join("a.png"), &existing_uri, 1000, old);
+        write_thumbnail(&large.join("b.png"), &existing_uri, 1000, now);
+        write_thumbnail(&normal.join("c.png"), &missing_uri, 1000, now);
+        write_thumbnail(&normal.join("d.png"), "smb://server/share/x.jpg", 1000, now);
+        write_thumbnail(&normal.join("f.png"), &unmounted_uri, 1000, now);
+
+        let fail_dir = cache.path().join("fail").join("cosmic-files-0.0.0");
+        fs::create_dir_all(&fail_dir).unwrap();
+        fs::write(fail_dir.join("e.png"), b"").unwrap();
+
+        let stats = cache_stats_in(cache.path()).unwrap();
+        assert_eq!(stats.thumbnails, 5);
+        assert_eq!(stats.fail_markers, 1);
+
+        // Only the thumbnail of the missing local file is removed
+        let purged = purge_orphans_in(cache.path()).unwrap();
+        assert_eq!(purged.removed, 1);
+        assert!(!normal.join("c.png").exists());
+        assert!(normal.join("d.png").exists());
+        assert!(normal.join("f.png").exists());
+
+        // The least recently used thumbnail is evicted first
+        let size = fs::metadata(normal.join("a.png")).unwrap().len();
+        let evicted = enforce_max_size_in(cache.path(), 3 * size).unwrap();
+        assert_eq!(evicted.removed, 1);
+        assert!(!normal.join("a.png").exists());
+        assert!(large.join("b.png").exists());
+
+        let cleared = clear_fail_markers_in(cache.path()).unwrap();
+        assert_eq!(cleared.removed, 1);
+        assert!(!fail_dir.exists());
+        assert_eq!(cache_stats_in(cache.path()).unwrap().thumbnails, 3);
+    }
+}
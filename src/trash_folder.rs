@@ -0,0 +1,516 @@
+// SPDX-License-Identifier: GPL-3.0-only
+
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+use std::{
+    ffi::OsString,
+    fs, io,
+    os::unix::{
+        ffi::{OsStrExt, OsStringExt},
+        fs::{DirBuilderExt, MetadataExt},
+    },
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use crate::fl;
+
+/// Days that trashed items can be kept for before they are deleted, selected in the settings
+pub const PURGE_DAYS: [Option<u32>; 6] = [None, Some(7), Some(14), Some(30), Some(60), Some(90)];
+
+/// Labels of the days that trashed items can be kept for
+pub static PURGE_NAMES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    PURGE_DAYS
+        .iter()
+        .map(|days_opt| match days_opt {
+            Some(days) => fl!("trash-purge-days", days = days),
+            None => fl!("trash-purge-never"),
+        })
+        .collect()
+});
+
+const DAY_SECS: i64 = 24 * 60 * 60;
+
+/// Whether an item trashed at `time_deleted` was trashed more than `days` days before `now`,
+/// both in seconds since the Unix epoch
+pub fn expired(time_deleted: i64, now: i64, days: u32) -> bool {
+    now.saturating_sub(time_deleted) > i64::from(days) * DAY_SECS
+}
+
+/// Contents of a trashed item, from the path of its `.trashinfo` file in a trash folder that
+/// follows the freedesktop trash specification
+pub fn item_file(info_path: &Path) -> Option<PathBuf> {
+    let trash_dir = info_path.parent()?.parent()?;
+    Some(trash_dir.join("files").join(info_path.file_stem()?))
+}
+
+/// Where a file is moved to when it is trashed
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TrashTarget {
+    /// The trash folder in the user's data directory, for files on the same volume
+    Home,
+    /// A trash folder in this top directory of another volume, created when needed
+    Volume(PathBuf),
+}
+
+/// Find where a file would be trashed, following the freedesktop trash specification. Returns
+/// `None` if its volume has no usable trash folder, so it can only be permanently deleted.
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+pub fn target(path: &Path) -> Option<TrashTarget> {
+    let dev = path.symlink_metadata().ok()?.dev();
+    if home_dev() == Some(dev) {
+        return Some(TrashTarget::Home);
+    }
+    let topdir = topdir(path, dev)?;
+    let uid = uzers::get_current_uid();
+    let usable = is_admin_trash(&topdir.join(".Trash"))
+        || topdir.join(format!(".Trash-{uid}")).is_dir()
+        || writable(&topdir);
+    usable.then_some(TrashTarget::Volume(topdir))
+}
+
+#[cfg(not(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+)))]
+pub fn target(_path: &Path) -> Option<TrashTarget> {
+    Some(TrashTarget::Home)
+}
+
+/// Move a file to the trash folder of its volume
+pub fn delete(path: &Path) -> anyhow::Result<()> {
+    match target(path) {
+        Some(TrashTarget::Home) => trash::delete(path)?,
+        #[cfg(all(
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        ))]
+        Some(TrashTarget::Volume(topdir)) => trash_to_volume(path, &topdir)?,
+        #[cfg(not(all(
+            unix,
+            not(target_os = "macos"),
+            not(target_os = "ios"),
+            not(target_os = "android")
+        )))]
+        Some(TrashTarget::Volume(_)) => unreachable!(),
+        None => anyhow::bail!("{} is on a drive without a trash folder", path.display()),
+    }
+    Ok(())
+}
+
+/// Move a file to the trash folder of the volume with the top directory `topdir`
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn trash_to_volume(path: &Path, topdir: &Path) -> io::Result<()> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} has no file name", path.display()),
+        )
+    })?;
+    let trash_dir = volume_trash_dir(topdir)?;
+    let files_dir = trash_dir.join("files");
+    let info_dir = trash_dir.join("info");
+    for dir in [&files_dir, &info_dir] {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+
+    // Paths in a volume trash are relative to its top directory, so they survive the volume
+    // being mounted somewhere else
+    let relative = path.strip_prefix(topdir).unwrap_or(path);
+    let info = trash_info(relative, chrono::Local::now().naive_local());
+    for n in 1.. {
+        let mut trash_name = name.to_os_string();
+        if n > 1 {
+            trash_name.push(format!(".{n}"));
+        }
+        let file_path = files_dir.join(&trash_name);
+        if file_path.symlink_metadata().is_ok() {
+            continue;
+        }
+        trash_name.push(".trashinfo");
+        let info_path = info_dir.join(&trash_name);
+        // Creating the info file reserves the name
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&info_path)
+        {
+            Ok(file) => {
+                let result = io::Write::write_all(&mut &file, info.as_bytes())
+                    .and_then(|()| fs::rename(path, &file_path));
+                if result.is_err() {
+                    let _ = fs::remove_file(&info_path);
+                }
+                return result;
+            }
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!()
+}
+
+/// List items in the trash folders of all mounted volumes other than the home volume
+#[cfg(target_os = "linux")]
+fn volume_items() -> Vec<trash::TrashItem> {
+    let mount_infos = match procfs::process::Process::myself().and_then(|p| p.mountinfo()) {
+        Ok(mount_infos) => mount_infos,
+        Err(err) => {
+            log::warn!("failed to get mount info: {err}");
+            return Vec::new();
+        }
+    };
+    let home_dev = home_dev();
+    let uid = uzers::get_current_uid();
+    let mut items = Vec::new();
+    for mount_info in mount_infos.iter() {
+        let topdir = &mount_info.mount_point;
+        match topdir.metadata() {
+            Ok(metadata) if Some(metadata.dev()) != home_dev => {}
+            _ => continue,
+        }
+        for trash_dir in [
+            topdir.join(".Trash").join(uid.to_string()),
+            topdir.join(format!(".Trash-{uid}")),
+        ] {
+            let Ok(entries) = fs::read_dir(trash_dir.join("info")) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let info_path = entry.path();
+                if info_path.extension().is_none_or(|ext| ext != "trashinfo") {
+                    continue;
+                }
+                let Ok(contents) = fs::read_to_string(&info_path) else {
+                    continue;
+                };
+                let Some((original_path, time_deleted)) = parse_trash_info(&contents, topdir)
+                else {
+                    log::warn!("failed to parse {}", info_path.display());
+                    continue;
+                };
+                let (Some(name), Some(original_parent)) =
+                    (original_path.file_name(), original_path.parent())
+                else {
+                    continue;
+                };
+                items.push(trash::TrashItem {
+                    id: info_path.clone().into_os_string(),
+                    name: name.to_os_string(),
+                    original_parent: original_parent.to_path_buf(),
+                    time_deleted,
+                });
+            }
+        }
+    }
+    items
+}
+
+/// List trashed items, including those in the trash folders of other mounted volumes
+// This config statement is from trash::os_limited
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+pub fn list() -> Result<Vec<trash::TrashItem>, trash::Error> {
+    #[allow(unused_mut)]
+    let mut items = trash::os_limited::list()?;
+    #[cfg(target_os = "linux")]
+    {
+        let ids: rustc_hash::FxHashSet<_> = items.iter().map(|item| item.id.clone()).collect();
+        items.extend(
+            volume_items()
+                .into_iter()
+                .filter(|item| !ids.contains(&item.id)),
+        );
+    }
+    Ok(items)
+}
+
+/// Permanently delete items that were trashed more than `days` days ago, returning how many
+/// items were deleted
+// This config statement is from trash::os_limited
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+pub fn purge(days: u32) -> Result<usize, trash::Error> {
+    let now = chrono::Utc::now().timestamp();
+    let items: Vec<_> = list()?
+        .into_iter()
+        .filter(|item| expired(item.time_deleted, now, days))
+        .collect();
+    let count = items.len();
+    if count > 0 {
+        trash::os_limited::purge_all(items)?;
+    }
+    Ok(count)
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+pub fn purge(_days: u32) -> Result<usize, trash::Error> {
+    Ok(0)
+}
+
+/// Device of the home trash folder
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn home_dev() -> Option<u64> {
+    Some(dirs::data_dir()?.metadata().ok()?.dev())
+}
+
+/// Top directory of the volume with device `dev` that contains `path`
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn topdir(path: &Path, dev: u64) -> Option<PathBuf> {
+    let mut topdir = None;
+    for ancestor in path.ancestors().skip(1) {
+        match ancestor.metadata() {
+            Ok(metadata) if metadata.dev() == dev => topdir = Some(ancestor),
+            _ => break,
+        }
+    }
+    topdir.map(Path::to_path_buf)
+}
+
+/// Whether `$topdir/.Trash` was set up by an administrator for all users: a real directory with
+/// the sticky bit set
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn is_admin_trash(path: &Path) -> bool {
+    path.symlink_metadata().is_ok_and(|metadata| {
+        metadata.is_dir() && !metadata.is_symlink() && metadata.mode() & 0o1000 != 0
+    })
+}
+
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn writable(path: &Path) -> bool {
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    unsafe { libc::access(c_path.as_ptr(), libc::W_OK) == 0 }
+}
+
+/// Trash folder of the current user on a volume, `$topdir/.Trash/$uid` if an administrator set up
+/// `$topdir/.Trash`, or else `$topdir/.Trash-$uid`. The folder is created if needed.
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn volume_trash_dir(topdir: &Path) -> io::Result<PathBuf> {
+    let uid = uzers::get_current_uid();
+    let create = |dir: &Path| match fs::DirBuilder::new().mode(0o700).create(dir) {
+        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => Err(err),
+        _ => Ok(()),
+    };
+
+    let admin_trash = topdir.join(".Trash");
+    if is_admin_trash(&admin_trash) {
+        let dir = admin_trash.join(uid.to_string());
+        match create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(err) => log::warn!("failed to create {}: {}", dir.display(), err),
+        }
+    }
+
+    let dir = topdir.join(format!(".Trash-{uid}"));
+    create(&dir)?;
+    if dir.symlink_metadata()?.is_symlink() {
+        return Err(io::Error::other(format!(
+            "{} is a symbolic link",
+            dir.display()
+        )));
+    }
+    Ok(dir)
+}
+
+/// Contents of a `.trashinfo` file for a file deleted at `deleted`
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn trash_info(path: &Path, deleted: chrono::NaiveDateTime) -> String {
+    // Paths are escaped like URIs, keeping slashes and unreserved characters
+    let mut escaped = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.!~*'()/".contains(&byte) {
+            escaped.push(char::from(byte));
+        } else {
+            escaped.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        escaped,
+        deleted.format("%Y-%m-%dT%H:%M:%S")
+    )
+}
+
+/// Original path and deletion time in seconds since the Unix epoch from the contents of a
+/// `.trashinfo` file in the trash folder of the volume with the top directory `topdir`
+#[cfg(all(
+    unix,
+    not(target_os = "macos"),
+    not(target_os = "ios"),
+    not(target_os = "android")
+))]
+fn parse_trash_info(contents: &str, topdir: &Path) -> Option<(PathBuf, i64)> {
+    let mut path_opt = None;
+    let mut time_opt = None;
+    for line in contents.lines() {
+        if let Some(value) = line.strip_prefix("Path=") {
+            let mut bytes = Vec::with_capacity(value.len());
+            let mut iter = value.bytes();
+            while let Some(byte) = iter.next() {
+                if byte == b'%' {
+                    let hex = [iter.next()?, iter.next()?];
+                    bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+                } else {
+                    bytes.push(byte);
+                }
+            }
+            path_opt = Some(topdir.join(PathBuf::from(OsString::from_vec(bytes))));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            let deleted =
+                chrono::NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%dT%H:%M:%S").ok()?;
+            time_opt = Some(
+                deleted
+                    .and_local_timezone(chrono::Local)
+                    .earliest()?
+                    .timestamp(),
+            );
+        }
+    }
+    Some((path_opt?, time_opt?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expires_after_days() {
+        let now = 100 * DAY_SECS;
+        assert!(!expired(now - 6 * DAY_SECS, now, 7));
+        assert!(!expired(now - 7 * DAY_SECS, now, 7));
+        assert!(expired(now - 7 * DAY_SECS - 1, now, 7));
+        // Items from the future are kept
+        assert!(!expired(now + DAY_SECS, now, 7));
+    }
+
+    #[test]
+    fn item_file_in_trash() {
+        assert_eq!(
+            item_file(Path::new(
+                "/home/user/.local/share/Trash/info/photo.jpg.trashinfo"
+            )),
+            Some(PathBuf::from(
+                "/home/user/.local/share/Trash/files/photo.jpg"
+            ))
+        );
+        assert_eq!(
+            item_file(Path::new("/media/usb/.Trash-1000/info/notes.2.trashinfo")),
+            Some(PathBuf::from("/media/usb/.Trash-1000/files/notes.2"))
+        );
+    }
+
+    #[cfg(all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    ))]
+    #[test]
+    fn trash_info_round_trip() {
+        let deleted = chrono::NaiveDate::from_ymd_opt(2024, 5, 20)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap();
+        let info = trash_info(Path::new("Photos/summer 100%.jpg"), deleted);
+        assert_eq!(
+            info,
+            "[Trash Info]\nPath=Photos/summer%20100%25.jpg\nDeletionDate=2024-05-20T09:30:00\n"
+        );
+
+        let (path, time_deleted) = parse_trash_info(&info, Path::new("/media/usb")).unwrap();
+        assert_eq!(path, PathBuf::from("/media/usb/Photos/summer 100%.jpg"));
+        assert_eq!(
+            time_deleted,
+            deleted
+                .and_local_timezone(chrono::Local)
+                .earliest()
+                .unwrap()
+                .timestamp()
+        );
+
+        // Absolute paths are kept as they are
+        let (path, _) = parse_trash_info(
+            "[Trash Info]\nPath=/media/usb/a.txt\nDeletionDate=2024-05-20T09:30:00\n",
+            Path::new("/media/usb"),
+        )
+        .unwrap();
+        assert_eq!(path, PathBuf::from("/media/usb/a.txt"));
+    }
+}